@@ -4,7 +4,7 @@
 use std::time::Instant;
 
 use crate::orderbook::OrderBook;
-use crate::types::{Order, OrderType, Side};
+use crate::types::{Order, OrderId, OrderType, Price, Quantity, Side};
 
 /// Benchmark the orderbook with a variety of operations
 #[cfg(feature = "perf")]
@@ -19,6 +19,11 @@ pub fn benchmark_orderbook() {
     bench_cancellation(&mut book);
     bench_market_depth(&mut book);
     bench_mixed_workload(&mut book);
+    bench_level_spill();
+    bench_best_level_recompute();
+    bench_batch_submission();
+    bench_deep_level_matching();
+    bench_sparse_depth();
 }
 
 /// Benchmark order insertion
@@ -35,19 +40,17 @@ fn bench_insertion(book: &mut OrderBook) {
         let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
         let price = if side == Side::Buy {
             // Buy orders from 9990 to 10000
-            9990 + (i as u64 % 100)
+            9990 + (i % 100) as Price
         } else {
             // Sell orders from 10000 to 10010
-            10000 + (i as u64 % 100)
+            10000 + (i % 100) as Price
         };
 
-        orders.push(Order::new(i as u64, price, 100, side, OrderType::Limit));
+        orders.push(Order::new(i as OrderId, price, 100, side, OrderType::Limit));
     }
 
     // Clear the book first
-    for i in 0..book.summary().order_count {
-        let _ = book.cancel_order(i as u64);
-    }
+    book.clear();
 
     // Measure insertion time
     let start = Instant::now();
@@ -75,14 +78,12 @@ fn bench_matching(book: &mut OrderBook) {
     let order_count = 10_000;
 
     // Clear the book first
-    for i in 0..book.summary().order_count {
-        let _ = book.cancel_order(i as u64);
-    }
+    book.clear();
 
     // Add buy orders to the book at different price levels
     for i in 0..order_count {
-        let price = 9500 + (i % 100) as u64; // Prices from 9500 to 9599
-        let order = Order::new(i as u64, price, 100, Side::Buy, OrderType::Limit);
+        let price = 9500 + (i % 100) as Price; // Prices from 9500 to 9599
+        let order = Order::new(i as OrderId, price, 100, Side::Buy, OrderType::Limit);
 
         let _ = book.add_order(order);
     }
@@ -94,7 +95,7 @@ fn bench_matching(book: &mut OrderBook) {
     for i in 0..match_count {
         let price = 9450; // Price below all buy orders, ensuring matches
         let order = Order::new(
-            (order_count + i) as u64,
+            (order_count + i) as OrderId,
             price,
             100,
             Side::Sell,
@@ -147,20 +148,18 @@ fn bench_cancellation(book: &mut OrderBook) {
     let order_count = 100_000;
 
     // Clear the book first
-    for i in 0..book.summary().order_count {
-        let _ = book.cancel_order(i as u64);
-    }
+    book.clear();
 
     // Add orders to the book
     for i in 0..order_count {
         let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
         let price = if side == Side::Buy {
-            99_900 + (i as u64 % 100)
+            99_900 + (i % 100) as Price
         } else {
-            100_000 + (i as u64 % 100)
+            100_000 + (i % 100) as Price
         };
 
-        let order = Order::new(i as u64, price, 100, side, OrderType::Limit);
+        let order = Order::new(i as OrderId, price, 100, side, OrderType::Limit);
 
         let _ = book.add_order(order);
     }
@@ -168,7 +167,7 @@ fn bench_cancellation(book: &mut OrderBook) {
     // Measure cancellation time
     let start = Instant::now();
     for i in 0..order_count {
-        let _ = book.cancel_order(i as u64);
+        let _ = book.cancel_order(i as OrderId);
     }
     let elapsed = start.elapsed();
     let ops_per_second = order_count as f64 / elapsed.as_secs_f64();
@@ -191,20 +190,18 @@ fn bench_market_depth(book: &mut OrderBook) {
     let order_count = 10_000;
 
     // Clear the book first
-    for i in 0..book.summary().order_count {
-        let _ = book.cancel_order(i as u64);
-    }
+    book.clear();
 
     // Add orders to the book across many price levels
     for i in 0..order_count {
         let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
         let price = if side == Side::Buy {
-            99_000 + (i as u64 % 1000)
+            99_000 + (i % 1000) as Price
         } else {
-            100_000 + (i as u64 % 1000)
+            100_000 + (i % 1000) as Price
         };
 
-        let order = Order::new(i as u64, price, 100, side, OrderType::Limit);
+        let order = Order::new(i as OrderId, price, 100, side, OrderType::Limit);
 
         let _ = book.add_order(order);
     }
@@ -238,9 +235,7 @@ fn bench_mixed_workload(book: &mut OrderBook) {
     println!("\n>> Testing Mixed Workload Performance");
 
     // Clear the book first
-    for i in 0..book.summary().order_count {
-        let _ = book.cancel_order(i as u64);
-    }
+    book.clear();
 
     // Parameters for the test
     let total_operations = 100_000;
@@ -269,17 +264,18 @@ fn bench_mixed_workload(book: &mut OrderBook) {
             0 => {
                 // Insert a limit order
                 let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
-                let jitter = (i % 20) as u64;
+                let jitter = i % 20;
 
                 let price = if side == Side::Buy {
-                    99_90 + jitter
+                    9_990 + jitter as Price
                 } else {
-                    100_00 + jitter
+                    10_000 + jitter as Price
                 };
+                let quantity = 100 + jitter as Quantity;
 
-                let order = Order::new(next_order_id, price, 100 + jitter, side, OrderType::Limit);
+                let order = Order::new(next_order_id, price, quantity, side, OrderType::Limit);
 
-                if let Ok(_) = book.add_order(order) {
+                if book.add_order(order).is_ok() {
                     live_orders.push(next_order_id);
                     next_order_id += 1;
                 }
@@ -296,7 +292,7 @@ fn bench_mixed_workload(book: &mut OrderBook) {
             2 => {
                 // Submit a market order
                 let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
-                let quantity = 100 + (i % 10) as u64;
+                let quantity = 100 + (i % 10) as Quantity;
 
                 let order = Order::new(
                     next_order_id,
@@ -332,7 +328,230 @@ fn bench_mixed_workload(book: &mut OrderBook) {
     println!("\nFinal orderbook state:\n{}", summary);
 }
 
+/// Benchmark a single very deep price level with and without a capped inline queue
+#[cfg(feature = "perf")]
+fn bench_level_spill() {
+    println!("\n>> Testing Deep Level With and Without Spill");
+
+    let order_count = 20_000;
+    let sweep_count = 10;
+
+    for inline_capacity in [None, Some(256)] {
+        let mut book = OrderBook::new("BTC-USD", order_count * 2);
+        book.set_level_inline_capacity(inline_capacity);
+
+        // Every resting order sits at the same price
+        let start = Instant::now();
+        for i in 0..order_count {
+            let order = Order::new(i as OrderId, 9_900, 100, Side::Buy, OrderType::Limit);
+            let _ = book.add_order(order);
+        }
+        let insert_elapsed = start.elapsed();
+
+        // Sweep the level in a handful of large market orders
+        let sweep_quantity = (order_count / sweep_count) as Quantity * 100;
+        let start = Instant::now();
+        for i in 0..sweep_count {
+            let order = Order::new(
+                (order_count + i) as OrderId,
+                0,
+                sweep_quantity,
+                Side::Sell,
+                OrderType::Market,
+            );
+            let _ = book.add_order(order);
+        }
+        let sweep_elapsed = start.elapsed();
+
+        println!(
+            "Inline capacity {:?}: inserted {} orders in {:?}, swept in {:?}",
+            inline_capacity, order_count, insert_elapsed, sweep_elapsed
+        );
+    }
+}
+
+/// Benchmark recomputing the best price as the top level empties, against a
+/// linear scan of the same level occupancy
+#[cfg(feature = "perf")]
+fn bench_best_level_recompute() {
+    println!("\n>> Testing Best Level Recompute");
+
+    let level_count = 1000;
+    let rounds = 100;
+
+    // One order per bid level, cancelled best-first so every cancel empties the top
+    let mut elapsed = std::time::Duration::ZERO;
+    for _ in 0..rounds {
+        let mut book = OrderBook::new("BTC-USD", level_count);
+        for i in 0..level_count {
+            let order = Order::new(
+                i as OrderId,
+                9_999 - i as Price,
+                100,
+                Side::Buy,
+                OrderType::Limit,
+            );
+            let _ = book.add_order(order);
+        }
+
+        let start = Instant::now();
+        for i in 0..level_count {
+            let _ = book.cancel_order(i as OrderId);
+        }
+        elapsed += start.elapsed();
+    }
+    let cancels = (level_count * rounds) as f64;
+
+    // The scan the bitmap replaces: probe from the front for the next occupied level
+    let mut levels: Vec<Option<u64>> = (0..1024).map(|_| None).collect();
+    let mut scan_elapsed = std::time::Duration::ZERO;
+    for _ in 0..rounds {
+        for (idx, level) in levels.iter_mut().enumerate().take(level_count) {
+            *level = Some(idx as u64);
+        }
+
+        let start = Instant::now();
+        for idx in 0..level_count {
+            levels[idx] = None;
+            std::hint::black_box(levels.iter().position(|level| level.is_some()));
+        }
+        scan_elapsed += start.elapsed();
+    }
+
+    println!(
+        "Bitmap-backed cancel of best level: {:.2} ns",
+        elapsed.as_nanos() as f64 / cancels
+    );
+    println!(
+        "Linear best-level scan alone: {:.2} ns",
+        scan_elapsed.as_nanos() as f64 / cancels
+    );
+}
+
 /// Run a long-running benchmark (minimum 1 minute) with a mixed workload
+#[cfg(feature = "perf")]
+fn bench_batch_submission() {
+    println!("\n>> Testing Batch vs Individual Submission");
+
+    let order_count = 100_000u64;
+
+    // Resting bids interleaved with market sells that take some of them
+    let workload = || {
+        (0..order_count).map(|i| {
+            if i % 4 == 3 {
+                Order::new(i as OrderId, 0, 250, Side::Sell, OrderType::Market)
+            } else {
+                Order::new(
+                    i as OrderId,
+                    9_990 - (i % 10) as Price,
+                    100,
+                    Side::Buy,
+                    OrderType::Limit,
+                )
+            }
+        })
+    };
+
+    let mut book = OrderBook::new("BTC-USD", order_count as usize);
+    let start = Instant::now();
+    for order in workload() {
+        let _ = book.add_order(order);
+    }
+    let individual_elapsed = start.elapsed();
+
+    let mut book = OrderBook::new("BTC-USD", order_count as usize);
+    let start = Instant::now();
+    let outcomes = book.add_orders(workload());
+    let batch_elapsed = start.elapsed();
+    std::hint::black_box(outcomes);
+
+    println!(
+        "add_order loop: {:.2} ns/order",
+        individual_elapsed.as_nanos() as f64 / order_count as f64
+    );
+    println!(
+        "add_orders batch: {:.2} ns/order",
+        batch_elapsed.as_nanos() as f64 / order_count as f64
+    );
+}
+
+#[cfg(feature = "perf")]
+fn bench_deep_level_matching() {
+    println!("\n>> Testing Small Fills Against a Deep Level");
+
+    // Each fill only touches the front of the queue, so its cost should not
+    // depend on how many orders wait behind it
+    for depth in [100u64, 10_000] {
+        let mut book = OrderBook::new("BTC-USD", depth as usize * 2);
+        for i in 0..depth {
+            let order = Order::new(i as OrderId, 9_900, 100, Side::Buy, OrderType::Limit);
+            let _ = book.add_order(order);
+        }
+
+        let fills = depth / 2;
+        let start = Instant::now();
+        for i in 0..fills {
+            let order = Order::new(
+                (depth + i) as OrderId,
+                0,
+                100,
+                Side::Sell,
+                OrderType::Market,
+            );
+            let _ = book.add_order(order);
+        }
+        let elapsed = start.elapsed();
+
+        println!(
+            "Level depth {}: {:.2} ns per front fill",
+            depth,
+            elapsed.as_nanos() as f64 / fills as f64
+        );
+    }
+}
+
+#[cfg(feature = "perf")]
+fn bench_sparse_depth() {
+    println!("\n>> Testing Market Depth on a Sparse Wide Book");
+
+    // Only the far end of each side is occupied; the query should cost the
+    // same as on a book quoting right at the base price
+    let iterations = 100_000;
+    for (label, offset) in [("near base", 1), ("index ~900", 900)] {
+        let mut book = OrderBook::new("BTC-USD", 1_000);
+        for i in 0..10 {
+            let buy = Order::new(
+                i,
+                10_000 - offset - i as Price,
+                100,
+                Side::Buy,
+                OrderType::Limit,
+            );
+            let sell = Order::new(
+                100 + i,
+                10_000 + offset + i as Price,
+                100,
+                Side::Sell,
+                OrderType::Limit,
+            );
+            let _ = book.add_order(buy);
+            let _ = book.add_order(sell);
+        }
+
+        let start = Instant::now();
+        for _ in 0..iterations {
+            std::hint::black_box(book.market_depth(10));
+        }
+        let elapsed = start.elapsed();
+
+        println!(
+            "Depth with orders {}: {:.2} ns per query",
+            label,
+            elapsed.as_nanos() as f64 / iterations as f64
+        );
+    }
+}
+
 pub fn benchmark_long_running(book: &mut OrderBook) {
     println!("\n>> Starting Long-Running Mixed Workload Benchmark (1+ minute)");
     println!("This benchmark simulates realistic market activity under sustained load");
@@ -352,7 +571,7 @@ pub fn benchmark_long_running(book: &mut OrderBook) {
     let price_levels = 200; // Number of distinct price levels to use
 
     // Tracking variables
-    let mut next_order_id: u64 = 0;
+    let mut next_order_id: OrderId = 0;
     let mut live_orders = Vec::with_capacity(1_000_000); // Orders that can be cancelled
     let mut total_operations = 0;
     let mut total_inserts = 0;
@@ -371,19 +590,19 @@ pub fn benchmark_long_running(book: &mut OrderBook) {
     let seed_count = 10_000;
     for i in 0..seed_count {
         let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
-        let price_offset = (i % price_levels) as u64;
+        let price_offset = i % price_levels;
 
         let price = if side == Side::Buy {
-            min_price + price_offset * ((max_price - min_price) / price_levels as u64)
+            min_price + price_offset * ((max_price - min_price) / price_levels)
         } else {
-            max_price - price_offset * ((max_price - min_price) / price_levels as u64)
+            max_price - price_offset * ((max_price - min_price) / price_levels)
         };
 
-        let quantity = 100 + (i % 10) * 10;
+        let quantity = 100 + (i % 10) as Quantity * 10;
 
         let order = Order::new(next_order_id, price, quantity, side, OrderType::Limit);
 
-        if let Ok(_) = book.add_order(order) {
+        if book.add_order(order).is_ok() {
             live_orders.push(next_order_id);
             next_order_id += 1;
             total_operations += 1;
@@ -423,15 +642,15 @@ pub fn benchmark_long_running(book: &mut OrderBook) {
                 } else {
                     Side::Sell
                 };
-                let price_offset = rand::random::<u64>() % price_levels as u64;
+                let price_offset = rand::random::<Price>() % price_levels;
 
                 let price = if side == Side::Buy {
-                    min_price + price_offset * ((max_price - min_price) / price_levels as u64)
+                    min_price + price_offset * ((max_price - min_price) / price_levels)
                 } else {
-                    max_price - price_offset * ((max_price - min_price) / price_levels as u64)
+                    max_price - price_offset * ((max_price - min_price) / price_levels)
                 };
 
-                let quantity = 100 + (rand::random::<u64>() % 10) * 10;
+                let quantity = 100 + (rand::random::<Quantity>() % 10) * 10;
 
                 let order = Order::new(next_order_id, price, quantity, side, OrderType::Limit);
 
@@ -452,7 +671,7 @@ pub fn benchmark_long_running(book: &mut OrderBook) {
                     let order_id = live_orders[idx];
 
                     let start = std::time::Instant::now();
-                    if let Ok(_) = book.cancel_order(order_id) {
+                    if book.cancel_order(order_id).is_ok() {
                         cancel_time += start.elapsed();
                         live_orders.swap_remove(idx);
                         total_operations += 1;
@@ -471,7 +690,7 @@ pub fn benchmark_long_running(book: &mut OrderBook) {
                 } else {
                     Side::Sell
                 };
-                let quantity = 100 + (rand::random::<u64>() % 20) * 10; // Slightly larger for market orders
+                let quantity = 100 + (rand::random::<Quantity>() % 20) * 10; // Slightly larger for market orders
 
                 let order = Order::new(
                     next_order_id,
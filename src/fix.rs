@@ -0,0 +1,104 @@
+//! Minimal FIX NewOrderSingle decoding
+
+use alloc::string::{String, ToString};
+
+use crate::types::{Order, OrderId, OrderType, Price, Quantity, Side};
+
+const SOH: char = '\x01';
+
+const TAG_CL_ORD_ID: u32 = 11;
+const TAG_ORDER_QTY: u32 = 38;
+const TAG_ORD_TYPE: u32 = 40;
+const TAG_PRICE: u32 = 44;
+const TAG_SIDE: u32 = 54;
+const TAG_MSG_TYPE: u32 = 35;
+
+/// Error decoding a FIX message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FixError {
+    /// A field is not of the form `tag=value`
+    MalformedField(String),
+    /// A required tag is absent
+    MissingTag(u32),
+    /// A tag's value could not be parsed or is not supported
+    InvalidValue { tag: u32, value: String },
+    /// The message is not a NewOrderSingle (35=D)
+    WrongMsgType(String),
+}
+
+impl core::fmt::Display for FixError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FixError::MalformedField(field) => write!(f, "Malformed FIX field '{}'", field),
+            FixError::MissingTag(tag) => write!(f, "Missing required FIX tag {}", tag),
+            FixError::InvalidValue { tag, value } => {
+                write!(f, "Invalid value '{}' for FIX tag {}", value, tag)
+            }
+            FixError::WrongMsgType(msg_type) => {
+                write!(f, "Expected NewOrderSingle (35=D), got 35={}", msg_type)
+            }
+        }
+    }
+}
+
+impl core::error::Error for FixError {}
+
+/// Decode a SOH-delimited NewOrderSingle into an order
+///
+/// Reads ClOrdID (11, which must be numeric and becomes the order ID),
+/// OrderQty (38), OrdType (40: `1` market, `2` limit, `K` market-to-limit),
+/// Side (54: `1` buy, `2` sell) and, for limit orders, Price (44). A MsgType
+/// (35) other than `D` is rejected; every other tag, header and trailer
+/// included, is ignored.
+pub fn parse_new_order_single(msg: &str) -> Result<Order, FixError> {
+    let mut order_id = None;
+    let mut price = None;
+    let mut quantity = None;
+    let mut side = None;
+    let mut order_type = None;
+
+    for field in msg.split(SOH).filter(|field| !field.is_empty()) {
+        let (tag, value) = field
+            .split_once('=')
+            .and_then(|(tag, value)| Some((tag.parse::<u32>().ok()?, value)))
+            .ok_or_else(|| FixError::MalformedField(field.to_string()))?;
+        let invalid = || FixError::InvalidValue {
+            tag,
+            value: value.to_string(),
+        };
+
+        match tag {
+            TAG_MSG_TYPE if value != "D" => return Err(FixError::WrongMsgType(value.to_string())),
+            TAG_CL_ORD_ID => order_id = Some(value.parse::<OrderId>().map_err(|_| invalid())?),
+            TAG_PRICE => price = Some(value.parse::<Price>().map_err(|_| invalid())?),
+            TAG_ORDER_QTY => quantity = Some(value.parse::<Quantity>().map_err(|_| invalid())?),
+            TAG_SIDE => {
+                side = Some(match value {
+                    "1" => Side::Buy,
+                    "2" => Side::Sell,
+                    _ => return Err(invalid()),
+                })
+            }
+            TAG_ORD_TYPE => {
+                order_type = Some(match value {
+                    "1" => OrderType::Market,
+                    "2" => OrderType::Limit,
+                    "K" => OrderType::MarketToLimit,
+                    _ => return Err(invalid()),
+                })
+            }
+            _ => {}
+        }
+    }
+
+    let order_id = order_id.ok_or(FixError::MissingTag(TAG_CL_ORD_ID))?;
+    let quantity = quantity.ok_or(FixError::MissingTag(TAG_ORDER_QTY))?;
+    let side = side.ok_or(FixError::MissingTag(TAG_SIDE))?;
+    let order_type = order_type.ok_or(FixError::MissingTag(TAG_ORD_TYPE))?;
+    let price = match order_type {
+        OrderType::Market | OrderType::MarketToLimit => price.unwrap_or(0),
+        _ => price.ok_or(FixError::MissingTag(TAG_PRICE))?,
+    };
+
+    Ok(Order::new(order_id, price, quantity, side, order_type))
+}
@@ -0,0 +1,100 @@
+//! Order tape ingestion for backtesting
+
+use std::io::{BufRead, BufReader, Read};
+
+use crate::orderbook::OrderBook;
+use crate::types::{Order, OrderType, Quantity, Side};
+
+/// Outcome of feeding an order tape into a book
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplayStats {
+    /// Rows parsed into orders and submitted
+    pub orders_submitted: u64,
+    /// Submitted orders the book rejected
+    pub orders_rejected: u64,
+    /// Rows that could not be parsed and were skipped
+    pub malformed_rows: u64,
+    pub executions: u64,
+    pub quantity_matched: Quantity,
+}
+
+/// Submit every row of a CSV order tape to `book`
+///
+/// Rows are `order_id,price,quantity,side,type,timestamp`, where the side is
+/// `buy`/`sell` (or `B`/`S`) and the type is one of `limit`, `market`, `ioc`,
+/// `fok`, `stop_market`, `stop_limit`, `post_only` or `market_to_limit`,
+/// case-insensitively. A leading `order_id,...` header and blank lines are
+/// ignored. Rows that do not parse are counted and skipped; a read error ends
+/// the run early.
+pub fn replay_csv(reader: impl Read, book: &mut OrderBook) -> ReplayStats {
+    let mut stats = ReplayStats::default();
+
+    for (row, line) in BufReader::new(reader).split(b'\n').enumerate() {
+        let Ok(line) = line else {
+            break;
+        };
+        let Ok(line) = std::str::from_utf8(&line) else {
+            stats.malformed_rows += 1;
+            continue;
+        };
+        let line = line.trim();
+        if line.is_empty() || (row == 0 && line.starts_with("order_id")) {
+            continue;
+        }
+
+        let Some(order) = parse_row(line) else {
+            stats.malformed_rows += 1;
+            continue;
+        };
+        stats.orders_submitted += 1;
+        match book.add_order(order) {
+            Ok(executions) => {
+                stats.executions += executions.len() as u64;
+                stats.quantity_matched += executions.iter().map(|e| e.quantity).sum::<Quantity>();
+            }
+            Err(_) => stats.orders_rejected += 1,
+        }
+    }
+
+    stats
+}
+
+/// Parse one `order_id,price,quantity,side,type,timestamp` row
+fn parse_row(line: &str) -> Option<Order> {
+    let mut fields = line.split(',').map(str::trim);
+    let order_id = fields.next()?.parse().ok()?;
+    let price = fields.next()?.parse().ok()?;
+    let quantity = fields.next()?.parse().ok()?;
+    let side = parse_side(fields.next()?)?;
+    let order_type = parse_order_type(fields.next()?)?;
+    let timestamp = fields.next()?.parse().ok()?;
+    if fields.next().is_some() {
+        return None;
+    }
+
+    let mut order = Order::new(order_id, price, quantity, side, order_type);
+    order.timestamp = timestamp;
+    Some(order)
+}
+
+fn parse_side(field: &str) -> Option<Side> {
+    match field.to_ascii_lowercase().as_str() {
+        "buy" | "b" => Some(Side::Buy),
+        "sell" | "s" => Some(Side::Sell),
+        _ => None,
+    }
+}
+
+fn parse_order_type(field: &str) -> Option<OrderType> {
+    match field.to_ascii_lowercase().as_str() {
+        "limit" => Some(OrderType::Limit),
+        "market" => Some(OrderType::Market),
+        "ioc" => Some(OrderType::ImmediateOrCancel),
+        "fok" => Some(OrderType::FillOrKill),
+        "stop_market" => Some(OrderType::StopMarket),
+        "stop_limit" => Some(OrderType::StopLimit),
+        "post_only" => Some(OrderType::PostOnly),
+        "market_to_limit" => Some(OrderType::MarketToLimit),
+        _ => None,
+    }
+}
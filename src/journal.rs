@@ -0,0 +1,371 @@
+//! Write-ahead journal of book operations for audit and deterministic replay
+
+#[cfg(feature = "std")]
+use std::fs::{File, OpenOptions};
+#[cfg(feature = "std")]
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+
+use crate::orderbook::OrderRecord;
+#[cfg(feature = "std")]
+use crate::types::OrderType;
+use crate::types::{OrderId, Price, Quantity, Side};
+
+/// A mutating book operation as it was submitted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalOp {
+    /// An order submission, with the worst price of a protected market order
+    AddOrder {
+        order: OrderRecord,
+        worst_price: Option<Price>,
+    },
+    CancelOrder {
+        order_id: OrderId,
+    },
+    /// A bulk cancel of one side, one account or, with neither, everything
+    MassCancel {
+        side: Option<Side>,
+        account_id: Option<u64>,
+    },
+    /// A partial cancel of `quantity` from a resting order
+    Reduce {
+        order_id: OrderId,
+        quantity: Quantity,
+    },
+    Amend {
+        order_id: OrderId,
+        price: Price,
+        quantity: Quantity,
+    },
+    /// An expiry sweep at the given time
+    Expire {
+        now_ns: u64,
+    },
+    /// An expiry sweep and stop check against an external last price
+    Housekeeping {
+        now_ns: u64,
+        last_price: Price,
+    },
+    /// A limit order queued for the next auction
+    AuctionOrder {
+        order: OrderRecord,
+    },
+    /// A call auction over the queued auction orders
+    Uncross,
+}
+
+/// One journaled operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JournalEntry {
+    /// Position in the journal, starting at 1
+    pub sequence: u64,
+    /// When the operation was journaled, in nanoseconds
+    pub timestamp: u64,
+    pub op: JournalOp,
+}
+
+/// Durable sink for journal entries
+///
+/// Entries are appended once the book has validated the operation, just
+/// before it is applied, so rejected operations are not journaled. One that
+/// still fails while being applied, such as an add that finds the order pool
+/// exhausted, is journaled and fails the same way on replay.
+#[cfg(feature = "std")]
+pub trait Journal: Send + Sync {
+    /// Persist one entry
+    fn append(&mut self, entry: &JournalEntry) -> io::Result<()>;
+
+    /// Read back every entry in sequence order
+    fn entries(&mut self) -> io::Result<Vec<JournalEntry>>;
+}
+
+/// In-memory journal, mostly useful for tests
+#[cfg(feature = "std")]
+impl Journal for Vec<JournalEntry> {
+    fn append(&mut self, entry: &JournalEntry) -> io::Result<()> {
+        self.push(*entry);
+        Ok(())
+    }
+
+    fn entries(&mut self) -> io::Result<Vec<JournalEntry>> {
+        Ok(self.clone())
+    }
+}
+
+/// Journal stored as one line of text per entry
+///
+/// Lines are whitespace-separated fields, `-` marking an absent value:
+///
+/// ```text
+/// <sequence> <timestamp> ADD <order_id> <price> <quantity> <hidden> <display>
+///     <B|S> <type code> <attributes> <order timestamp> <stop price> <expires_at>
+///     <account_id> <worst_price> <min_quantity>
+/// <sequence> <timestamp> CANCEL <order_id>
+/// <sequence> <timestamp> MASSCANCEL <B|S|-> <account_id>
+/// <sequence> <timestamp> REDUCE <order_id> <quantity>
+/// <sequence> <timestamp> AMEND <order_id> <price> <quantity>
+/// <sequence> <timestamp> EXPIRE <now_ns>
+/// <sequence> <timestamp> HOUSEKEEPING <now_ns> <last_price>
+/// <sequence> <timestamp> AUCTION <order fields as for ADD, worst_price `-`>
+/// <sequence> <timestamp> UNCROSS
+/// ```
+///
+/// The type code is the [`OrderType`] discriminant. The attributes are `R`
+/// for a reduce-only order, `H` for a hidden one and `A` for all-or-none, in
+/// that order, or `-` for none of them.
+#[cfg(feature = "std")]
+pub struct FileJournal {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+#[cfg(feature = "std")]
+impl FileJournal {
+    /// Open the journal at `path`, creating it if needed and appending to any
+    /// entries already there
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self {
+            path,
+            writer: BufWriter::new(file),
+        })
+    }
+
+    /// Write buffered entries through to the file
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Journal for FileJournal {
+    fn append(&mut self, entry: &JournalEntry) -> io::Result<()> {
+        writeln!(self.writer, "{}", format_entry(entry))
+    }
+
+    fn entries(&mut self) -> io::Result<Vec<JournalEntry>> {
+        self.flush()?;
+        let reader = BufReader::new(File::open(&self.path)?);
+        let mut entries = Vec::new();
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = parse_entry(&line).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("malformed journal line {}: {}", number + 1, line),
+                )
+            })?;
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(feature = "std")]
+fn format_optional(value: Option<impl core::fmt::Display>) -> String {
+    value.map_or_else(|| "-".to_string(), |value| value.to_string())
+}
+
+/// The attribute field for (reduce-only, hidden, all-or-none)
+#[cfg(feature = "std")]
+fn format_attributes((reduce_only, hidden, all_or_none): (bool, bool, bool)) -> String {
+    let letters: String = [(reduce_only, 'R'), (hidden, 'H'), (all_or_none, 'A')]
+        .into_iter()
+        .filter_map(|(set, letter)| set.then_some(letter))
+        .collect();
+    if letters.is_empty() {
+        "-".to_string()
+    } else {
+        letters
+    }
+}
+
+/// Read an attribute field back, accepting only the form `format_attributes`
+/// writes
+#[cfg(feature = "std")]
+fn parse_attributes(field: &str) -> Option<(bool, bool, bool)> {
+    let attributes = (
+        field.contains('R'),
+        field.contains('H'),
+        field.contains('A'),
+    );
+    (format_attributes(attributes) == field).then_some(attributes)
+}
+
+/// An order line, shared by submissions and auction orders
+#[cfg(feature = "std")]
+fn format_order(
+    head: &str,
+    keyword: &str,
+    order: &OrderRecord,
+    worst_price: Option<Price>,
+) -> String {
+    format!(
+        "{} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {}",
+        head,
+        keyword,
+        order.order_id,
+        order.price,
+        order.quantity,
+        order.hidden_quantity,
+        order.display_quantity,
+        match order.side {
+            Side::Buy => "B",
+            Side::Sell => "S",
+        },
+        order.order_type as u8,
+        format_attributes((order.reduce_only, order.hidden, order.all_or_none)),
+        order.timestamp,
+        order.stop_price,
+        format_optional(order.expires_at),
+        format_optional(order.account_id),
+        format_optional(worst_price),
+        order.min_quantity,
+    )
+}
+
+#[cfg(feature = "std")]
+fn format_entry(entry: &JournalEntry) -> String {
+    let head = format!("{} {}", entry.sequence, entry.timestamp);
+    match entry.op {
+        JournalOp::AddOrder { order, worst_price } => {
+            format_order(&head, "ADD", &order, worst_price)
+        }
+        JournalOp::CancelOrder { order_id } => format!("{} CANCEL {}", head, order_id),
+        JournalOp::MassCancel { side, account_id } => format!(
+            "{} MASSCANCEL {} {}",
+            head,
+            match side {
+                Some(Side::Buy) => "B",
+                Some(Side::Sell) => "S",
+                None => "-",
+            },
+            format_optional(account_id),
+        ),
+        JournalOp::Reduce { order_id, quantity } => {
+            format!("{} REDUCE {} {}", head, order_id, quantity)
+        }
+        JournalOp::Amend {
+            order_id,
+            price,
+            quantity,
+        } => format!("{} AMEND {} {} {}", head, order_id, price, quantity),
+        JournalOp::Expire { now_ns } => format!("{} EXPIRE {}", head, now_ns),
+        JournalOp::Housekeeping { now_ns, last_price } => {
+            format!("{} HOUSEKEEPING {} {}", head, now_ns, last_price)
+        }
+        JournalOp::AuctionOrder { order } => format_order(&head, "AUCTION", &order, None),
+        JournalOp::Uncross => format!("{} UNCROSS", head),
+    }
+}
+
+#[cfg(feature = "std")]
+fn parse_number<T: core::str::FromStr>(fields: &[&str], idx: usize) -> Option<T> {
+    fields.get(idx)?.parse().ok()
+}
+
+/// Read a field written by `format_optional`
+#[cfg(feature = "std")]
+fn parse_optional<T: core::str::FromStr>(fields: &[&str], idx: usize) -> Option<Option<T>> {
+    match *fields.get(idx)? {
+        "-" => Some(None),
+        value => value.parse().ok().map(Some),
+    }
+}
+
+#[cfg(feature = "std")]
+fn parse_entry(line: &str) -> Option<JournalEntry> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let sequence = parse_number(&fields, 0)?;
+    let timestamp = parse_number(&fields, 1)?;
+    let (op, field_count) = match *fields.get(2)? {
+        keyword @ ("ADD" | "AUCTION") => {
+            let side = match *fields.get(8)? {
+                "B" => Side::Buy,
+                "S" => Side::Sell,
+                _ => return None,
+            };
+            let (reduce_only, hidden, all_or_none) = parse_attributes(fields.get(10)?)?;
+            let order = OrderRecord {
+                order_id: parse_number(&fields, 3)?,
+                price: parse_number(&fields, 4)?,
+                quantity: parse_number(&fields, 5)?,
+                hidden_quantity: parse_number(&fields, 6)?,
+                display_quantity: parse_number(&fields, 7)?,
+                side,
+                order_type: OrderType::from_code(fields.get(9)?.parse().ok()?)?,
+                reduce_only,
+                hidden,
+                all_or_none,
+                timestamp: parse_number(&fields, 11)?,
+                stop_price: parse_number(&fields, 12)?,
+                expires_at: parse_optional(&fields, 13)?,
+                account_id: parse_optional(&fields, 14)?,
+                min_quantity: parse_number(&fields, 16)?,
+            };
+            let worst_price = parse_optional(&fields, 15)?;
+            match (keyword, worst_price) {
+                ("ADD", _) => (JournalOp::AddOrder { order, worst_price }, 17),
+                (_, None) => (JournalOp::AuctionOrder { order }, 17),
+                _ => return None,
+            }
+        }
+        "CANCEL" => (
+            JournalOp::CancelOrder {
+                order_id: parse_number(&fields, 3)?,
+            },
+            4,
+        ),
+        "MASSCANCEL" => {
+            let side = match *fields.get(3)? {
+                "B" => Some(Side::Buy),
+                "S" => Some(Side::Sell),
+                "-" => None,
+                _ => return None,
+            };
+            let account_id = parse_optional(&fields, 4)?;
+            (JournalOp::MassCancel { side, account_id }, 5)
+        }
+        "REDUCE" => (
+            JournalOp::Reduce {
+                order_id: parse_number(&fields, 3)?,
+                quantity: parse_number(&fields, 4)?,
+            },
+            5,
+        ),
+        "AMEND" => (
+            JournalOp::Amend {
+                order_id: parse_number(&fields, 3)?,
+                price: parse_number(&fields, 4)?,
+                quantity: parse_number(&fields, 5)?,
+            },
+            6,
+        ),
+        "EXPIRE" => (
+            JournalOp::Expire {
+                now_ns: parse_number(&fields, 3)?,
+            },
+            4,
+        ),
+        "HOUSEKEEPING" => (
+            JournalOp::Housekeeping {
+                now_ns: parse_number(&fields, 3)?,
+                last_price: parse_number(&fields, 4)?,
+            },
+            5,
+        ),
+        "UNCROSS" => (JournalOp::Uncross, 3),
+        _ => return None,
+    };
+
+    (fields.len() == field_count).then_some(JournalEntry {
+        sequence,
+        timestamp,
+        op,
+    })
+}
@@ -3,23 +3,65 @@
 //! A microsecond-level performance order book implementation
 //! focused on speed, efficiency, and low latency.
 
-#![feature(portable_simd)]
+//!
+//! The matching core (`OrderBook`, `Order`, `PriceLevel`, `OrderPool`) only
+//! needs `alloc`. Without the default `std` feature the crate is `no_std`:
+//! the monotonic clock, the file journal, the benchmarks and the
+//! multi-book/shared wrappers are compiled out, and callers stamp orders
+//! themselves through [`OrderBook::add_order_at`].
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![feature(core_float_math)]
 
+extern crate alloc;
+
+#[cfg(feature = "std")]
 pub mod benchmarks;
+#[cfg(feature = "fix")]
+pub mod fix;
+#[cfg(feature = "io")]
+pub mod io;
+pub mod journal;
+#[cfg(feature = "std")]
+pub mod manager;
 pub mod memory;
 pub mod orderbook;
+#[cfg(feature = "std")]
+pub mod shared;
 pub mod types;
 
 #[cfg(feature = "perf")]
 pub use benchmarks::benchmark_orderbook;
-pub use memory::{OrderPool, PriceLookupTable};
-pub use orderbook::OrderBook;
-pub use types::{Execution, Order, OrderType, Side};
+#[cfg(feature = "fix")]
+pub use fix::{FixError, parse_new_order_single};
+#[cfg(feature = "io")]
+pub use io::{ReplayStats, replay_csv};
+#[cfg(feature = "std")]
+pub use journal::{FileJournal, Journal};
+pub use journal::{JournalEntry, JournalOp};
+#[cfg(feature = "std")]
+pub use manager::OrderBookManager;
+pub use memory::OrderPool;
+pub use orderbook::{
+    BboCallback, BookConfig, BookSnapshot, DepthCallback, ExecutionCallback, HousekeepingReport,
+    MarketOrderResult, MatchingPolicy, OrderBook, OrderOutcome, OrderRecord, RejectCallback,
+    SelfTradePolicy, SimResult, StatsCallback, StatsSnapshot, check_execution_prices,
+};
+#[cfg(feature = "std")]
+pub use shared::SharedOrderBook;
+pub use types::{
+    DepthLevel, DepthUpdate, Event, Execution, Order, OrderBuilder, OrderError, OrderId, OrderType,
+    OrderView, Price, Quantity, Side, vwap,
+};
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::types::{Order, OrderType, Side};
+    use alloc::format;
+    use alloc::string::{String, ToString};
+    use alloc::vec;
+    use alloc::vec::Vec;
 
     #[test]
     fn test_order_insertion() {
@@ -57,7 +99,6 @@ mod tests {
 
         // Check summary
         let summary = book.summary();
-        #[cfg(feature = "perf")]
         assert_eq!(summary.order_count, 2);
         assert_eq!(summary.buy_levels, 1);
         assert_eq!(summary.sell_levels, 1);
@@ -93,7 +134,7 @@ mod tests {
 
         // Check statistics
         let summary = book.summary();
-        assert_eq!(summary.total_quantity_matched, 10);
+        assert_eq!(summary.total_quantity_matched, 5);
     }
 
     #[test]
@@ -131,7 +172,7 @@ mod tests {
 
         // Check statistics
         let summary = book.summary();
-        assert_eq!(summary.total_quantity_matched, 15 * 2);
+        assert_eq!(summary.total_quantity_matched, 15);
     }
 
     #[test]
@@ -155,7 +196,6 @@ mod tests {
         assert_eq!(bids.len(), 0);
 
         // Check summary
-        #[cfg(feature = "perf")]
         assert_eq!(book.summary().order_count, 0);
     }
 
@@ -262,7 +302,7 @@ mod tests {
 
         // Check statistics
         let summary = book.summary();
-        assert_eq!(summary.total_quantity_matched, 30);
+        assert_eq!(summary.total_quantity_matched, 15);
     }
 
     #[test]
@@ -355,13 +395,12 @@ mod tests {
         }
 
         // Check that we have the expected number of orders
-        #[cfg(feature = "perf")]
         assert_eq!(book.summary().order_count, 200);
 
         // Check market depth
         let (bids, asks) = book.market_depth(10);
-        assert!(bids.len() > 0);
-        assert!(asks.len() > 0);
+        assert!(!bids.is_empty());
+        assert!(!asks.is_empty());
 
         // Check best bid/ask
         assert!(book.best_bid().is_some());
@@ -402,4 +441,3908 @@ mod tests {
         assert_eq!(bids[0], (9999, 5)); // 5 quantity remaining at price 10000
         assert_eq!(asks.len(), 0); // No asks remaining
     }
+
+    #[test]
+    fn test_execution_price_check() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        book.add_order(Order::new(1, 10010, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10020, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        // Real matching output respects the aggressor's limit
+        let executions = book
+            .add_order(Order::new(3, 10020, 15, Side::Buy, OrderType::Limit))
+            .unwrap();
+        assert!(check_execution_prices(Side::Buy, 10020, &executions).is_ok());
+
+        // A matching loop that filled a buy above its limit must be caught
+        let bad = vec![Execution {
+            order_id: 2,
+            price: 10030,
+            quantity: 5,
+            timestamp: 0,
+            side: Side::Sell,
+            trade_id: 1,
+            maker_order_id: 2,
+            taker_order_id: 3,
+            aggressor_side: Side::Buy,
+        }];
+        assert!(check_execution_prices(Side::Buy, 10020, &bad).is_err());
+
+        // Likewise a sell filled below its limit
+        let bad = vec![Execution {
+            order_id: 1,
+            price: 9890,
+            quantity: 5,
+            timestamp: 0,
+            side: Side::Buy,
+            trade_id: 1,
+            maker_order_id: 1,
+            taker_order_id: 3,
+            aggressor_side: Side::Sell,
+        }];
+        assert!(check_execution_prices(Side::Sell, 9900, &bad).is_err());
+        assert!(check_execution_prices(Side::Sell, 9890, &bad).is_ok());
+    }
+
+    #[test]
+    fn test_market_depth_typed() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        book.add_order(Order::new(1, 9900, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 9800, 20, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 10100, 30, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        let (bids, asks) = book.market_depth(10);
+        let (typed_bids, typed_asks) = book.market_depth_typed(10);
+
+        assert_eq!(bids.len(), typed_bids.len());
+        assert_eq!(asks.len(), typed_asks.len());
+
+        for (tuple, level) in bids.iter().zip(&typed_bids) {
+            assert_eq!(tuple.0, level.price);
+            assert_eq!(tuple.1, level.quantity);
+        }
+        for (tuple, level) in asks.iter().zip(&typed_asks) {
+            assert_eq!(tuple.0, level.price);
+            assert_eq!(tuple.1, level.quantity);
+        }
+
+        assert_eq!(DepthLevel::from((9900, 10)), typed_bids[0]);
+    }
+
+    #[test]
+    fn test_disable_kill_switch() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        book.add_order(Order::new(1, 9900, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+
+        book.disable();
+        assert!(book.is_disabled());
+
+        // New orders are rejected while disabled
+        let result = book.add_order(Order::new(2, 9800, 10, Side::Buy, OrderType::Limit));
+        assert!(result.is_err());
+        let result = book.add_order(Order::new(3, 0, 10, Side::Sell, OrderType::Market));
+        assert!(result.is_err());
+
+        // The book is intact and cancels still work
+        assert_eq!(book.best_bid(), Some(9900));
+        assert!(book.cancel_order(1).is_ok());
+        assert_eq!(book.best_bid(), None);
+
+        // Re-enabling restores adds
+        book.enable();
+        assert!(!book.is_disabled());
+        assert!(
+            book.add_order(Order::new(2, 9800, 10, Side::Buy, OrderType::Limit))
+                .is_ok()
+        );
+        assert_eq!(book.best_bid(), Some(9800));
+    }
+
+    #[test]
+    fn test_display_depth_rounding() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        // Fine-tick bids that round onto 10000 and 9995
+        book.add_order(Order::new(1, 9999, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 9998, 20, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 9996, 30, Side::Buy, OrderType::Limit))
+            .unwrap();
+
+        // Fine-tick asks that round onto 10000 and 10005
+        book.add_order(Order::new(4, 10001, 1, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(5, 10002, 2, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(6, 10004, 3, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(7, 10006, 4, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        let (bids, asks) = book.display_depth(10, 5);
+        assert_eq!(bids, vec![(10000, 30), (9995, 30)]);
+        assert_eq!(asks, vec![(10000, 3), (10005, 7)]);
+
+        // The level limit applies to displayed prices, not raw levels
+        let (bids, asks) = book.display_depth(1, 5);
+        assert_eq!(bids, vec![(10000, 30)]);
+        assert_eq!(asks, vec![(10000, 3)]);
+
+        // Rounding to a single tick is the raw depth
+        assert_eq!(book.display_depth(10, 1), book.market_depth(10));
+    }
+
+    #[test]
+    fn test_arrival_slippage() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        book.add_order(Order::new(1, 9990, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 9980, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 10010, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(4, 10030, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        // Mid is 10000; a buy of 20 fills 10 @ 10010 and 10 @ 10030
+        assert_eq!(book.expected_fill(Side::Buy, 20), Some(10020));
+        assert_eq!(
+            book.arrival_slippage(Side::Buy, 20),
+            Some((10000, 10020, 20))
+        );
+
+        // A sell of 20 fills 10 @ 9990 and 10 @ 9980, below mid
+        assert_eq!(
+            book.arrival_slippage(Side::Sell, 20),
+            Some((10000, 9985, -15))
+        );
+
+        // Insufficient liquidity
+        assert_eq!(book.arrival_slippage(Side::Buy, 21), None);
+
+        // The estimate leaves the book untouched
+        assert_eq!(book.market_depth(10).1, vec![(10010, 10), (10030, 10)]);
+    }
+
+    #[test]
+    fn test_order_pool_try_get() {
+        let mut pool = OrderPool::new(4);
+
+        let live = pool
+            .allocate(Order::new(1, 9900, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        let freed = pool
+            .allocate(Order::new(2, 9800, 20, Side::Buy, OrderType::Limit))
+            .unwrap();
+        pool.deallocate(freed);
+
+        assert_eq!(pool.try_get(live).map(|order| order.order_id), Some(1));
+        assert!(pool.try_get(freed).is_none());
+
+        // Never-allocated and out-of-range slots are not live either
+        assert!(pool.try_get(3).is_none());
+        assert!(pool.try_get(100).is_none());
+    }
+
+    #[test]
+    fn test_simulate_matches_real_outcome() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        book.add_order(Order::new(1, 10010, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10020, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 9990, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+
+        let order = Order::new(4, 10020, 15, Side::Buy, OrderType::Limit);
+        let simulated = book.simulate(order.clone()).unwrap();
+
+        // The real book is unchanged by the simulation
+        assert_eq!(book.market_depth(10).1, vec![(10010, 10), (10020, 10)]);
+        assert_eq!(book.summary().total_quantity_matched, 0);
+
+        let real = book.add_order(order).unwrap();
+        assert_eq!(simulated.len(), real.len());
+        for (sim, exec) in simulated.iter().zip(&real) {
+            assert_eq!(sim.order_id, exec.order_id);
+            assert_eq!(sim.price, exec.price);
+            assert_eq!(sim.quantity, exec.quantity);
+        }
+
+        // Rejections are simulated too
+        let duplicate = Order::new(3, 9980, 10, Side::Buy, OrderType::Limit);
+        assert!(book.simulate(duplicate).is_err());
+    }
+
+    #[test]
+    fn test_level_spill_preserves_priority() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.set_level_inline_capacity(Some(2));
+
+        // Five orders at one price: two inline, three spilled
+        for id in 1..=5 {
+            book.add_order(Order::new(id, 9900, 10, Side::Buy, OrderType::Limit))
+                .unwrap();
+        }
+        assert_eq!(book.market_depth(10).0, vec![(9900, 50)]);
+
+        // Matching walks inline orders first, then the spill queue in order
+        let executions = book
+            .add_order(Order::new(6, 0, 35, Side::Sell, OrderType::Market))
+            .unwrap();
+        let filled: Vec<(u64, u64)> = executions
+            .iter()
+            .map(|exec| (exec.order_id, exec.quantity))
+            .collect();
+        assert_eq!(filled, vec![(1, 10), (2, 10), (3, 10), (4, 5)]);
+
+        // The partially filled spilled order keeps its place at the front
+        let executions = book
+            .add_order(Order::new(7, 9900, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        assert_eq!(executions[0].order_id, 4);
+        assert_eq!(executions[0].quantity, 5);
+        assert_eq!(executions[1].order_id, 5);
+        assert_eq!(book.market_depth(10).0, vec![(9900, 5)]);
+    }
+
+    #[test]
+    fn test_price_level_spill_queue() {
+        let mut level = types::PriceLevel::with_inline_capacity(9900, 2);
+        for idx in 0..4 {
+            level.add_order(idx, 10);
+        }
+        assert_eq!(level.order_count(), 4);
+        assert_eq!(level.spilled_count(), 2);
+
+        // Freeing an inline slot promotes the oldest spilled order
+        assert!(level.unlink(0));
+        assert_eq!(level.spilled_count(), 1);
+        assert_eq!(level.iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+
+        // Cancelling a spilled order works too
+        assert!(level.remove_order(3, 10));
+        assert_eq!(level.iter().collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(level.total_quantity, 30);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_stats_callback_interval() {
+        use std::sync::{Arc, Mutex};
+
+        let mut book = OrderBook::new("TEST", 1000);
+        let snapshots = Arc::new(Mutex::new(Vec::new()));
+
+        let sink = Arc::clone(&snapshots);
+        book.on_stats(
+            3,
+            Box::new(move |snapshot: &StatsSnapshot| {
+                sink.lock().unwrap().push(snapshot.clone());
+            }),
+        );
+
+        // Seven adds and three cancels make ten operations
+        for id in 1..=7 {
+            book.add_order(Order::new(id, 9900 - id, 10, Side::Buy, OrderType::Limit))
+                .unwrap();
+        }
+        for id in 1..=3 {
+            book.cancel_order(id).unwrap();
+        }
+
+        {
+            let snapshots = snapshots.lock().unwrap();
+            assert_eq!(snapshots.len(), 3);
+            assert_eq!(snapshots[0].operations, 3);
+            assert_eq!(snapshots[2].operations, 9);
+            assert_eq!(snapshots[2].sample_count, 3);
+            assert!(snapshots[2].p50_latency_ns <= snapshots[2].p99_latency_ns);
+            assert!(snapshots[2].p99_latency_ns <= snapshots[2].max_latency_ns);
+            assert_eq!(snapshots[2].summary.buy_levels, 5);
+        }
+
+        // On-demand snapshots cover the operations since the last one
+        book.emit_stats();
+        let snapshots = snapshots.lock().unwrap();
+        assert_eq!(snapshots.len(), 4);
+        assert_eq!(snapshots[3].operations, 10);
+        assert_eq!(snapshots[3].sample_count, 1);
+    }
+
+    #[test]
+    fn test_min_cross_increment() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.set_min_cross_increment(5);
+
+        book.add_order(Order::new(1, 10010, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 9990, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+
+        // A sell overlapping the bid by less than the increment does not trade.
+        // It cannot rest below the base price either, so it is rejected.
+        let result = book.add_order(Order::new(3, 9987, 5, Side::Sell, OrderType::Limit));
+        assert!(result.is_err());
+        assert_eq!(book.market_depth(10).0, vec![(9990, 10)]);
+
+        // Nor does a buy overlapping the ask by less than the increment
+        let result = book.add_order(Order::new(4, 10012, 5, Side::Buy, OrderType::Limit));
+        assert!(result.is_err());
+        assert_eq!(book.market_depth(10).1, vec![(10010, 10)]);
+        assert_eq!(book.summary().total_quantity_matched, 0);
+
+        // An overlap of exactly one increment trades at the resting price
+        let executions = book
+            .add_order(Order::new(5, 9985, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].order_id, 2);
+        assert_eq!(executions[0].price, 9990);
+    }
+
+    #[test]
+    fn test_summary_side_order_counts() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        book.add_order(Order::new(1, 9900, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 9900, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 9800, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(4, 10100, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(5, 10200, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        let summary = book.summary();
+        assert_eq!(summary.total_orders, 5);
+        assert_eq!(summary.bid_orders, 3);
+        assert_eq!(summary.ask_orders, 2);
+
+        // Fill one bid fully and another partially
+        book.add_order(Order::new(6, 9900, 15, Side::Sell, OrderType::Limit))
+            .unwrap();
+        // Sweep one ask with a market order
+        book.add_order(Order::new(7, 0, 10, Side::Buy, OrderType::Market))
+            .unwrap();
+        // Cancel a bid
+        book.cancel_order(3).unwrap();
+
+        let summary = book.summary();
+        assert_eq!(summary.bid_orders, 1);
+        assert_eq!(summary.ask_orders, 1);
+        assert_eq!(summary.total_orders, 2);
+    }
+
+    #[test]
+    fn test_fair_value_lopsided_book() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        // Heavy bids, thin asks
+        book.add_order(Order::new(1, 9990, 100, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 9980, 300, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 10010, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(4, 10020, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        let mid = book.mid_price().unwrap();
+        let fair = book.fair_value(2).unwrap();
+
+        // Buying pressure from the heavier bid side lifts fair value above mid
+        assert!(fair > mid);
+        assert!(fair < 10020.0);
+
+        // bid vwap 9982.5 x 20, ask vwap 10015 x 400
+        let expected = (9982.5 * 20.0 + 10015.0 * 400.0) / 420.0;
+        assert!((fair - expected).abs() < 1e-9);
+
+        // One level is the top-of-book microprice
+        let micro = (9990.0 * 10.0 + 10010.0 * 100.0) / 110.0;
+        assert!((book.fair_value(1).unwrap() - micro).abs() < 1e-9);
+
+        // An empty side has no fair value
+        let empty = OrderBook::new("EMPTY", 10);
+        assert_eq!(empty.fair_value(5), None);
+    }
+
+    #[test]
+    fn test_apply_event_reconstructs_book() {
+        // Source venue book driven by real order flow
+        let mut source = OrderBook::new("SRC", 1000);
+        let mut events = Vec::new();
+
+        let adds = [
+            Order::new(1, 9900, 10, Side::Buy, OrderType::Limit),
+            Order::new(2, 9900, 20, Side::Buy, OrderType::Limit),
+            Order::new(3, 9800, 30, Side::Buy, OrderType::Limit),
+            Order::new(4, 10100, 15, Side::Sell, OrderType::Limit),
+            Order::new(5, 10200, 25, Side::Sell, OrderType::Limit),
+            Order::new(6, 10300, 35, Side::Sell, OrderType::Limit),
+        ];
+        for order in adds {
+            source.add_order(order.clone()).unwrap();
+            events.push(Event::Add(order));
+        }
+
+        // An aggressive sell trades against orders 1 and 2
+        let executions = source
+            .add_order(Order::new(7, 9900, 15, Side::Sell, OrderType::Limit))
+            .unwrap();
+        for exec in &executions {
+            events.push(Event::Execution {
+                order_id: exec.order_id,
+                quantity: exec.quantity,
+            });
+        }
+
+        // Order 6 is cancelled and the 10100 level is pulled
+        source.cancel_order(6).unwrap();
+        events.push(Event::Cancel { order_id: 6 });
+        source.cancel_order(4).unwrap();
+        events.push(Event::LevelDelete {
+            side: Side::Sell,
+            price: 10100,
+        });
+
+        // Order 3 is modified down in size and order 5 re-priced
+        source.cancel_order(3).unwrap();
+        source
+            .add_order(Order::new(3, 9800, 12, Side::Buy, OrderType::Limit))
+            .unwrap();
+        events.push(Event::Modify {
+            order_id: 3,
+            price: 9800,
+            quantity: 12,
+        });
+        source.cancel_order(5).unwrap();
+        source
+            .add_order(Order::new(5, 10150, 25, Side::Sell, OrderType::Limit))
+            .unwrap();
+        events.push(Event::Modify {
+            order_id: 5,
+            price: 10150,
+            quantity: 25,
+        });
+
+        let mut mirror = OrderBook::new("MIRROR", 1000);
+        for event in events {
+            mirror.apply_event(event).unwrap();
+        }
+
+        assert_eq!(mirror.market_depth(10), source.market_depth(10));
+        assert_eq!(mirror.best_bid(), source.best_bid());
+        assert_eq!(mirror.best_ask(), source.best_ask());
+        assert_eq!(mirror.market_depth(10).0, vec![(9900, 15), (9800, 12)]);
+        assert_eq!(mirror.summary().total_quantity_matched, 15);
+
+        // Events for unknown orders are rejected
+        assert!(mirror.apply_event(Event::Cancel { order_id: 99 }).is_err());
+    }
+
+    #[test]
+    fn test_market_order_abandoned_mid_sweep() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        book.add_order(Order::new(1, 10010, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 10020, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        // The client pulls the order as soon as the first level is done
+        let mut checks = 0;
+        let result = book
+            .add_market_order_until(
+                Order::new(4, 0, 20, Side::Buy, OrderType::Market),
+                |executions| {
+                    checks += 1;
+                    !executions.is_empty()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(checks, 1);
+        assert_eq!(result.executions.len(), 2);
+        assert!(result.executions.iter().all(|exec| exec.price == 10010));
+        assert_eq!(result.unfilled_quantity, 5);
+
+        // The deeper level is untouched
+        assert_eq!(book.market_depth(10).1, vec![(10020, 10)]);
+
+        // A predicate that never fires sweeps as usual
+        let result = book
+            .add_market_order_until(Order::new(5, 0, 4, Side::Buy, OrderType::Market), |_| false)
+            .unwrap();
+        assert_eq!(result.unfilled_quantity, 0);
+
+        // Limit orders are rejected
+        let limit = Order::new(6, 10020, 1, Side::Buy, OrderType::Limit);
+        assert!(book.add_market_order_until(limit, |_| false).is_err());
+    }
+
+    #[test]
+    fn test_walk_opposite_preview() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        book.add_order(Order::new(1, 10010, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 10020, 20, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(4, 10030, 30, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        let preview: Vec<_> = book.walk_opposite(Side::Buy, 10020).collect();
+        assert_eq!(preview, vec![(10010, 15, 2), (10020, 20, 1)]);
+
+        // Submitting the order trades against exactly the previewed levels
+        let executions = book
+            .add_order(Order::new(5, 10020, 35, Side::Buy, OrderType::Limit))
+            .unwrap();
+        let mut traded: Vec<(u64, u64)> = Vec::new();
+        for exec in &executions {
+            match traded.last_mut() {
+                Some(last) if last.0 == exec.price => last.1 += exec.quantity,
+                _ => traded.push((exec.price, exec.quantity)),
+            }
+        }
+        let previewed: Vec<(u64, u64)> = preview.iter().map(|&(p, q, _)| (p, q)).collect();
+        assert_eq!(traded, previewed);
+
+        // A limit below the best ask sees nothing
+        assert_eq!(book.walk_opposite(Side::Buy, 10000).count(), 0);
+
+        // Hidden orders are left out, and an all-hidden level skipped
+        book.add_order(Order::new(6, 10030, 4, Side::Sell, OrderType::Limit).with_hidden())
+            .unwrap();
+        book.add_order(Order::new(7, 10040, 8, Side::Sell, OrderType::Limit).with_hidden())
+            .unwrap();
+        let preview: Vec<_> = book.walk_opposite(Side::Buy, 10040).collect();
+        assert_eq!(preview, vec![(10030, 30, 1)]);
+    }
+
+    #[test]
+    fn test_immediate_or_cancel() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        book.add_order(Order::new(1, 10010, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10020, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        // Crosses both levels and drops the unfilled 5
+        let ioc = Order::new(3, 10020, 25, Side::Buy, OrderType::ImmediateOrCancel);
+        assert_eq!(ioc.order_type(), OrderType::ImmediateOrCancel);
+        assert_eq!(ioc.side(), Side::Buy);
+
+        let executions = book.add_order(ioc).unwrap();
+        assert_eq!(executions.len(), 2);
+        assert_eq!(executions[0].price, 10010);
+        assert_eq!(executions[1].price, 10020);
+        assert_eq!(executions.iter().map(|exec| exec.quantity).sum::<u64>(), 20);
+
+        // No residual order rests on either side
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(book.best_ask(), None);
+        assert_eq!(book.summary().total_orders, 0);
+        assert_eq!(book.summary().order_count, 0);
+
+        // A non-crossing IOC within range simply expires
+        let ioc = Order::new(4, 9900, 5, Side::Buy, OrderType::ImmediateOrCancel);
+        assert!(book.add_order(ioc).unwrap().is_empty());
+        assert_eq!(book.best_bid(), None);
+
+        // An out-of-range IOC that matched nothing is still rejected
+        let ioc = Order::new(5, 10500, 5, Side::Buy, OrderType::ImmediateOrCancel);
+        assert!(book.add_order(ioc).is_err());
+    }
+
+    #[test]
+    fn test_fill_or_kill() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        book.add_order(Order::new(1, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10020, 7, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        let depth_before = book.market_depth(10);
+        let summary_before = book.summary();
+
+        // Only 12 available, so a FOK for 15 is killed without executing
+        let fok = Order::new(3, 10020, 15, Side::Buy, OrderType::FillOrKill);
+        assert!(book.add_order(fok).unwrap().is_empty());
+
+        assert_eq!(book.market_depth(10), depth_before);
+        assert_eq!(book.best_ask(), Some(10010));
+        assert_eq!(book.best_bid(), None);
+        let summary = book.summary();
+        assert_eq!(
+            summary.total_quantity_matched,
+            summary_before.total_quantity_matched
+        );
+        assert_eq!(summary.total_orders, summary_before.total_orders);
+
+        // Resting quantities are intact: exactly 12 can still be swept
+        let result = book
+            .add_market_order_until(Order::new(4, 0, 12, Side::Buy, OrderType::Market), |_| {
+                false
+            })
+            .unwrap();
+        let filled: Vec<(u64, u64)> = result
+            .executions
+            .iter()
+            .map(|exec| (exec.order_id, exec.quantity))
+            .collect();
+        assert_eq!(filled, vec![(1, 5), (2, 7)]);
+
+        // A FOK that can be fully satisfied executes in full
+        book.add_order(Order::new(5, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(6, 10020, 7, Side::Sell, OrderType::Limit))
+            .unwrap();
+        let fok = Order::new(7, 10020, 12, Side::Buy, OrderType::FillOrKill);
+        let executions = book.add_order(fok).unwrap();
+        assert_eq!(executions.iter().map(|exec| exec.quantity).sum::<u64>(), 12);
+        assert_eq!(book.best_ask(), None);
+        assert_eq!(book.best_bid(), None);
+    }
+
+    #[test]
+    fn test_stop_market_cascade() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        book.add_order(Order::new(1, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10020, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 10030, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        // Stops rest dormant without touching the book
+        let stop_a = Order::new(10, 0, 5, Side::Buy, OrderType::StopMarket).with_stop_price(10010);
+        let stop_b = Order::new(11, 0, 5, Side::Buy, OrderType::StopMarket).with_stop_price(10020);
+        let stop_c = Order::new(12, 0, 5, Side::Sell, OrderType::StopMarket).with_stop_price(9000);
+        assert!(book.add_order(stop_a).unwrap().is_empty());
+        assert!(book.add_order(stop_b).unwrap().is_empty());
+        assert!(book.add_order(stop_c).unwrap().is_empty());
+        assert_eq!(book.stop_order_count(), 3);
+        assert_eq!(book.best_ask(), Some(10010));
+
+        // Stop IDs are reserved like resting ones
+        assert!(
+            book.add_order(Order::new(10, 10000, 1, Side::Sell, OrderType::Limit))
+                .is_err()
+        );
+
+        // Trading at 10010 fires stop A, whose fill at 10020 fires stop B
+        let executions = book
+            .add_order(Order::new(4, 10010, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        let fills: Vec<(u64, u64, u64)> = executions
+            .iter()
+            .map(|exec| (exec.order_id, exec.price, exec.quantity))
+            .collect();
+        assert_eq!(fills, vec![(1, 10010, 5), (2, 10020, 5), (3, 10030, 5)]);
+
+        // The sell stop is untouched and can still be cancelled
+        assert_eq!(book.stop_order_count(), 1);
+        assert_eq!(book.best_ask(), Some(10030));
+        book.cancel_order(12).unwrap();
+        assert_eq!(book.stop_order_count(), 0);
+    }
+
+    #[test]
+    fn test_stop_limit_rests_after_trigger() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        book.add_order(Order::new(1, 9990, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 9980, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+
+        // Sell stop-limits resting above the market once triggered, marketable
+        // against the remaining bid, and marketable but too large to fill
+        // with a limit that cannot be placed on the ask side
+        let stop = Order::new(10, 10005, 3, Side::Sell, OrderType::StopLimit).with_stop_price(9990);
+        let marketable =
+            Order::new(11, 9000, 3, Side::Sell, OrderType::StopLimit).with_stop_price(9990);
+        let bad = Order::new(12, 9000, 10, Side::Sell, OrderType::StopLimit).with_stop_price(9990);
+        book.add_order(stop).unwrap();
+        book.add_order(marketable).unwrap();
+        book.add_order(bad).unwrap();
+        assert_eq!(book.best_ask(), None);
+
+        // A trade at 9990 fires all three stops; the marketable one matches
+        let executions = book
+            .add_order(Order::new(3, 9990, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        let fills: Vec<_> = executions
+            .iter()
+            .map(|e| (e.maker_order_id, e.taker_order_id, e.price, e.quantity))
+            .collect();
+        assert_eq!(fills, vec![(1, 3, 9990, 5), (2, 11, 9980, 3)]);
+        assert_eq!(book.stop_order_count(), 0);
+
+        // The first limit did not cross the remaining bid and now rests
+        let (bids, asks) = book.market_depth(10);
+        assert_eq!(bids, vec![(9980, 2)]);
+        assert_eq!(asks, vec![(10005, 3)]);
+
+        // The stop-limit that could neither fill nor rest was cancelled
+        let rejected = book.take_rejected_stops();
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].0, 12);
+        assert_eq!(rejected[0].1, OrderError::PriceOutOfRange(9000));
+        assert!(book.take_rejected_stops().is_empty());
+    }
+
+    #[test]
+    fn test_iceberg_reveals_only_peak() {
+        let mut book = OrderBook::new("TEST", 1000);
+        let mut iceberg_filled = 0;
+
+        // 25 to sell, showing 10 at a time
+        let iceberg =
+            Order::new(1, 10010, 25, Side::Sell, OrderType::Limit).with_display_quantity(10);
+        book.add_order(iceberg).unwrap();
+        book.add_order(Order::new(2, 10010, 4, Side::Sell, OrderType::Limit))
+            .unwrap();
+        assert_eq!(book.market_depth(10).1, vec![(10010, 14)]);
+
+        // Consuming the first peak refreshes the iceberg behind order 2
+        let executions = book
+            .add_order(Order::new(3, 10010, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        assert_eq!(executions.len(), 1);
+        iceberg_filled += executions[0].quantity;
+        assert_eq!(book.market_depth(10).1, vec![(10010, 14)]);
+
+        let executions = book
+            .add_order(Order::new(4, 10010, 6, Side::Buy, OrderType::Limit))
+            .unwrap();
+        let fills: Vec<(u64, u64)> = executions
+            .iter()
+            .map(|exec| (exec.order_id, exec.quantity))
+            .collect();
+        assert_eq!(fills, vec![(2, 4), (1, 2)]);
+        iceberg_filled += 2;
+        assert_eq!(book.market_depth(10).1, vec![(10010, 8)]);
+
+        // A sweep larger than the peak keeps taking slices until the reserve is gone
+        let result = book
+            .add_market_order_until(Order::new(5, 0, 100, Side::Buy, OrderType::Market), |_| {
+                false
+            })
+            .unwrap();
+        assert!(result.executions.iter().all(|exec| exec.order_id == 1));
+        iceberg_filled += result
+            .executions
+            .iter()
+            .map(|exec| exec.quantity)
+            .sum::<u64>();
+        assert_eq!(result.unfilled_quantity, 87);
+        assert_eq!(book.best_ask(), None);
+
+        // Every unit of the iceberg traded exactly once
+        assert_eq!(iceberg_filled, 25);
+    }
+
+    #[test]
+    fn test_post_only() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        book.add_order(Order::new(1, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 9990, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+
+        // Crossing post-only orders are rejected without executing
+        let err = book
+            .add_order(Order::new(3, 10010, 5, Side::Buy, OrderType::PostOnly))
+            .unwrap_err();
+        assert_eq!(err, OrderError::WouldCross(3));
+        assert!(
+            book.add_order(Order::new(4, 9990, 5, Side::Sell, OrderType::PostOnly))
+                .is_err()
+        );
+        assert_eq!(book.market_depth(10), (vec![(9990, 5)], vec![(10010, 5)]));
+        assert_eq!(book.summary().total_quantity_matched, 0);
+
+        // Non-crossing post-only orders rest like limits
+        assert!(
+            book.add_order(Order::new(5, 9995, 3, Side::Buy, OrderType::PostOnly))
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            book.add_order(Order::new(6, 10005, 3, Side::Sell, OrderType::PostOnly))
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(book.best_bid(), Some(9995));
+        assert_eq!(book.best_ask(), Some(10005));
+    }
+
+    #[test]
+    fn test_expire_orders() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        book.add_order(Order::new(1, 9990, 5, Side::Buy, OrderType::Limit).with_expiry(100))
+            .unwrap();
+        book.add_order(Order::new(2, 9980, 5, Side::Buy, OrderType::Limit).with_expiry(200))
+            .unwrap();
+        book.add_order(Order::new(3, 10010, 5, Side::Sell, OrderType::Limit).with_expiry(300))
+            .unwrap();
+        book.add_order(Order::new(4, 10020, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        // Expiry is inclusive; later and open-ended orders survive
+        assert_eq!(book.expire_orders(200), vec![1, 2]);
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(book.best_ask(), Some(10010));
+
+        assert!(book.expire_orders(299).is_empty());
+        assert_eq!(book.expire_orders(u64::MAX), vec![3]);
+        assert_eq!(book.best_ask(), Some(10020));
+        assert_eq!(book.summary().total_orders, 1);
+    }
+
+    #[test]
+    fn test_amend_order_priority() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        book.add_order(Order::new(1, 10010, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10010, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 10020, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        // Reducing order 1 keeps it at the front of the queue
+        book.amend_order(1, 10010, 4).unwrap();
+        assert_eq!(book.market_depth(10).1, vec![(10010, 14), (10020, 10)]);
+        let executions = book
+            .add_order(Order::new(4, 10010, 2, Side::Buy, OrderType::Limit))
+            .unwrap();
+        assert_eq!(executions[0].order_id, 1);
+
+        // Moving order 2 joins the back of the 10020 level behind order 3
+        book.amend_order(2, 10020, 10).unwrap();
+        assert_eq!(book.market_depth(10).1, vec![(10010, 2), (10020, 20)]);
+        book.amend_order(1, 10020, 2).unwrap();
+        assert_eq!(book.best_ask(), Some(10020));
+        let executions = book
+            .add_order(Order::new(5, 10020, 22, Side::Buy, OrderType::Limit))
+            .unwrap();
+        let order_ids: Vec<u64> = executions.iter().map(|exec| exec.order_id).collect();
+        assert_eq!(order_ids, vec![3, 2, 1]);
+
+        // Amending into the opposite side or an unknown order is rejected
+        book.add_order(Order::new(6, 10030, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(7, 9990, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        assert!(book.amend_order(6, 9990, 5).is_err());
+        assert!(book.amend_order(99, 9990, 5).is_err());
+        assert_eq!(book.market_depth(10), (vec![(9990, 5)], vec![(10030, 5)]));
+    }
+
+    #[test]
+    fn test_order_error_variants() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        book.add_order(Order::new(1, 9990, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+
+        let duplicate = Order::new(1, 9980, 5, Side::Buy, OrderType::Limit);
+        assert_eq!(
+            book.add_order(duplicate).unwrap_err(),
+            OrderError::DuplicateOrderId(1)
+        );
+        let out_of_range = Order::new(2, 15_000, 5, Side::Sell, OrderType::Limit);
+        assert_eq!(
+            book.add_order(out_of_range).unwrap_err(),
+            OrderError::PriceOutOfRange(15_000)
+        );
+        assert_eq!(book.cancel_order(42), Err(OrderError::OrderNotFound(42)));
+
+        book.disable();
+        let order = Order::new(3, 9980, 5, Side::Buy, OrderType::Limit);
+        assert_eq!(book.add_order(order).unwrap_err(), OrderError::Disabled);
+
+        // Display keeps the human-readable messages
+        assert_eq!(
+            OrderError::DuplicateOrderId(1).to_string(),
+            "Order ID 1 already exists"
+        );
+    }
+
+    #[test]
+    fn test_matched_quantity_counted_once() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        book.add_order(Order::new(1, 9990, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        let executions = book
+            .add_order(Order::new(2, 9990, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        assert_eq!(executions.len(), 1);
+
+        // One 5-lot trade is 5 matched, not 10
+        assert_eq!(book.summary().total_quantity_matched, 5);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_timestamps_advance() {
+        let first = Order::new(1, 9990, 5, Side::Buy, OrderType::Limit);
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let second = Order::new(2, 9990, 5, Side::Buy, OrderType::Limit);
+
+        assert!(second.timestamp > first.timestamp);
+        assert!(second.timestamp - first.timestamp >= 2_000_000);
+    }
+
+    #[test]
+    fn test_best_price_after_emptying_levels() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        // Spread levels across several bitmap words on both sides
+        let bid_prices = [9999, 9990, 9936, 9935, 9800, 9000];
+        let ask_prices = [10000, 10063, 10064, 10200, 10900];
+        for (i, &price) in bid_prices.iter().enumerate() {
+            book.add_order(Order::new(i as u64, price, 5, Side::Buy, OrderType::Limit))
+                .unwrap();
+        }
+        for (i, &price) in ask_prices.iter().enumerate() {
+            let id = 100 + i as u64;
+            book.add_order(Order::new(id, price, 5, Side::Sell, OrderType::Limit))
+                .unwrap();
+        }
+
+        // Cancel the best bid each time; the next one down takes over
+        for (i, _) in bid_prices.iter().enumerate() {
+            assert_eq!(book.best_bid(), Some(bid_prices[i]));
+            book.cancel_order(i as u64).unwrap();
+        }
+        assert_eq!(book.best_bid(), None);
+
+        // Emptying asks by matching walks up the levels the same way
+        for (i, _) in ask_prices.iter().enumerate() {
+            assert_eq!(book.best_ask(), Some(ask_prices[i]));
+            let taker = Order::new(200 + i as u64, 0, 5, Side::Buy, OrderType::Market);
+            book.add_order(taker).unwrap();
+        }
+        assert_eq!(book.best_ask(), None);
+
+        // Levels can be reused once emptied
+        book.add_order(Order::new(300, 9936, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        assert_eq!(book.best_bid(), Some(9936));
+    }
+
+    #[test]
+    fn test_with_config_tick_grid() {
+        let mut book = OrderBook::with_config("STK", 1000, 50_000, 5);
+
+        // Prices on the 5-tick grid either side of the base rest normally
+        book.add_order(Order::new(1, 49_995, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 50_005, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        assert_eq!(book.best_bid(), Some(49_995));
+        assert_eq!(book.best_ask(), Some(50_005));
+
+        // Off-grid prices are rejected rather than snapped to a level
+        let off_tick_buy = Order::new(3, 49_993, 10, Side::Buy, OrderType::Limit);
+        assert_eq!(
+            book.add_order(off_tick_buy).unwrap_err(),
+            OrderError::NotTickAligned(49_993)
+        );
+        let off_tick_sell = Order::new(4, 50_007, 10, Side::Sell, OrderType::Limit);
+        assert!(book.add_order(off_tick_sell).is_err());
+
+        // Matching works on the configured grid
+        let executions = book
+            .add_order(Order::new(5, 49_990, 4, Side::Sell, OrderType::Limit))
+            .unwrap();
+        assert_eq!(executions[0].price, 49_995);
+        assert_eq!(book.market_depth(10).0, vec![(49_995, 6)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "tick_size must be positive")]
+    fn test_with_config_zero_tick() {
+        OrderBook::with_config("STK", 1000, 50_000, 0);
+    }
+
+    #[test]
+    fn test_configurable_price_levels() {
+        let config = BookConfig {
+            base_price: 100_000,
+            tick_size: 1,
+            price_levels: 65_536,
+            ..BookConfig::default()
+        };
+        let mut book = OrderBook::from_config("WIDE", 1000, config);
+
+        // The far ends of a 65536-level range are now reachable
+        book.add_order(Order::new(
+            1,
+            100_000 - 65_535,
+            5,
+            Side::Buy,
+            OrderType::Limit,
+        ))
+        .unwrap();
+        book.add_order(Order::new(
+            2,
+            100_000 + 65_535,
+            5,
+            Side::Sell,
+            OrderType::Limit,
+        ))
+        .unwrap();
+        assert_eq!(book.best_bid(), Some(34_465));
+        assert_eq!(book.best_ask(), Some(165_535));
+        assert_eq!(
+            book.market_depth(10),
+            (vec![(34_465, 5)], vec![(165_535, 5)])
+        );
+
+        // One tick past either end is still rejected
+        let past_end = Order::new(3, 100_000 + 65_536, 5, Side::Sell, OrderType::Limit);
+        assert_eq!(
+            book.add_order(past_end).unwrap_err(),
+            OrderError::PriceOutOfRange(165_536)
+        );
+
+        // A sweep crosses the whole range to reach the far level
+        let executions = book
+            .add_order(Order::new(4, 0, 5, Side::Buy, OrderType::Market))
+            .unwrap();
+        assert_eq!(executions[0].price, 165_535);
+        assert_eq!(book.best_ask(), None);
+
+        // The default book stays at 1024 levels
+        let mut default_book = OrderBook::new("TEST", 1000);
+        assert!(
+            default_book
+                .add_order(Order::new(
+                    1,
+                    10_000 + 1024,
+                    5,
+                    Side::Sell,
+                    OrderType::Limit
+                ))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_get_order() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        let order = Order::new(1, 9990, 10, Side::Buy, OrderType::Limit);
+        let timestamp = order.timestamp;
+        book.add_order(order).unwrap();
+        book.add_order(Order::new(2, 9980, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+
+        let view = book.get_order(1).unwrap();
+        assert_eq!(view.price, 9990);
+        assert_eq!(view.quantity, 10);
+        assert_eq!(view.side, Side::Buy);
+        assert_eq!(view.order_type, OrderType::Limit);
+        assert_eq!(view.timestamp, timestamp);
+
+        // Partial fills show the remaining quantity
+        book.add_order(Order::new(3, 9990, 4, Side::Sell, OrderType::Limit))
+            .unwrap();
+        assert_eq!(book.get_order(1).unwrap().quantity, 6);
+
+        // Fully matched and cancelled orders are gone
+        book.add_order(Order::new(4, 9990, 6, Side::Sell, OrderType::Limit))
+            .unwrap();
+        assert_eq!(book.get_order(1), None);
+        book.cancel_order(2).unwrap();
+        assert_eq!(book.get_order(2), None);
+        assert_eq!(book.get_order(999_999), None);
+    }
+
+    #[test]
+    fn test_snapshot_restore_round_trip() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        book.add_order(Order::new(1, 9990, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 9990, 7, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 9980, 4, Side::Buy, OrderType::Limit))
+            .unwrap();
+        let iceberg =
+            Order::new(4, 10010, 30, Side::Sell, OrderType::Limit).with_display_quantity(10);
+        book.add_order(iceberg).unwrap();
+        book.add_order(Order::new(5, 10020, 6, Side::Sell, OrderType::Limit))
+            .unwrap();
+        let stop = Order::new(6, 0, 3, Side::Buy, OrderType::StopMarket).with_stop_price(10050);
+        book.add_order(stop).unwrap();
+
+        // Take part of the iceberg's visible peak
+        book.add_order(Order::new(7, 10010, 4, Side::Buy, OrderType::Limit))
+            .unwrap();
+
+        let restored = OrderBook::restore(book.snapshot()).unwrap();
+        assert_eq!(restored.snapshot(), book.snapshot());
+        assert_eq!(restored.best_bid(), book.best_bid());
+        assert_eq!(restored.best_ask(), book.best_ask());
+        assert_eq!(restored.market_depth(10), book.market_depth(10));
+        assert_eq!(restored.stop_order_count(), 1);
+        assert_eq!(restored.get_order(4), book.get_order(4));
+
+        // Both books fill the same orders in the same sequence
+        let mut restored = restored;
+        let sweep = || Order::new(8, 0, 20, Side::Sell, OrderType::Market);
+        let fills = |executions: Vec<Execution>| -> Vec<(u64, u64)> {
+            executions
+                .iter()
+                .map(|exec| (exec.order_id, exec.quantity))
+                .collect()
+        };
+        let expected = fills(book.add_order(sweep()).unwrap());
+        assert_eq!(expected, vec![(1, 10), (2, 7), (3, 3)]);
+        assert_eq!(fills(restored.add_order(sweep()).unwrap()), expected);
+        assert_eq!(
+            restored.summary().total_quantity_matched,
+            book.summary().total_quantity_matched
+        );
+    }
+
+    #[test]
+    fn test_market_order_unfilled_remainder() {
+        let mut book = OrderBook::new("TEST", 10000);
+        let mut order_count = 0;
+
+        // 300 available across 30 ask levels
+        for i in 0..30 {
+            let sell_order = Order::new(
+                order_count,
+                10_100 + i * 5,
+                10,
+                Side::Sell,
+                OrderType::Limit,
+            );
+            book.add_order(sell_order).unwrap();
+            order_count += 1;
+        }
+
+        // A market buy for 500 fills what exists and reports the rest
+        let market_order = Order::new(order_count, 0, 500, Side::Buy, OrderType::Market);
+        let result = book.add_market_order(market_order).unwrap();
+
+        let filled: u64 = result.executions.iter().map(|exec| exec.quantity).sum();
+        assert_eq!(filled, 300);
+        assert_eq!(result.unfilled_quantity, 500 - 300);
+        assert_eq!(book.best_ask(), None);
+
+        // Limit orders are not accepted here
+        let limit = Order::new(order_count + 1, 9990, 5, Side::Buy, OrderType::Limit);
+        assert_eq!(
+            book.add_market_order(limit).unwrap_err(),
+            OrderError::NotMarketOrder(order_count + 1)
+        );
+    }
+
+    #[test]
+    fn test_protected_market_order() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        book.add_order(Order::new(1, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10020, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 10030, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        // A buy capped at 10020 takes two levels and leaves 10030 alone
+        let order = Order::new(4, 0, 12, Side::Buy, OrderType::Market);
+        let result = book.add_market_order_protected(order, 10020).unwrap();
+        let prices: Vec<u64> = result.executions.iter().map(|exec| exec.price).collect();
+        assert_eq!(prices, vec![10010, 10020]);
+        assert_eq!(result.unfilled_quantity, 2);
+        assert_eq!(book.market_depth(10).1, vec![(10030, 5)]);
+
+        // A sell capped above the best bid does not trade at all
+        book.add_order(Order::new(5, 9990, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        let order = Order::new(6, 0, 5, Side::Sell, OrderType::Market);
+        let result = book.add_market_order_protected(order, 9995).unwrap();
+        assert!(result.executions.is_empty());
+        assert_eq!(result.unfilled_quantity, 5);
+        assert_eq!(book.best_bid(), Some(9990));
+    }
+
+    #[test]
+    fn test_self_trade_prevention() {
+        let setup = |policy: SelfTradePolicy| {
+            let mut book = OrderBook::new("TEST", 1000);
+            book.set_self_trade_policy(policy);
+            book.add_order(Order::new(1, 10010, 5, Side::Sell, OrderType::Limit).with_account(7))
+                .unwrap();
+            book.add_order(Order::new(2, 10010, 5, Side::Sell, OrderType::Limit).with_account(8))
+                .unwrap();
+            book
+        };
+
+        // CancelResting drops our own ask and keeps matching behind it
+        let mut book = setup(SelfTradePolicy::CancelResting);
+        let order = Order::new(3, 10010, 5, Side::Buy, OrderType::Limit).with_account(7);
+        let executions = book.add_order(order).unwrap();
+        let fills: Vec<(u64, u64)> = executions
+            .iter()
+            .map(|exec| (exec.order_id, exec.quantity))
+            .collect();
+        assert_eq!(fills, vec![(2, 5)]);
+        assert_eq!(book.get_order(1), None);
+        assert_eq!(book.best_ask(), None);
+
+        // CancelAggressing stops the incoming order and leaves the book alone
+        let mut book = setup(SelfTradePolicy::CancelAggressing);
+        let order = Order::new(3, 10010, 8, Side::Buy, OrderType::Limit).with_account(7);
+        assert!(book.add_order(order).unwrap().is_empty());
+        assert_eq!(book.market_depth(10), (vec![], vec![(10010, 10)]));
+        assert_eq!(book.get_order(3), None);
+
+        // CancelBoth removes the resting order and the aggressor
+        let mut book = setup(SelfTradePolicy::CancelBoth);
+        let order = Order::new(3, 0, 8, Side::Buy, OrderType::Market).with_account(7);
+        let result = book.add_market_order(order).unwrap();
+        assert!(result.executions.is_empty());
+        assert_eq!(book.get_order(1), None);
+        assert_eq!(book.market_depth(10).1, vec![(10010, 5)]);
+
+        // Other accounts and untagged orders trade as usual
+        let order = Order::new(4, 10010, 5, Side::Buy, OrderType::Limit);
+        assert_eq!(book.add_order(order).unwrap().len(), 1);
+        assert_eq!(book.summary().total_quantity_matched, 5);
+    }
+
+    #[test]
+    fn test_pro_rata_matching() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.set_matching_policy(MatchingPolicy::ProRata);
+
+        book.add_order(Order::new(1, 9990, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 9990, 20, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 9990, 30, Side::Buy, OrderType::Limit))
+            .unwrap();
+
+        // 10 against 10/20/30 is 1.67/3.33/5: floors 1/3/5, and the spare unit
+        // goes to order 1 with the largest remainder
+        let executions = book
+            .add_order(Order::new(4, 9990, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        let fills: Vec<(u64, u64)> = executions
+            .iter()
+            .map(|exec| (exec.order_id, exec.quantity))
+            .collect();
+        assert_eq!(fills, vec![(1, 2), (2, 3), (3, 5)]);
+        assert_eq!(book.market_depth(10).0, vec![(9990, 50)]);
+
+        // Now 8/17/25: 6 splits into 0.96/2.04/3.0, floors 0/2/3, and order 1
+        // takes the spare unit
+        let executions = book
+            .add_order(Order::new(5, 9990, 6, Side::Sell, OrderType::Limit))
+            .unwrap();
+        let fills: Vec<(u64, u64)> = executions
+            .iter()
+            .map(|exec| (exec.order_id, exec.quantity))
+            .collect();
+        assert_eq!(fills, vec![(1, 1), (2, 2), (3, 3)]);
+
+        // Equal remainders are settled in time priority
+        let mut book = OrderBook::new("TEST", 1000);
+        book.set_matching_policy(MatchingPolicy::ProRata);
+        for id in 1..=3 {
+            book.add_order(Order::new(id, 9990, 10, Side::Buy, OrderType::Limit))
+                .unwrap();
+        }
+        let executions = book
+            .add_order(Order::new(4, 9990, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        let quantities: Vec<u64> = executions.iter().map(|exec| exec.quantity).collect();
+        assert_eq!(quantities, vec![4, 3, 3]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_execution_callback() {
+        use std::sync::{Arc, Mutex};
+
+        let mut book = OrderBook::new("TEST", 1000);
+        let feed = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&feed);
+        book.on_execution(Box::new(move |exec: &Execution| {
+            sink.lock().unwrap().push(exec.clone());
+        }));
+
+        book.add_order(Order::new(1, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 10020, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        let stop = Order::new(10, 0, 5, Side::Buy, OrderType::StopMarket).with_stop_price(10020);
+        book.add_order(stop).unwrap();
+        assert!(feed.lock().unwrap().is_empty());
+
+        // The sweep reaches 10020 and fires the stop, whose fill is streamed too
+        let executions = book
+            .add_order(Order::new(4, 10020, 12, Side::Buy, OrderType::Limit))
+            .unwrap();
+        let fields = |execs: &[Execution]| -> Vec<(u64, u64, u64, Side)> {
+            execs
+                .iter()
+                .map(|exec| (exec.order_id, exec.price, exec.quantity, exec.side))
+                .collect()
+        };
+        let streamed = fields(&feed.lock().unwrap());
+        assert_eq!(streamed, fields(&executions));
+        assert_eq!(
+            streamed,
+            vec![
+                (1, 10010, 5, Side::Sell),
+                (2, 10010, 5, Side::Sell),
+                (3, 10020, 2, Side::Sell),
+                (3, 10020, 5, Side::Sell),
+            ]
+        );
+
+        // Simulations run on a fork and stay silent
+        book.simulate(Order::new(5, 0, 1, Side::Buy, OrderType::Market))
+            .unwrap();
+        assert_eq!(feed.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_last_trade_after_sweep() {
+        let mut book = OrderBook::new("TEST", 1000);
+        assert_eq!(book.last_trade(), None);
+
+        book.add_order(Order::new(1, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10020, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 10030, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        // The sweep ends with a partial fill at the worst level
+        let order = Order::new(4, 0, 13, Side::Buy, OrderType::Market);
+        assert_eq!(book.add_market_order(order).unwrap().executions.len(), 3);
+        assert_eq!(book.last_trade_price(), Some(10030));
+        assert_eq!(book.last_trade(), Some((10030, 3)));
+
+        // Orders that only rest leave it alone
+        book.add_order(Order::new(5, 9990, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        let summary = book.summary();
+        assert_eq!(summary.last_trade_price, Some(10030));
+        assert_eq!(summary.last_trade_quantity, Some(3));
+        assert!(summary.to_string().contains("Last Trade: 3 @ 10030"));
+    }
+
+    #[test]
+    fn test_vwap() {
+        assert_eq!(vwap(&[]), None);
+
+        let mut book = OrderBook::new("TEST", 1000);
+        book.add_order(Order::new(1, 10010, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10020, 30, Side::Sell, OrderType::Limit))
+            .unwrap();
+        let executions = book
+            .add_order(Order::new(3, 10020, 40, Side::Buy, OrderType::Limit))
+            .unwrap();
+        // (10010 * 10 + 10020 * 30) / 40
+        assert_eq!(vwap(&executions), Some(10017.5));
+
+        // Each price * quantity overflows u64 on its own
+        let fill = |price: u64, quantity: u64| Execution {
+            order_id: 1,
+            price,
+            quantity,
+            timestamp: 0,
+            side: Side::Sell,
+            trade_id: 1,
+            maker_order_id: 1,
+            taker_order_id: 2,
+            aggressor_side: Side::Buy,
+        };
+        let executions = [fill(1 << 40, 1 << 30), fill(3 << 40, 1 << 30)];
+        assert_eq!(vwap(&executions), Some((2u64 << 40) as f64));
+    }
+
+    #[test]
+    fn test_market_depth_detailed() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        for id in 1..=3 {
+            book.add_order(Order::new(id, 9900, id * 10, Side::Buy, OrderType::Limit))
+                .unwrap();
+        }
+        book.add_order(Order::new(4, 9800, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(5, 10100, 7, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        let (bids, asks) = book.market_depth_detailed(10);
+        assert_eq!(
+            bids,
+            vec![
+                DepthLevel {
+                    price: 9900,
+                    quantity: 60,
+                    order_count: 3,
+                },
+                DepthLevel {
+                    price: 9800,
+                    quantity: 5,
+                    order_count: 1,
+                },
+            ]
+        );
+        assert_eq!(asks.len(), 1);
+        assert_eq!(asks[0].order_count, 1);
+        assert_eq!(book.market_depth_detailed(1).0.len(), 1);
+    }
+
+    #[test]
+    fn test_imbalance_and_microprice() {
+        let mut book = OrderBook::new("TEST", 1000);
+        assert_eq!(book.imbalance(5), None);
+        assert_eq!(book.microprice(), None);
+
+        book.add_order(Order::new(1, 9990, 30, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 9980, 30, Side::Buy, OrderType::Limit))
+            .unwrap();
+        assert_eq!(book.imbalance(5), None);
+
+        book.add_order(Order::new(3, 10010, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(4, 10020, 20, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        // Top level only: 30 bid vs 10 ask
+        assert_eq!(book.imbalance(1), Some(0.5));
+        // Two levels: 60 bid vs 30 ask
+        let imbalance = book.imbalance(2).unwrap();
+        assert!((imbalance - 1.0 / 3.0).abs() < 1e-12);
+
+        // (9990 * 10 + 10010 * 30) / 40 sits above the 10000 mid
+        assert_eq!(book.microprice(), Some(10005.0));
+        assert!(book.microprice().unwrap() > book.mid_price().unwrap());
+
+        // Hidden bids, at the best price and on a better all-hidden level,
+        // move neither measure
+        book.add_order(Order::new(5, 9990, 1000, Side::Buy, OrderType::Limit).with_hidden())
+            .unwrap();
+        book.add_order(Order::new(6, 9995, 50, Side::Buy, OrderType::Limit).with_hidden())
+            .unwrap();
+        assert_eq!(book.imbalance(1), Some(0.5));
+        let imbalance = book.imbalance(2).unwrap();
+        assert!((imbalance - 1.0 / 3.0).abs() < 1e-12);
+        assert_eq!(book.microprice(), Some(10005.0));
+    }
+
+    #[test]
+    fn test_checksum() {
+        let mut book = OrderBook::new("TEST", 1000);
+        assert_eq!(book.checksum(10), 0);
+
+        book.add_order(Order::new(1, 9990, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 9980, 20, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 9970, 3, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(4, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(5, 10020, 7, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        // crc32("9990:10:10010:5:9980:20:10020:7:9970:3")
+        assert_eq!(book.checksum(10), 4_124_457_601);
+
+        // Any change in the covered levels changes the checksum
+        book.cancel_order(3).unwrap();
+        assert_ne!(book.checksum(10), 4_124_457_601);
+        assert_eq!(book.checksum(2), book.checksum(10));
+    }
+
+    #[test]
+    fn test_order_book_l3() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        for id in 1..=3 {
+            book.add_order(Order::new(id, 10010, 10, Side::Sell, OrderType::Limit))
+                .unwrap();
+        }
+        book.add_order(Order::new(4, 10020, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(5, 9990, 8, Side::Buy, OrderType::Limit))
+            .unwrap();
+
+        book.add_order(Order::new(6, 10010, 4, Side::Buy, OrderType::Limit))
+            .unwrap();
+
+        let (bids, asks) = book.order_book_l3(10);
+        assert_eq!(bids, vec![(9990, vec![(5, 8)])]);
+        assert_eq!(
+            asks,
+            vec![
+                (10010, vec![(1, 6), (2, 10), (3, 10)]),
+                (10020, vec![(4, 5)]),
+            ]
+        );
+
+        // The queue is the matching order
+        let executions = book
+            .add_order(Order::new(7, 10010, 16, Side::Buy, OrderType::Limit))
+            .unwrap();
+        let filled: Vec<u64> = executions.iter().map(|exec| exec.order_id).collect();
+        assert_eq!(filled, vec![1, 2]);
+        assert_eq!(book.order_book_l3(1).1, vec![(10010, vec![(3, 10)])]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_manager_routes_by_symbol() {
+        let mut manager = OrderBookManager::new();
+        manager.get_or_create("BTC-USD", 1000);
+        manager.insert(OrderBook::with_config("ETH-USD", 1000, 2000, 5));
+        assert_eq!(manager.len(), 2);
+
+        manager
+            .add_order(
+                "BTC-USD",
+                Order::new(1, 9990, 10, Side::Buy, OrderType::Limit),
+            )
+            .unwrap();
+        manager
+            .add_order(
+                "ETH-USD",
+                Order::new(1, 2010, 7, Side::Sell, OrderType::Limit),
+            )
+            .unwrap();
+
+        // The same order ID lives independently on each book
+        let btc = manager.book("BTC-USD").unwrap();
+        assert_eq!((btc.best_bid(), btc.best_ask()), (Some(9990), None));
+        let eth = manager.book("ETH-USD").unwrap();
+        assert_eq!((eth.best_bid(), eth.best_ask()), (None, Some(2010)));
+
+        // The ETH grid keeps its own tick size
+        assert!(
+            manager
+                .add_order(
+                    "ETH-USD",
+                    Order::new(2, 2012, 1, Side::Sell, OrderType::Limit)
+                )
+                .is_err()
+        );
+
+        manager.cancel_order("BTC-USD", 1).unwrap();
+        assert_eq!(manager.book("BTC-USD").unwrap().best_bid(), None);
+        assert!(manager.book("ETH-USD").unwrap().get_order(1).is_some());
+
+        assert_eq!(
+            manager.cancel_order("SOL-USD", 1).unwrap_err(),
+            OrderError::UnknownSymbol("SOL-USD".to_string())
+        );
+
+        let mut symbols: Vec<String> = manager.summaries().map(|s| s.symbol).collect();
+        symbols.sort();
+        assert_eq!(symbols, vec!["BTC-USD", "ETH-USD"]);
+
+        manager
+            .book_mut("ETH-USD")
+            .unwrap()
+            .cancel_order(1)
+            .unwrap();
+        assert!(manager.summaries().all(|summary| summary.total_orders == 0));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_shared_order_book_concurrent_access() {
+        use std::sync::Arc;
+        use std::thread;
+
+        let shared = Arc::new(SharedOrderBook::new(OrderBook::new("TEST", 10_000)));
+
+        let writer = {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || {
+                for id in 1..=2000u64 {
+                    let (price, side) = if id % 2 == 0 {
+                        (9900 + id % 50, Side::Buy)
+                    } else {
+                        (10000 + id % 50, Side::Sell)
+                    };
+                    shared
+                        .add_order(Order::new(id, price, 10, side, OrderType::Limit))
+                        .unwrap();
+                    if id % 3 == 0 {
+                        shared.cancel_order(id).unwrap();
+                    }
+                }
+            })
+        };
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let shared = Arc::clone(&shared);
+                thread::spawn(move || {
+                    for _ in 0..500 {
+                        let book = shared.read();
+                        assert!(!book.is_crossed());
+                        let (bids, asks) = book.market_depth(5);
+                        assert_eq!(bids.first().map(|level| level.0), book.best_bid());
+                        assert_eq!(asks.first().map(|level| level.0), book.best_ask());
+                    }
+                })
+            })
+            .collect();
+
+        writer.join().unwrap();
+        for reader in readers {
+            reader.join().unwrap();
+        }
+
+        // Every third order was cancelled and the sides never crossed
+        let summary = shared.summary();
+        assert_eq!(summary.total_orders, 2000 - 666);
+        assert_eq!(summary.total_quantity_matched, 0);
+        assert_eq!(shared.best_bid(), Some(9948));
+        assert_eq!(shared.best_ask(), Some(10001));
+    }
+
+    #[test]
+    fn test_add_orders_matches_individual_calls() {
+        let orders = vec![
+            Order::new(1, 9990, 10, Side::Buy, OrderType::Limit),
+            Order::new(2, 9980, 10, Side::Buy, OrderType::Limit),
+            Order::new(1, 9970, 5, Side::Buy, OrderType::Limit),
+            Order::new(3, 9980, 15, Side::Sell, OrderType::Limit),
+            Order::new(4, 0, 10, Side::Sell, OrderType::Market),
+            Order::new(5, 10010, 7, Side::Sell, OrderType::Limit),
+        ];
+
+        let mut individual = OrderBook::new("TEST", 1000);
+        let expected: Vec<_> = orders
+            .iter()
+            .map(|order| individual.add_order(order.clone()))
+            .collect();
+
+        let mut batched = OrderBook::new("TEST", 1000);
+        let outcomes = batched.add_orders(orders);
+
+        let fills = |outcome: &OrderOutcome| {
+            outcome.clone().map(|executions| {
+                executions
+                    .iter()
+                    .map(|exec| (exec.order_id, exec.price, exec.quantity))
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(outcomes.len(), expected.len());
+        for (outcome, expected) in outcomes.iter().zip(&expected) {
+            assert_eq!(fills(outcome), fills(expected));
+        }
+
+        // The duplicate ID is rejected in place without stopping the batch
+        assert_eq!(
+            outcomes[2].as_ref().unwrap_err(),
+            &OrderError::DuplicateOrderId(1)
+        );
+        assert_eq!(
+            fills(&outcomes[3]).unwrap(),
+            vec![(1, 9990, 10), (2, 9980, 5)]
+        );
+        assert_eq!(batched.market_depth(10), individual.market_depth(10));
+    }
+
+    #[test]
+    fn test_level_fill_order_in_place() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.set_level_inline_capacity(Some(2));
+
+        // Orders 3 and 4 spill; order 2 is an iceberg showing 5 of 15
+        book.add_order(Order::new(1, 10010, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        let iceberg =
+            Order::new(2, 10010, 15, Side::Sell, OrderType::Limit).with_display_quantity(5);
+        book.add_order(iceberg).unwrap();
+        book.add_order(Order::new(3, 10010, 10, Side::Sell, OrderType::Limit).with_account(7))
+            .unwrap();
+        book.add_order(Order::new(4, 10010, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        // Order 3 shares the aggressor's account and is cancelled mid-queue;
+        // the iceberg's new slices queue behind order 4
+        let executions = book
+            .add_order(Order::new(5, 10010, 32, Side::Buy, OrderType::Limit).with_account(7))
+            .unwrap();
+        let fills: Vec<(u64, u64)> = executions
+            .iter()
+            .map(|exec| (exec.order_id, exec.quantity))
+            .collect();
+        assert_eq!(fills, vec![(1, 10), (2, 5), (4, 10), (2, 5), (2, 2)]);
+
+        assert_eq!(book.get_order(3), None);
+        assert_eq!(book.order_book_l3(1).1, vec![(10010, vec![(2, 3)])]);
+        assert_eq!(book.summary().ask_orders, 1);
+    }
+
+    #[test]
+    fn test_pool_double_free_detected() {
+        let mut pool = OrderPool::new(4);
+        let index = pool
+            .allocate(Order::new(1, 9900, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        assert_eq!(pool.available_capacity(), 3);
+
+        assert!(pool.deallocate(index));
+        assert!(!pool.deallocate(index));
+        assert!(!pool.deallocate(99));
+        assert_eq!(pool.available_capacity(), 4);
+
+        // The slot is handed out once, not twice
+        let first = pool.allocate(Order::new(2, 9900, 10, Side::Buy, OrderType::Limit));
+        let second = pool.allocate(Order::new(3, 9900, 10, Side::Buy, OrderType::Limit));
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_clear_resets_book() {
+        let mut book = OrderBook::new("TEST", 100);
+        for id in 1..=50 {
+            let (price, side) = if id % 2 == 0 {
+                (9990 - id, Side::Buy)
+            } else {
+                (10010 + id, Side::Sell)
+            };
+            book.add_order(Order::new(id, price, 10, side, OrderType::Limit))
+                .unwrap();
+        }
+        book.add_order(Order::new(60, 0, 5, Side::Sell, OrderType::Market))
+            .unwrap();
+        let stop = Order::new(61, 0, 5, Side::Buy, OrderType::StopMarket).with_stop_price(20000);
+        book.add_order(stop).unwrap();
+
+        book.clear();
+
+        let summary = book.summary();
+        assert_eq!((summary.best_bid, summary.best_ask), (None, None));
+        assert_eq!((summary.buy_levels, summary.sell_levels), (0, 0));
+        assert_eq!(summary.total_orders, 0);
+        assert_eq!(summary.total_orders_processed, 0);
+        assert_eq!(summary.total_quantity_matched, 0);
+        assert_eq!(book.last_trade(), None);
+        assert_eq!(book.stop_order_count(), 0);
+
+        // Used IDs and the whole pool are available again
+        for id in 1..=100 {
+            book.add_order(Order::new(id, 9900, 1, Side::Buy, OrderType::Limit))
+                .unwrap();
+        }
+        assert_eq!(book.market_depth(1).0, vec![(9900, 100)]);
+    }
+
+    #[test]
+    fn test_live_order_count() {
+        let mut book = OrderBook::new("TEST", 1000);
+        assert!(book.is_empty());
+
+        for id in 1..=4 {
+            book.add_order(Order::new(id, 9990, 10, Side::Buy, OrderType::Limit))
+                .unwrap();
+        }
+        book.add_order(Order::new(5, 10010, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        assert_eq!(book.len(), 5);
+
+        // One full fill and one partial fill
+        book.add_order(Order::new(6, 0, 15, Side::Sell, OrderType::Market))
+            .unwrap();
+        assert_eq!(book.len(), 4);
+
+        book.cancel_order(2).unwrap();
+        book.cancel_order(5).unwrap();
+        assert_eq!(book.len(), 2);
+        assert_eq!(book.summary().order_count, 2);
+
+        // Dormant stops are not resting orders
+        let stop = Order::new(7, 0, 5, Side::Buy, OrderType::StopMarket).with_stop_price(20000);
+        book.add_order(stop).unwrap();
+        assert_eq!(book.len(), 2);
+
+        book.add_order(Order::new(8, 0, 20, Side::Sell, OrderType::Market))
+            .unwrap();
+        assert!(book.is_empty());
+        assert_eq!(book.summary().order_count, 0);
+    }
+
+    #[test]
+    fn test_iter_orders() {
+        let mut book = OrderBook::new("TEST", 1000);
+        let orders = [
+            Order::new(1, 9980, 10, Side::Buy, OrderType::Limit),
+            Order::new(2, 9990, 20, Side::Buy, OrderType::Limit),
+            Order::new(3, 10020, 30, Side::Sell, OrderType::Limit),
+            Order::new(4, 9990, 40, Side::Buy, OrderType::Limit),
+            Order::new(5, 10010, 50, Side::Sell, OrderType::Limit),
+        ];
+        for order in &orders {
+            book.add_order(order.clone()).unwrap();
+        }
+
+        let views: Vec<OrderView> = book.iter_orders().collect();
+        assert_eq!(views.len(), book.len());
+        let ids: Vec<u64> = views.iter().map(|view| view.order_id).collect();
+        assert_eq!(ids, vec![2, 4, 1, 5, 3]);
+
+        for view in &views {
+            let order = &orders[view.order_id as usize - 1];
+            assert_eq!(view.price, order.price);
+            assert_eq!(view.quantity, order.quantity);
+            assert_eq!(view.side, order.side());
+            assert_eq!(Some(*view), book.get_order(view.order_id));
+        }
+    }
+
+    #[test]
+    fn test_order_builder() {
+        let order = Order::builder()
+            .id(7)
+            .price(9900)
+            .quantity(10)
+            .side(Side::Buy)
+            .account(3)
+            .build()
+            .unwrap();
+        assert_eq!((order.order_id, order.price, order.quantity), (7, 9900, 10));
+        assert_eq!(order.side(), Side::Buy);
+        assert_eq!(order.order_type(), OrderType::Limit);
+        assert_eq!(order.account_id, Some(3));
+
+        let zero = Order::builder().id(8).price(9900).side(Side::Buy).build();
+        assert_eq!(zero.err(), Some(OrderError::ZeroQuantity(8)));
+
+        let unpriced = Order::builder().id(9).quantity(10).side(Side::Sell).build();
+        assert_eq!(unpriced.err(), Some(OrderError::MissingField("price")));
+
+        // Market orders need no price, stops need a trigger
+        let market = Order::builder()
+            .id(10)
+            .quantity(10)
+            .side(Side::Sell)
+            .order_type(OrderType::Market)
+            .build();
+        assert!(market.is_ok());
+        let stop = Order::builder()
+            .id(11)
+            .quantity(10)
+            .side(Side::Sell)
+            .order_type(OrderType::StopMarket)
+            .build();
+        assert_eq!(stop.err(), Some(OrderError::MissingStopPrice(11)));
+        let sideless = Order::builder().id(12).price(9900).quantity(1).build();
+        assert_eq!(sideless.err(), Some(OrderError::MissingField("side")));
+    }
+
+    #[test]
+    fn test_price_scale() {
+        let config = BookConfig {
+            price_scale: 100,
+            ..BookConfig::default()
+        };
+        let mut book = OrderBook::from_config("TEST", 1000, config);
+
+        let bid = book.from_decimal(99.0).unwrap();
+        assert_eq!(bid, 9900);
+        book.add_order(Order::new(1, bid, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10_005, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        assert_eq!(book.best_bid_decimal(), Some(99.0));
+        assert_eq!(book.best_ask_decimal(), Some(100.05));
+        assert_eq!(book.from_decimal(-1.0), None);
+
+        let display = book.summary().to_string();
+        assert!(display.contains("Best Bid: 99.00"));
+        assert!(display.contains("Best Ask: 100.05"));
+
+        // Matching still works on integer units
+        let executions = book
+            .add_order(Order::new(3, 10_005, 4, Side::Buy, OrderType::Limit))
+            .unwrap();
+        assert_eq!(executions[0].price, 10_005);
+        assert!(
+            book.summary()
+                .to_string()
+                .contains("Last Trade: 4 @ 100.05")
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_journal_replay() {
+        let path =
+            std::env::temp_dir().join(format!("orderbook-journal-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut book = OrderBook::new("TEST", 1000);
+        book.set_journal(Box::new(FileJournal::open(&path).unwrap()));
+
+        book.add_order(Order::new(1, 9990, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 9980, 20, Side::Buy, OrderType::Limit).with_account(4))
+            .unwrap();
+        book.add_order(Order::new(3, 10010, 15, Side::Sell, OrderType::Limit).with_expiry(50))
+            .unwrap();
+        let iceberg =
+            Order::new(4, 10020, 30, Side::Sell, OrderType::Limit).with_display_quantity(10);
+        book.add_order(iceberg).unwrap();
+        book.add_order(Order::new(5, 0, 15, Side::Sell, OrderType::Market))
+            .unwrap();
+        book.add_market_order_protected(Order::new(6, 0, 40, Side::Buy, OrderType::Market), 10020)
+            .unwrap();
+        assert!(
+            book.add_order(Order::new(2, 9970, 5, Side::Buy, OrderType::Limit))
+                .is_err()
+        );
+        book.amend_order(2, 9985, 12).unwrap();
+        book.cancel_order(99).unwrap_err();
+        assert_eq!(
+            book.amend_order(2, 10020, 12),
+            Err(OrderError::WouldCross(2))
+        );
+        book.reduce_order(98, 1).unwrap_err();
+        book.add_order(Order::new(7, 10030, 5, Side::Sell, OrderType::Limit).with_expiry(10))
+            .unwrap();
+        assert_eq!(book.expire_orders(20), vec![7]);
+
+        let mut journal = book.take_journal().unwrap();
+        let entries = journal.entries().unwrap();
+        // The rejected add, cancel, amend and reduce leave no entry
+        assert_eq!(entries.len(), 9);
+        assert!(entries.iter().all(|entry| !matches!(
+            entry.op,
+            JournalOp::CancelOrder { order_id: 99 }
+                | JournalOp::Reduce { order_id: 98, .. }
+                | JournalOp::Amend { price: 10020, .. }
+        )));
+        assert!(
+            entries
+                .iter()
+                .zip(1..)
+                .all(|(entry, seq)| entry.sequence == seq)
+        );
+
+        // Reading the file back gives the same entries
+        let mut reopened = FileJournal::open(&path).unwrap();
+        assert_eq!(reopened.entries().unwrap(), entries);
+
+        let replayed = OrderBook::new("TEST", 1000).replay(&mut reopened).unwrap();
+        assert_eq!(replayed.market_depth(10), book.market_depth(10));
+        // Amends restamp orders with the clock, so compare everything else
+        let resting = |book: &OrderBook| {
+            book.snapshot()
+                .orders
+                .into_iter()
+                .map(|record| (record.order_id, record.price, record.quantity, record.side))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(resting(&replayed), resting(&book));
+        let (original, copy) = (book.summary(), replayed.summary());
+        assert_eq!(copy.total_orders_processed, original.total_orders_processed);
+        assert_eq!(copy.total_quantity_matched, original.total_quantity_matched);
+        assert_eq!(replayed.last_trade(), book.last_trade());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[cfg(feature = "io")]
+    #[test]
+    fn test_replay_csv() {
+        let tape = "order_id,price,quantity,side,type,timestamp
+1,9990,10,buy,limit,100
+2,9980,20,B,Limit,110
+3,10010,15,sell,limit,120
+not,a,valid,row
+4,0,12,SELL,market,130
+5,10020,5,sell,post_only
+6,10000,8,sell,ioc,140
+
+7,10000,4,buy,limit,150
+8,10030,6,sell,fok,160
+";
+        let mut book = OrderBook::new("TEST", 1000);
+        let stats = replay_csv(tape.as_bytes(), &mut book);
+
+        // Order 7 is a buy at the base price, which is off the buy grid
+        assert_eq!(
+            stats,
+            ReplayStats {
+                orders_submitted: 7,
+                orders_rejected: 1,
+                malformed_rows: 2,
+                executions: 2,
+                quantity_matched: 12,
+            }
+        );
+        let (bids, asks) = book.market_depth(10);
+        assert_eq!(bids, vec![(9980, 18)]);
+        assert_eq!(asks, vec![(10010, 15)]);
+    }
+
+    #[cfg(feature = "fix")]
+    #[test]
+    fn test_parse_new_order_single() {
+        let msg = "8=FIX.4.4\x019=70\x0135=D\x0149=CLIENT\x0111=42\x0155=FUEL\x0154=2\x0138=25\x0140=2\x0144=10010\x0110=123\x01";
+        let order = parse_new_order_single(msg).unwrap();
+        assert_eq!(order.order_id, 42);
+        assert_eq!(order.price, 10010);
+        assert_eq!(order.quantity, 25);
+        assert_eq!(order.side(), Side::Sell);
+        assert_eq!(order.order_type(), OrderType::Limit);
+
+        // Market orders need no price
+        let market = parse_new_order_single("35=D\x0111=7\x0154=1\x0138=5\x0140=1\x01").unwrap();
+        assert_eq!(market.order_type(), OrderType::Market);
+        assert_eq!(market.side(), Side::Buy);
+
+        let mut book = OrderBook::new("FUEL", 1000);
+        book.add_order(order).unwrap();
+        assert_eq!(book.add_order(market).unwrap()[0].quantity, 5);
+    }
+
+    #[cfg(feature = "fix")]
+    #[test]
+    fn test_parse_new_order_single_rejections() {
+        // No OrderQty
+        let missing = parse_new_order_single("35=D\x0111=1\x0154=1\x0140=2\x0144=9990\x01");
+        assert_eq!(missing.err(), Some(FixError::MissingTag(38)));
+        // A limit order without a price
+        let unpriced = parse_new_order_single("11=1\x0154=1\x0138=5\x0140=2\x01");
+        assert_eq!(unpriced.err(), Some(FixError::MissingTag(44)));
+
+        let stop = parse_new_order_single("11=1\x0154=1\x0138=5\x0140=3\x0144=9990\x01");
+        assert_eq!(
+            stop.err(),
+            Some(FixError::InvalidValue {
+                tag: 40,
+                value: "3".to_string()
+            })
+        );
+        let cancel = parse_new_order_single("35=F\x0111=1\x01");
+        assert_eq!(cancel.err(), Some(FixError::WrongMsgType("F".to_string())));
+        let garbled = parse_new_order_single("11=1\x01junk\x01");
+        assert_eq!(
+            garbled.err(),
+            Some(FixError::MalformedField("junk".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_liquidity_up_to() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.add_order(Order::new(1, 10010, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 10020, 20, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(4, 10030, 30, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(5, 9990, 7, Side::Buy, OrderType::Limit))
+            .unwrap();
+
+        assert_eq!(
+            book.liquidity_up_to(Side::Buy, 10025),
+            (35, 15 * 10010 + 20 * 10020)
+        );
+        assert_eq!(book.liquidity_up_to(Side::Buy, 10000), (0, 0));
+        assert_eq!(book.liquidity_up_to(Side::Sell, 9990), (7, 7 * 9990));
+        assert_eq!(book.liquidity_up_to(Side::Sell, 9995), (0, 0));
+
+        // Hidden size is not counted
+        book.add_order(Order::new(6, 10010, 100, Side::Sell, OrderType::Limit).with_hidden())
+            .unwrap();
+        book.add_order(Order::new(7, 9980, 100, Side::Buy, OrderType::Limit).with_hidden())
+            .unwrap();
+        assert_eq!(
+            book.liquidity_up_to(Side::Buy, 10025),
+            (35, 15 * 10010 + 20 * 10020)
+        );
+        assert_eq!(book.liquidity_up_to(Side::Sell, 9980), (7, 7 * 9990));
+    }
+
+    #[test]
+    fn test_reduce_only_capped_to_position() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.enable_position_tracking();
+
+        // Account 1 goes long 10
+        book.add_order(Order::new(1, 10010, 10, Side::Sell, OrderType::Limit).with_account(2))
+            .unwrap();
+        book.add_order(Order::new(2, 0, 10, Side::Buy, OrderType::Market).with_account(1))
+            .unwrap();
+
+        // Selling 25 reduce-only can only flatten the 10
+        book.add_order(Order::new(3, 9990, 30, Side::Buy, OrderType::Limit).with_account(3))
+            .unwrap();
+        let reduce = Order::new(4, 9990, 25, Side::Sell, OrderType::Limit)
+            .with_account(1)
+            .with_reduce_only();
+        let executions = book.add_order(reduce).unwrap();
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].quantity, 10);
+        assert_eq!(book.market_depth(10).0, vec![(9990, 20)]);
+        assert!(book.market_depth(10).1.is_empty());
+
+        // Without tracking the flag has no effect
+        let mut untracked = OrderBook::new("TEST", 1000);
+        untracked
+            .add_order(Order::new(1, 9990, 30, Side::Buy, OrderType::Limit))
+            .unwrap();
+        let reduce = Order::new(2, 9990, 25, Side::Sell, OrderType::Limit).with_reduce_only();
+        assert_eq!(untracked.add_order(reduce).unwrap()[0].quantity, 25);
+    }
+
+    #[test]
+    fn test_reduce_only_rejected_when_flat() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.enable_position_tracking();
+        book.add_order(Order::new(1, 9990, 10, Side::Buy, OrderType::Limit).with_account(2))
+            .unwrap();
+
+        let reduce = Order::new(2, 9990, 5, Side::Sell, OrderType::Limit)
+            .with_account(1)
+            .with_reduce_only();
+        assert_eq!(
+            book.add_order(reduce).unwrap_err(),
+            OrderError::WouldIncreasePosition(2)
+        );
+
+        // Once short, a reduce-only sell still cannot add to the position
+        book.add_order(Order::new(3, 9990, 4, Side::Sell, OrderType::Limit).with_account(1))
+            .unwrap();
+        let reduce = Order::builder()
+            .id(4)
+            .price(9990)
+            .quantity(5)
+            .side(Side::Sell)
+            .account(1)
+            .reduce_only(true)
+            .build()
+            .unwrap();
+        assert_eq!(
+            book.add_order(reduce).unwrap_err(),
+            OrderError::WouldIncreasePosition(4)
+        );
+        assert_eq!(book.market_depth(10).0, vec![(9990, 6)]);
+    }
+
+    #[test]
+    fn test_position_tracking() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.enable_position_tracking();
+
+        book.add_order(Order::new(1, 10010, 10, Side::Sell, OrderType::Limit).with_account(2))
+            .unwrap();
+        book.add_order(Order::new(2, 10020, 10, Side::Sell, OrderType::Limit).with_account(3))
+            .unwrap();
+        // Sweeps all of account 2's order and part of account 3's
+        book.add_order(Order::new(3, 0, 14, Side::Buy, OrderType::Market).with_account(1))
+            .unwrap();
+
+        assert_eq!(book.position(1), 14);
+        assert_eq!(book.position(2), -10);
+        assert_eq!(book.position(3), -4);
+        assert_eq!(book.traded_volume(1), 14);
+        assert_eq!(book.traded_volume(2), 10);
+
+        // Account 1 sells some back to account 3, reducing both positions
+        book.add_order(Order::new(4, 9990, 6, Side::Buy, OrderType::Limit).with_account(3))
+            .unwrap();
+        book.add_order(Order::new(5, 9990, 6, Side::Sell, OrderType::Limit).with_account(1))
+            .unwrap();
+        assert_eq!(book.position(1), 8);
+        assert_eq!(book.position(3), 2);
+        assert_eq!(book.traded_volume(1), 20);
+        assert_eq!(book.traded_volume(3), 10);
+
+        assert_eq!(book.position(99), 0);
+        assert_eq!(OrderBook::new("TEST", 1000).traded_volume(1), 0);
+    }
+
+    #[test]
+    fn test_reduce_order_keeps_priority() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.add_order(Order::new(1, 10010, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10010, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        assert_eq!(book.reduce_order(1, 4).unwrap(), 6);
+        assert_eq!(book.market_depth(10).1, vec![(10010, 16)]);
+        assert_eq!(book.len(), 2);
+
+        // Order 1 is still first in the queue
+        let executions = book
+            .add_order(Order::new(3, 0, 8, Side::Buy, OrderType::Market))
+            .unwrap();
+        let fills: Vec<(u64, u64)> = executions
+            .iter()
+            .map(|e| (e.order_id, e.quantity))
+            .collect();
+        assert_eq!(fills, vec![(1, 6), (2, 2)]);
+
+        assert_eq!(
+            book.reduce_order(42, 1).unwrap_err(),
+            OrderError::OrderNotFound(42)
+        );
+    }
+
+    #[test]
+    fn test_reduce_order_to_zero_removes_it() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.add_order(Order::new(1, 9990, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+
+        assert_eq!(book.reduce_order(1, 10).unwrap(), 0);
+        assert!(book.is_empty());
+        assert!(book.market_depth(10).0.is_empty());
+        assert_eq!(book.best_bid(), None);
+
+        // The ID and pool slot are free again
+        book.add_order(Order::new(1, 9980, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        assert_eq!(book.market_depth(10).0, vec![(9980, 5)]);
+    }
+
+    #[test]
+    fn test_cancel_side() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.add_order(Order::new(1, 9990, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 9990, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 9950, 7, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(4, 10010, 8, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(5, 10030, 9, Side::Sell, OrderType::Limit))
+            .unwrap();
+        let stop = Order::new(6, 0, 3, Side::Buy, OrderType::StopMarket).with_stop_price(10050);
+        book.add_order(stop).unwrap();
+
+        assert_eq!(book.cancel_side(Side::Buy), 4);
+        let (bids, asks) = book.market_depth(10);
+        assert!(bids.is_empty());
+        assert_eq!(asks, vec![(10010, 8), (10030, 9)]);
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(book.best_ask(), Some(10010));
+        assert_eq!(book.len(), 2);
+        assert_eq!(book.summary().order_count, 2);
+
+        // Freed IDs and slots are usable again
+        book.add_order(Order::new(1, 9980, 4, Side::Buy, OrderType::Limit))
+            .unwrap();
+        assert_eq!(book.best_bid(), Some(9980));
+
+        assert_eq!(book.cancel_all(), 3);
+        assert!(book.is_empty());
+        assert_eq!(book.best_ask(), None);
+    }
+
+    #[test]
+    fn test_cancel_account() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.add_order(Order::new(1, 9990, 10, Side::Buy, OrderType::Limit).with_account(1))
+            .unwrap();
+        book.add_order(Order::new(2, 9990, 5, Side::Buy, OrderType::Limit).with_account(2))
+            .unwrap();
+        book.add_order(Order::new(3, 9980, 7, Side::Buy, OrderType::Limit).with_account(1))
+            .unwrap();
+        book.add_order(Order::new(4, 10010, 8, Side::Sell, OrderType::Limit).with_account(1))
+            .unwrap();
+
+        assert_eq!(book.cancel_account(1), 3);
+        let (bids, asks) = book.market_depth(10);
+        assert_eq!(bids, vec![(9990, 5)]);
+        assert!(asks.is_empty());
+        assert_eq!(book.cancel_account(1), 0);
+        assert_eq!(book.len(), 1);
+    }
+
+    #[test]
+    fn test_validate_order() {
+        let mut book = OrderBook::with_config("TEST", 1000, 10000, 5);
+        book.add_order(Order::new(1, 9995, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(
+            Order::new(2, 0, 3, Side::Buy, OrderType::StopMarket).with_stop_price(10050),
+        )
+        .unwrap();
+
+        let valid = Order::new(3, 9990, 5, Side::Buy, OrderType::Limit);
+        assert_eq!(book.validate_order(&valid), Ok(()));
+        // Validating leaves no trace, so the same order can still be added
+        assert_eq!(book.len(), 1);
+        book.add_order(valid).unwrap();
+
+        let resting_duplicate = Order::new(1, 9990, 5, Side::Buy, OrderType::Limit);
+        assert_eq!(
+            book.validate_order(&resting_duplicate),
+            Err(OrderError::DuplicateOrderId(1))
+        );
+        let stop_duplicate = Order::new(2, 10010, 5, Side::Sell, OrderType::Limit);
+        assert_eq!(
+            book.validate_order(&stop_duplicate),
+            Err(OrderError::DuplicateOrderId(2))
+        );
+
+        // Off the tick grid, and past the range of the grid
+        let misaligned = Order::new(4, 9993, 5, Side::Buy, OrderType::Limit);
+        assert_eq!(
+            book.validate_order(&misaligned),
+            Err(OrderError::NotTickAligned(9993))
+        );
+        let too_far = Order::new(4, 10000 + 5 * 1024, 5, Side::Sell, OrderType::PostOnly);
+        assert_eq!(
+            book.validate_order(&too_far),
+            Err(OrderError::PriceOutOfRange(10000 + 5 * 1024))
+        );
+
+        let unarmed = Order::new(4, 0, 5, Side::Sell, OrderType::StopMarket);
+        assert_eq!(
+            book.validate_order(&unarmed),
+            Err(OrderError::MissingStopPrice(4))
+        );
+
+        book.disable();
+        assert_eq!(
+            book.validate_order(&Order::new(4, 9990, 5, Side::Buy, OrderType::Limit)),
+            Err(OrderError::Disabled)
+        );
+    }
+
+    #[test]
+    fn test_market_depth_sparse_book() {
+        let mut book = OrderBook::new("TEST", 1000);
+        // A few levels scattered deep into each side, with gaps between them
+        for (i, offset) in [900u64, 905, 950, 1000, 1023].into_iter().enumerate() {
+            let id = i as u64 * 2;
+            book.add_order(Order::new(
+                id,
+                10000 - offset,
+                10 + id,
+                Side::Buy,
+                OrderType::Limit,
+            ))
+            .unwrap();
+            book.add_order(Order::new(
+                id + 1,
+                10000 + offset,
+                11 + id,
+                Side::Sell,
+                OrderType::Limit,
+            ))
+            .unwrap();
+        }
+        book.cancel_order(4).unwrap();
+
+        let (bids, asks) = book.market_depth(3);
+        assert_eq!(bids, vec![(9100, 10), (9095, 12), (9000, 16)]);
+        assert_eq!(asks, vec![(10900, 11), (10905, 13), (10950, 15)]);
+
+        // Asking for more levels than exist returns every occupied one, in
+        // the same order as walking the full-depth view
+        let (bids, asks) = book.market_depth(100);
+        assert_eq!(bids.len(), 4);
+        assert_eq!(asks.len(), 5);
+        let (detailed_bids, detailed_asks) = book.market_depth_detailed(100);
+        let flatten = |side: &[DepthLevel]| {
+            side.iter()
+                .map(|level| (level.price, level.quantity))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(bids, flatten(&detailed_bids));
+        assert_eq!(asks, flatten(&detailed_asks));
+        assert_eq!(book.market_depth(0), (vec![], vec![]));
+    }
+
+    #[test]
+    fn test_negative_prices() {
+        // Zero sits at book price 1_000_000, with the sides split around -0.50
+        let config = BookConfig {
+            base_price: 999_950,
+            price_scale: 100,
+            price_offset: 1_000_000,
+            ..BookConfig::default()
+        };
+        let mut book = OrderBook::from_config("SPREAD", 1000, config);
+
+        let bid = book.from_signed(-75).unwrap();
+        assert_eq!(book.from_decimal(-0.75), Some(bid));
+        book.add_order(Order::new(1, bid, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        // Positive prices still rest on the same book
+        let ask = book.from_signed(25).unwrap();
+        book.add_order(Order::new(2, ask, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        assert_eq!(book.best_bid_decimal(), Some(-0.75));
+        assert_eq!(book.best_ask_decimal(), Some(0.25));
+
+        let executions = book
+            .add_order(Order::new(
+                3,
+                book.from_signed(-80).unwrap(),
+                4,
+                Side::Sell,
+                OrderType::Limit,
+            ))
+            .unwrap();
+        assert_eq!(executions.len(), 1);
+        assert_eq!(book.to_signed(executions[0].price), -75);
+        assert_eq!(executions[0].quantity, 4);
+        assert_eq!(book.last_trade(), Some((bid, 4)));
+
+        let summary = book.summary().to_string();
+        assert!(summary.contains("Best Bid: -0.75"));
+        assert!(summary.contains("Last Trade: 4 @ -0.75"));
+
+        // Nothing below the offset's floor is representable
+        assert_eq!(book.from_signed(-1_000_001), None);
+        assert_eq!(OrderBook::new("TEST", 10).from_decimal(-1.0), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_bbo_change_callback() {
+        use std::sync::{Arc, Mutex};
+
+        let mut book = OrderBook::new("TEST", 1000);
+        book.add_order(Order::new(1, 9980, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+
+        let quotes = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&quotes);
+        book.on_bbo_change(Box::new(move |bid, ask| {
+            sink.lock().unwrap().push((bid, ask))
+        }));
+
+        // New best ask, then a deeper ask and a same-price bid that change nothing
+        book.add_order(Order::new(2, 10010, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 10020, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(4, 9980, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        // Improving the bid
+        book.add_order(Order::new(5, 9990, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        // A partial fill of the best ask leaves it in place
+        book.add_order(Order::new(6, 0, 4, Side::Buy, OrderType::Market))
+            .unwrap();
+        // Consuming the rest of it moves the ask up
+        book.add_order(Order::new(7, 0, 6, Side::Buy, OrderType::Market))
+            .unwrap();
+        // Cancelling a deeper bid changes nothing, emptying the best does
+        book.cancel_order(4).unwrap();
+        book.cancel_order(5).unwrap();
+        book.clear();
+
+        assert_eq!(
+            *quotes.lock().unwrap(),
+            vec![
+                (Some(9980), Some(10010)),
+                (Some(9990), Some(10010)),
+                (Some(9990), Some(10020)),
+                (Some(9980), Some(10020)),
+                (None, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_spread_bps() {
+        let mut book = OrderBook::with_config("TEST", 1000, 9950, 50);
+        assert_eq!(book.spread_bps(), None);
+
+        book.add_order(Order::new(1, 9900, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        assert_eq!(book.spread_bps(), None);
+        book.add_order(Order::new(2, 10000, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        assert_eq!(book.spread(), Some(100));
+        assert_eq!(book.spread_ticks(), Some(2));
+        // 100 / 9950 * 10_000
+        let bps = book.spread_bps().unwrap();
+        assert!((bps - 100.502_512_562_8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fifo_preserved_after_removal() {
+        let mut book = OrderBook::new("TEST", 1000);
+        for id in 1..=5 {
+            book.add_order(Order::new(id, 10010, 10, Side::Sell, OrderType::Limit))
+                .unwrap();
+        }
+
+        // Partially fill A (1), then cancel B (2) from the front half of the queue
+        book.add_order(Order::new(10, 0, 4, Side::Buy, OrderType::Market))
+            .unwrap();
+        book.cancel_order(2).unwrap();
+
+        let executions = book
+            .add_order(Order::new(11, 0, 35, Side::Buy, OrderType::Market))
+            .unwrap();
+        let fills: Vec<(u64, u64)> = executions
+            .iter()
+            .map(|e| (e.order_id, e.quantity))
+            .collect();
+        assert_eq!(fills, vec![(1, 6), (3, 10), (4, 10), (5, 9)]);
+    }
+
+    #[test]
+    fn test_execution_trade_ids() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.add_order(Order::new(1, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 10020, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(4, 9990, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+
+        let executions = book
+            .add_order(Order::new(5, 10020, 12, Side::Buy, OrderType::Limit))
+            .unwrap();
+        let ids: Vec<u64> = executions.iter().map(|e| e.trade_id).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+        for (execution, maker) in executions.iter().zip([1, 2, 3]) {
+            assert_eq!(execution.maker_order_id, maker);
+            assert_eq!(execution.order_id, maker);
+            assert_eq!(execution.taker_order_id, 5);
+            assert_eq!(execution.aggressor_side, Side::Buy);
+            assert_eq!(execution.side, Side::Sell);
+        }
+
+        // IDs keep counting across submissions and survive a snapshot
+        let mut book = OrderBook::restore(book.snapshot()).unwrap();
+        let executions = book
+            .add_order(Order::new(6, 0, 3, Side::Sell, OrderType::Market))
+            .unwrap();
+        assert_eq!(executions[0].trade_id, 4);
+        assert_eq!(executions[0].maker_order_id, 4);
+        assert_eq!(executions[0].taker_order_id, 6);
+        assert_eq!(executions[0].aggressor_side, Side::Sell);
+    }
+
+    #[test]
+    fn test_total_notional_matched() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.add_order(Order::new(1, 10010, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10020, 30, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 0, 25, Side::Buy, OrderType::Market))
+            .unwrap();
+
+        let expected = 10010 * 10 + 10020 * 15;
+        assert_eq!(book.total_notional_matched(), expected);
+        let summary = book.summary();
+        assert_eq!(summary.total_notional_matched, expected);
+        assert!(
+            summary
+                .to_string()
+                .contains(&format!("Matched Notional: {}", expected))
+        );
+
+        // Far past u64 once multiplied out
+        let mut book = OrderBook::with_config("BIG", 10, u64::MAX / 2, 1);
+        book.add_order(Order::new(
+            1,
+            u64::MAX / 2,
+            u64::MAX / 4,
+            Side::Sell,
+            OrderType::Limit,
+        ))
+        .unwrap();
+        book.add_order(Order::new(2, 0, u64::MAX / 4, Side::Buy, OrderType::Market))
+            .unwrap();
+        assert_eq!(
+            book.total_notional_matched(),
+            (u64::MAX / 2) as u128 * (u64::MAX / 4) as u128
+        );
+    }
+
+    #[test]
+    fn test_queue_ahead() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.add_order(Order::new(1, 9990, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 9990, 20, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 9990, 30, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(4, 9980, 40, Side::Buy, OrderType::Limit))
+            .unwrap();
+
+        assert_eq!(book.queue_ahead(1), Some(0));
+        assert_eq!(book.queue_ahead(2), Some(10));
+        assert_eq!(book.queue_ahead(3), Some(30));
+        // Other levels do not count
+        assert_eq!(book.queue_ahead(4), Some(0));
+
+        // A partial fill at the front shortens the queue
+        book.add_order(Order::new(5, 0, 4, Side::Sell, OrderType::Market))
+            .unwrap();
+        assert_eq!(book.queue_ahead(2), Some(6));
+        book.cancel_order(1).unwrap();
+        assert_eq!(book.queue_ahead(3), Some(20));
+        assert_eq!(book.queue_ahead(1), None);
+        assert_eq!(book.queue_ahead(99), None);
+    }
+
+    #[test]
+    fn test_max_order_quantity() {
+        let config = BookConfig {
+            max_order_quantity: Some(100),
+            ..BookConfig::default()
+        };
+        let mut book = OrderBook::from_config("TEST", 1000, config);
+
+        book.add_order(Order::new(1, 9990, 100, Side::Buy, OrderType::Limit))
+            .unwrap();
+        assert_eq!(
+            book.add_order(Order::new(2, 9990, 101, Side::Buy, OrderType::Limit))
+                .unwrap_err(),
+            OrderError::QuantityTooLarge(2)
+        );
+        // Market orders are capped the same way, traded or not
+        assert_eq!(
+            book.add_order(Order::new(3, 0, 101, Side::Sell, OrderType::Market))
+                .unwrap_err(),
+            OrderError::QuantityTooLarge(3)
+        );
+        // Amends are capped too, leaving the order as it was
+        book.add_order(Order::new(4, 9980, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        assert_eq!(
+            book.amend_order(4, 9980, 101),
+            Err(OrderError::QuantityTooLarge(4))
+        );
+        book.amend_order(4, 9980, 5).unwrap();
+        assert_eq!(book.market_depth(10).0, vec![(9990, 100), (9980, 5)]);
+    }
+
+    #[test]
+    fn test_max_order_notional() {
+        let config = BookConfig {
+            max_order_notional: Some(1_000_000),
+            ..BookConfig::default()
+        };
+        let mut book = OrderBook::from_config("TEST", 1000, config);
+
+        // 9_990 * 100 fits, 10_010 * 100 does not
+        book.add_order(Order::new(1, 9990, 100, Side::Buy, OrderType::Limit))
+            .unwrap();
+        assert_eq!(
+            book.add_order(Order::new(2, 10010, 100, Side::Sell, OrderType::Limit))
+                .unwrap_err(),
+            OrderError::NotionalTooLarge(2)
+        );
+        // Repricing a fitting order can push it over the limit
+        book.add_order(Order::new(6, 10010, 99, Side::Sell, OrderType::Limit))
+            .unwrap();
+        assert_eq!(
+            book.amend_order(6, 10110, 99),
+            Err(OrderError::NotionalTooLarge(6))
+        );
+        book.cancel_order(6).unwrap();
+
+        // With nothing traded a market order is only checked on quantity
+        book.add_order(Order::new(3, 0, 10, Side::Sell, OrderType::Market))
+            .unwrap();
+        // Now valued at the 9_990 last trade
+        assert_eq!(
+            book.add_order(Order::new(4, 0, 101, Side::Sell, OrderType::Market))
+                .unwrap_err(),
+            OrderError::NotionalTooLarge(4)
+        );
+        book.add_order(Order::new(5, 0, 90, Side::Sell, OrderType::Market))
+            .unwrap();
+        assert!(book.market_depth(10).0.is_empty());
+    }
+
+    #[test]
+    fn test_price_collar() {
+        let config = BookConfig {
+            price_collar_bps: Some(100),
+            ..BookConfig::default()
+        };
+        let mut book = OrderBook::from_config("TEST", 1000, config);
+
+        // Nothing to collar against yet
+        book.add_order(Order::new(1, 9000, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10000, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.cancel_order(1).unwrap();
+        book.add_order(Order::new(3, 9990, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+
+        // Mid is 9995, so the collar spans roughly 9895..=10095
+        book.add_order(Order::new(4, 9900, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(5, 10090, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        assert_eq!(
+            book.add_order(Order::new(6, 9890, 10, Side::Buy, OrderType::Limit))
+                .unwrap_err(),
+            OrderError::OutsideCollar(6)
+        );
+        assert_eq!(
+            book.add_order(Order::new(
+                7,
+                10200,
+                10,
+                Side::Buy,
+                OrderType::ImmediateOrCancel
+            ))
+            .unwrap_err(),
+            OrderError::OutsideCollar(7)
+        );
+        // An amend cannot walk a resting order out of the collar
+        assert_eq!(
+            book.amend_order(4, 9800, 10),
+            Err(OrderError::OutsideCollar(4))
+        );
+        assert_eq!(book.get_order(4).unwrap().price, 9900);
+        // Market orders are not collared
+        book.add_order(Order::new(8, 0, 5, Side::Sell, OrderType::Market))
+            .unwrap();
+        assert_eq!(book.market_depth(10).0, vec![(9990, 5), (9900, 10)]);
+    }
+
+    #[test]
+    fn test_render_ladder() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.add_order(Order::new(1, 9990, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 9990, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 9950, 7, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(4, 10010, 8, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(5, 10030, 9, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        let mut ladder = String::new();
+        book.render(&mut ladder, 10).unwrap();
+        let lines: Vec<&str> = ladder.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Price |   Quantity | Orders",
+                "10030 |          9 |      1",
+                "10010 |          8 |      1",
+                "-------- spread 20 --------",
+                " 9990 |         15 |      2",
+                " 9950 |          7 |      1",
+            ]
+        );
+
+        let mut empty = String::new();
+        OrderBook::new("TEST", 10).render(&mut empty, 10).unwrap();
+        assert!(empty.contains(" no spread "));
+    }
+
+    #[test]
+    fn test_add_order_at_uses_supplied_timestamp() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        book.add_order_at(Order::new(1, 10010, 10, Side::Sell, OrderType::Limit), 5)
+            .unwrap();
+        assert_eq!(book.get_order(1).unwrap().timestamp, 5);
+
+        let executions = book
+            .add_order_at(Order::new(2, 10010, 4, Side::Buy, OrderType::Limit), 7)
+            .unwrap();
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].timestamp, 7);
+        assert_eq!(book.get_order(1).unwrap().quantity, 6);
+
+        // With std the clock takes over again afterwards; without it the
+        // supplied timestamp stays in effect
+        let executions = book
+            .add_order(Order::new(3, 10010, 1, Side::Buy, OrderType::Limit))
+            .unwrap();
+        if cfg!(feature = "std") {
+            assert_ne!(executions[0].timestamp, 7);
+        } else {
+            assert_eq!(executions[0].timestamp, 7);
+        }
+    }
+
+    #[test]
+    fn test_traded_volume_at() {
+        let mut book = OrderBook::new("TEST", 1000);
+
+        book.add_order(Order::new(1, 9920, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 9900, 12, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 9900, 15, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        assert_eq!(book.traded_volume_at(9920), 5);
+        assert_eq!(book.traded_volume_at(9900), 10);
+        assert_eq!(book.traded_volume_at(9910), 0);
+        assert_eq!(book.get_order(2).unwrap().quantity, 2);
+
+        // The count outlives the orders that traded
+        book.cancel_order(2).unwrap();
+        assert_eq!(book.traded_volume_at(9900), 10);
+        let restored = OrderBook::restore(book.snapshot()).unwrap();
+        assert_eq!(restored.traded_volume_at(9920), 5);
+        assert_eq!(restored.traded_volume_at(9900), 10);
+
+        book.clear();
+        assert_eq!(book.traded_volume_at(9900), 0);
+    }
+
+    #[test]
+    fn test_market_to_limit() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.add_order(Order::new(1, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10020, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        // add_order cannot report a remainder, so it refuses the order whole
+        let order = Order::new(3, 0, 8, Side::Buy, OrderType::MarketToLimit);
+        assert_eq!(
+            book.add_order(order.clone()),
+            Err(OrderError::UnrestableRemainder(3))
+        );
+        assert_eq!(book.market_depth(10).1, vec![(10010, 5), (10020, 10)]);
+
+        // Only the top level trades; the deeper one is left alone
+        let result = book.add_market_order(order).unwrap();
+        assert_eq!(result.executions.len(), 1);
+        let fill = &result.executions[0];
+        assert_eq!((fill.price, fill.quantity), (10010, 5));
+        assert_eq!(book.best_ask(), Some(10020));
+        assert_eq!(book.market_depth(10).1, vec![(10020, 10)]);
+
+        // The remainder's price is an ask price, which no bid can rest at, so
+        // it is reported as unfilled
+        assert_eq!(result.unfilled_quantity, 3);
+        assert_eq!(book.best_bid(), None);
+        assert!(book.get_order(3).is_none());
+
+        // A worst price has no meaning for it on the protected entry point
+        let order = Order::new(6, 0, 1, Side::Buy, OrderType::MarketToLimit);
+        assert_eq!(
+            book.add_market_order_protected(order, 10020).unwrap_err(),
+            OrderError::NotMarketOrder(6)
+        );
+
+        book.add_order(Order::new(4, 9990, 6, Side::Buy, OrderType::Limit))
+            .unwrap();
+        // One that fills completely is fine on add_order
+        let order = Order::new(5, 0, 4, Side::Sell, OrderType::MarketToLimit);
+        let executions = book.add_order(order).unwrap();
+        assert_eq!((executions[0].price, executions[0].quantity), (9990, 4));
+        assert_eq!(book.get_order(4).unwrap().quantity, 2);
+
+        // With nothing on the other side there is no price to limit at
+        let mut empty = OrderBook::new("TEST", 1000);
+        let order = Order::new(1, 0, 4, Side::Sell, OrderType::MarketToLimit);
+        assert_eq!(
+            empty.add_order(order).unwrap_err(),
+            OrderError::NoOpposingPrice(1)
+        );
+        assert_eq!(empty.summary().total_orders_processed, 0);
+    }
+
+    #[test]
+    fn test_simulate_fill_matches_add_order() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.add_order(Order::new(1, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(
+            Order::new(2, 10010, 10, Side::Sell, OrderType::Limit).with_display_quantity(3),
+        )
+        .unwrap();
+        book.add_order(Order::new(3, 10010, 4, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(4, 10020, 8, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        let sim = book.simulate_fill(Side::Buy, 20, None);
+        let summary = book.summary();
+        let executions = book
+            .add_order(Order::new(5, 0, 20, Side::Buy, OrderType::Market))
+            .unwrap();
+
+        let fills = |executions: &[Execution]| {
+            executions
+                .iter()
+                .map(|e| (e.maker_order_id, e.price, e.quantity, e.trade_id))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(fills(&sim.executions), fills(&executions));
+        assert_eq!(sim.average_price, vwap(&executions));
+        assert_eq!(sim.unfilled_quantity, 0);
+        assert_eq!(summary.total_quantity_matched, 0);
+
+        // A limit stops the walk; what is left over is reported
+        book.add_order(Order::new(6, 10030, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        let sim = book.simulate_fill(Side::Buy, 20, Some(10020));
+        assert_eq!(sim.executions.len(), 1);
+        assert_eq!(
+            (sim.executions[0].price, sim.executions[0].quantity),
+            (10020, 7)
+        );
+        assert_eq!(sim.unfilled_quantity, 13);
+        assert_eq!(book.simulate_fill(Side::Sell, 5, None).average_price, None);
+    }
+
+    #[test]
+    fn test_simulate_fill_pro_rata() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.set_matching_policy(MatchingPolicy::ProRata);
+        for (id, quantity) in [(1, 30), (2, 10), (3, 20)] {
+            book.add_order(Order::new(id, 9990, quantity, Side::Buy, OrderType::Limit))
+                .unwrap();
+        }
+
+        let sim = book.simulate_fill(Side::Sell, 25, Some(9990));
+        let executions = book
+            .add_order(Order::new(4, 9990, 25, Side::Sell, OrderType::Limit))
+            .unwrap();
+        let fills = |executions: &[Execution]| {
+            executions
+                .iter()
+                .map(|e| (e.maker_order_id, e.quantity))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(fills(&sim.executions), fills(&executions));
+    }
+
+    #[test]
+    fn test_state_eq() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.add_order(Order::new(1, 9990, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(
+            Order::new(2, 9990, 12, Side::Buy, OrderType::Limit).with_display_quantity(4),
+        )
+        .unwrap();
+        book.add_order(Order::new(3, 10010, 7, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(
+            Order::new(4, 0, 5, Side::Buy, OrderType::StopMarket).with_stop_price(10050),
+        )
+        .unwrap();
+
+        let restored = OrderBook::restore(book.snapshot()).unwrap();
+        assert!(book.state_eq(&restored));
+        assert!(restored.state_eq(&book));
+
+        let mut extra = restored.fork();
+        extra
+            .add_order(Order::new(5, 9980, 1, Side::Buy, OrderType::Limit))
+            .unwrap();
+        assert!(!book.state_eq(&extra));
+        extra.cancel_order(5).unwrap();
+        assert!(book.state_eq(&extra));
+    }
+
+    #[test]
+    fn test_cancel_after_partial_fill_keeps_level_total() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.add_order(Order::new(1, 9990, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 9990, 20, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 9990, 4, Side::Sell, OrderType::Limit))
+            .unwrap();
+        assert_eq!(book.market_depth(1).0, vec![(9990, 26)]);
+
+        book.cancel_order(1).unwrap();
+        assert_eq!(book.market_depth(1).0, vec![(9990, 20)]);
+        book.add_order(Order::new(4, 9990, 15, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.cancel_order(2).unwrap();
+        assert_eq!(book.best_bid(), None);
+
+        // A level never reports less than nothing
+        let mut level = types::PriceLevel::new(9990, 4);
+        level.add_order(0, 5);
+        assert!(level.remove_order(0, 5));
+        assert_eq!(level.total_quantity, 0);
+        assert!(!level.remove_order(0, 5));
+    }
+
+    #[test]
+    fn test_not_tick_aligned() {
+        let config = BookConfig {
+            base_price: 10000,
+            tick_size: 5,
+            ..BookConfig::default()
+        };
+        let mut book = OrderBook::from_config("TEST", 1000, config);
+
+        let misaligned = Order::new(1, 9903, 10, Side::Buy, OrderType::Limit);
+        assert_eq!(
+            book.add_order(misaligned).unwrap_err(),
+            OrderError::NotTickAligned(9903)
+        );
+        book.add_order(Order::new(1, 9905, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        assert_eq!(book.best_bid(), Some(9905));
+
+        // On the grid but on the wrong side of the base is still out of range
+        let wrong_side = Order::new(2, 10005, 10, Side::Buy, OrderType::PostOnly);
+        assert_eq!(
+            book.add_order(wrong_side).unwrap_err(),
+            OrderError::PriceOutOfRange(10005)
+        );
+        assert_eq!(
+            book.amend_order(1, 9907, 10).unwrap_err(),
+            OrderError::NotTickAligned(9907)
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_depth_updates_rebuild_depth() {
+        use std::collections::BTreeMap;
+        use std::sync::{Arc, Mutex};
+
+        let mut book = OrderBook::new("TEST", 1000);
+        book.add_order(Order::new(1, 9990, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+
+        // Seed the mirror from the book as it stands at registration
+        let (bids, asks) = book.market_depth(100);
+        let mut mirror_bids: BTreeMap<u64, u64> = bids.into_iter().collect();
+        let mut mirror_asks: BTreeMap<u64, u64> = asks.into_iter().collect();
+
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&batches);
+        book.on_depth_update(Box::new(move |updates: &[DepthUpdate]| {
+            sink.lock().unwrap().push(updates.to_vec());
+        }));
+
+        book.add_order(Order::new(2, 9980, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(
+            Order::new(3, 10010, 9, Side::Sell, OrderType::Limit).with_display_quantity(3),
+        )
+        .unwrap();
+        book.add_order(Order::new(4, 10020, 6, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(5, 10010, 7, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(6, 0, 12, Side::Sell, OrderType::Market))
+            .unwrap();
+        book.amend_order(2, 9970, 4).unwrap();
+        book.reduce_order(4, 2).unwrap();
+        book.add_order(Order::new(7, 10030, 1, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.cancel_order(7).unwrap();
+
+        // Mass cancels report every level they change or empty
+        book.add_order(Order::new(8, 9960, 3, Side::Buy, OrderType::Limit).with_account(5))
+            .unwrap();
+        book.add_order(Order::new(9, 9970, 2, Side::Buy, OrderType::Limit).with_account(5))
+            .unwrap();
+        assert_eq!(book.cancel_account(5), 2);
+        assert!(book.cancel_side(Side::Sell) > 0);
+
+        // A rejected order changes no level and reports nothing
+        let reported = batches.lock().unwrap().len();
+        book.add_order(Order::new(2, 9990, 1, Side::Buy, OrderType::Limit))
+            .unwrap_err();
+        assert_eq!(batches.lock().unwrap().len(), reported);
+
+        for update in batches.lock().unwrap().iter().flatten() {
+            let mirror = match update.side {
+                Side::Buy => &mut mirror_bids,
+                Side::Sell => &mut mirror_asks,
+            };
+            if update.quantity == 0 {
+                mirror.remove(&update.price);
+            } else {
+                mirror.insert(update.price, update.quantity);
+            }
+        }
+        let (bids, asks) = book.market_depth(100);
+        assert_eq!(mirror_bids.into_iter().rev().collect::<Vec<_>>(), bids);
+        assert_eq!(mirror_asks.into_iter().collect::<Vec<_>>(), asks);
+
+        // Clearing removes every level
+        book.clear();
+        let batches = batches.lock().unwrap();
+        let last = batches.last().unwrap();
+        assert!(last.iter().all(|update| update.quantity == 0));
+        assert_eq!(last.len(), bids.len() + asks.len());
+    }
+
+    #[test]
+    fn test_sequence_breaks_wall_clock_ties() {
+        let mut book = OrderBook::new("TEST", 100);
+        book.add_order_at(Order::new(1, 10010, 5, Side::Sell, OrderType::Limit), 42)
+            .unwrap();
+        book.add_order_at(Order::new(2, 10010, 5, Side::Sell, OrderType::Limit), 42)
+            .unwrap();
+
+        let (first, second) = (book.get_order(1).unwrap(), book.get_order(2).unwrap());
+        assert_eq!(first.timestamp, second.timestamp);
+        assert!(first.sequence < second.sequence);
+
+        let executions = book
+            .add_order(Order::new(3, 10010, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        assert_eq!(executions[0].maker_order_id, 1);
+        assert!(book.get_order(1).is_none());
+        assert_eq!(book.get_order(2).unwrap().sequence, second.sequence);
+    }
+
+    #[test]
+    fn test_match_preview_matches_executions() {
+        let mut book = OrderBook::new("TEST", 100);
+        book.add_order(Order::new(1, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10010, 3, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 10020, 4, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(4, 10030, 6, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        let preview = book.match_preview(Side::Buy, 10020, 12, 10);
+        assert_eq!(preview, vec![(1, 10010, 5), (2, 10010, 3), (3, 10020, 4)]);
+        // The limit keeps the preview off the 10030 level however large the order
+        assert_eq!(book.match_preview(Side::Buy, 10020, 50, 10), preview);
+        assert_eq!(book.match_preview(Side::Buy, 10020, 12, 2), preview[..2]);
+        assert_eq!(book.best_ask(), Some(10010));
+
+        let executions = book
+            .add_order(Order::new(5, 10020, 12, Side::Buy, OrderType::Limit))
+            .unwrap();
+        let actual: Vec<_> = executions
+            .iter()
+            .map(|e| (e.maker_order_id, e.price, e.quantity))
+            .collect();
+        assert_eq!(actual, preview);
+    }
+
+    #[test]
+    fn test_min_quantity_kills_short_fill() {
+        let mut book = OrderBook::new("TEST", 100);
+        book.add_order(Order::new(1, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10020, 3, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        // Only 8 is available against a minimum of 10
+        let market = Order::new(3, 0, 12, Side::Buy, OrderType::Market).with_min_quantity(10);
+        let result = book.add_market_order(market).unwrap();
+        assert!(result.executions.is_empty());
+        assert_eq!(result.unfilled_quantity, 12);
+        let ioc =
+            Order::new(4, 10020, 12, Side::Buy, OrderType::ImmediateOrCancel).with_min_quantity(10);
+        assert!(book.add_order(ioc).unwrap().is_empty());
+        assert_eq!(book.market_depth(10).1, vec![(10010, 5), (10020, 3)]);
+
+        // A reachable minimum matches normally, up to the full size
+        let market = Order::new(5, 0, 12, Side::Buy, OrderType::Market).with_min_quantity(6);
+        let result = book.add_market_order(market).unwrap();
+        assert_eq!(result.executions.len(), 2);
+        assert_eq!(result.unfilled_quantity, 4);
+    }
+
+    #[test]
+    fn test_level_quantity_overflow_is_rejected() {
+        let mut book = OrderBook::new("TEST", 100);
+        book.add_order(Order::new(
+            1,
+            9990,
+            u64::MAX - 5,
+            Side::Buy,
+            OrderType::Limit,
+        ))
+        .unwrap();
+
+        let order = Order::new(2, 9990, 10, Side::Buy, OrderType::Limit);
+        assert_eq!(
+            book.validate_order(&order),
+            Err(OrderError::QuantityOverflow(2))
+        );
+        assert_eq!(book.add_order(order), Err(OrderError::QuantityOverflow(2)));
+        assert_eq!(book.market_depth(1).0, vec![(9990, u64::MAX - 5)]);
+
+        // Exactly filling the level is fine, and other levels are unaffected
+        book.add_order(Order::new(3, 9990, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(4, 9980, u64::MAX, Side::Buy, OrderType::Limit))
+            .unwrap();
+        assert_eq!(
+            book.amend_order(4, 9990, 1),
+            Err(OrderError::QuantityOverflow(4))
+        );
+        assert_eq!(book.get_order(4).unwrap().price, 9980);
+    }
+
+    #[test]
+    fn test_configured_orders_per_level() {
+        let config = BookConfig {
+            orders_per_level: 2,
+            ..BookConfig::default()
+        };
+        let mut book = OrderBook::from_config("TEST", 100, config);
+        assert_eq!(book.config().orders_per_level, 2);
+        for id in 1..=5 {
+            book.add_order(Order::new(id, 10010, id, Side::Sell, OrderType::Limit))
+                .unwrap();
+        }
+        book.add_order(Order::new(6, 10020, 1, Side::Sell, OrderType::Limit))
+            .unwrap();
+        let fresh = book.level(Side::Sell, 10020).unwrap();
+        assert!(fresh.capacity() >= 2 && fresh.capacity() < 1024);
+        assert!(book.level(Side::Sell, 10010).unwrap().capacity() >= 5);
+
+        // The grown level still matches in time priority
+        let executions = book
+            .add_order(Order::new(7, 10010, 6, Side::Buy, OrderType::Limit))
+            .unwrap();
+        let makers: Vec<u64> = executions.iter().map(|e| e.maker_order_id).collect();
+        assert_eq!(makers, vec![1, 2, 3]);
+        assert_eq!(book.market_depth(1).1, vec![(10010, 9)]);
+    }
+
+    #[test]
+    fn test_depth_in_range() {
+        let mut book = OrderBook::with_config("TEST", 100, 10_000, 50);
+        let mut id = 0;
+        for price in (9800..10_000).step_by(50) {
+            id += 1;
+            book.add_order(Order::new(id, price, id, Side::Buy, OrderType::Limit))
+                .unwrap();
+        }
+        for price in (10_000..=10_200).step_by(50) {
+            id += 1;
+            book.add_order(Order::new(id, price, id, Side::Sell, OrderType::Limit))
+                .unwrap();
+        }
+
+        let (bids, asks) = book.depth_in_range(9900, 10_100);
+        assert_eq!(bids, vec![(9950, 4), (9900, 3)]);
+        assert_eq!(asks, vec![(10_000, 5), (10_050, 6), (10_100, 7)]);
+
+        // Bounds between ticks and past the grid are clipped to it
+        let (bids, asks) = book.depth_in_range(9820, 9999);
+        assert_eq!(bids, vec![(9950, 4), (9900, 3), (9850, 2)]);
+        assert!(asks.is_empty());
+        let (bids, asks) = book.depth_in_range(0, u64::MAX);
+        assert_eq!((bids.len(), asks.len()), (4, 5));
+        assert_eq!(book.depth_in_range(10_100, 9900), (vec![], vec![]));
+    }
+
+    #[test]
+    fn test_partial_sweeps_never_leave_the_book_crossed() {
+        let mut book = OrderBook::new("TEST", 100);
+        let uncrossed = |book: &OrderBook| {
+            let (bids, asks) = book.market_depth(1);
+            assert!(!book.is_crossed());
+            assert_eq!(book.best_bid(), bids.first().map(|&(price, _)| price));
+            assert_eq!(book.best_ask(), asks.first().map(|&(price, _)| price));
+        };
+        book.add_order(Order::new(1, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10020, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 9990, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+
+        // Take the best ask and part of the next, then hit the cached best again
+        book.add_order(Order::new(4, 10020, 7, Side::Buy, OrderType::Limit))
+            .unwrap();
+        uncrossed(&book);
+        assert_eq!(book.best_ask(), Some(10020));
+        book.add_order(Order::new(
+            5,
+            10020,
+            3,
+            Side::Buy,
+            OrderType::ImmediateOrCancel,
+        ))
+        .unwrap();
+        uncrossed(&book);
+        assert_eq!(book.best_ask(), None);
+
+        // Rebuild the ask side and sweep it from the bid side as well
+        book.add_order(Order::new(6, 10000, 2, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(
+            7,
+            9990,
+            4,
+            Side::Sell,
+            OrderType::ImmediateOrCancel,
+        ))
+        .unwrap();
+        uncrossed(&book);
+        assert_eq!(book.best_bid(), Some(9990));
+        book.add_order_at(Order::new(8, 0, 10, Side::Buy, OrderType::Market), 1)
+            .unwrap();
+        uncrossed(&book);
+    }
+
+    #[test]
+    fn test_hidden_order_matches_but_is_not_shown() {
+        let mut book = OrderBook::new("TEST", 100);
+        let hidden = Order::new(1, 10010, 5, Side::Sell, OrderType::Limit).with_hidden();
+        assert!(hidden.hidden());
+        assert_ne!(hidden.flags() & types::HIDDEN_FLAG, 0);
+        assert_eq!(hidden.flags() & types::RESERVED_FLAGS, 0);
+        book.add_order(hidden).unwrap();
+        book.add_order(Order::new(2, 10010, 3, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 10020, 4, Side::Sell, OrderType::Limit).with_hidden())
+            .unwrap();
+
+        // Only the lit order shows, and the all-hidden level not at all
+        assert_eq!(book.market_depth(10).1, vec![(10010, 3)]);
+        assert_eq!(book.market_depth_detailed(10).1[0].order_count, 1);
+        assert_eq!(book.order_book_l3(10).1, vec![(10010, vec![(2, 3)])]);
+
+        // The hidden order keeps its time priority when matched
+        let executions = book
+            .add_order(Order::new(4, 10010, 6, Side::Buy, OrderType::Limit))
+            .unwrap();
+        let fills: Vec<_> = executions
+            .iter()
+            .map(|e| (e.maker_order_id, e.quantity))
+            .collect();
+        assert_eq!(fills, vec![(1, 5), (2, 1)]);
+        assert_eq!(book.market_depth(10).1, vec![(10010, 2)]);
+
+        book.add_order(Order::new(
+            5,
+            10020,
+            6,
+            Side::Buy,
+            OrderType::ImmediateOrCancel,
+        ))
+        .unwrap();
+        assert!(book.get_order(3).is_none());
+        assert_eq!(book.market_depth(10).1, vec![]);
+    }
+
+    #[test]
+    fn test_weighted_mid_leans_to_deep_levels() {
+        let mut book = OrderBook::new("TEST", 100);
+        assert_eq!(book.weighted_mid(2), None);
+        book.add_order(Order::new(1, 9990, 1, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 9980, 100, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 10010, 1, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(4, 10020, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        // The thin top levels alone balance at the naive mid
+        assert_eq!(book.mid_price(), Some(10_000.0));
+        assert_eq!(book.weighted_mid(1), Some(10_000.0));
+
+        // The fat second bid pulls the value down towards it
+        let weighted = book.weighted_mid(2).unwrap();
+        assert!((weighted - 1_118_200.0 / 112.0).abs() < 1e-9);
+        assert!(weighted < 9985.0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_reject_callback() {
+        use std::sync::{Arc, Mutex};
+
+        let mut book = OrderBook::new("TEST", 100);
+        let rejects = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&rejects);
+        book.on_reject(Box::new(move |order: &Order, error: &OrderError| {
+            sink.lock()
+                .unwrap()
+                .push((order.order_id, order.quantity, error.clone()));
+        }));
+
+        book.add_order(Order::new(1, 9990, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        let duplicate = book.add_order(Order::new(1, 9980, 7, Side::Buy, OrderType::Limit));
+        assert_eq!(duplicate, Err(OrderError::DuplicateOrderId(1)));
+        assert_eq!(
+            *rejects.lock().unwrap(),
+            vec![(1, 7, OrderError::DuplicateOrderId(1))]
+        );
+
+        // Accepted orders fire nothing, and the hook can be removed
+        book.add_order(Order::new(2, 9980, 7, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.clear_reject_callback();
+        book.add_order(Order::new(2, 9980, 7, Side::Buy, OrderType::Limit))
+            .unwrap_err();
+        assert_eq!(rejects.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_market_depth_ladder_fills_gaps() {
+        let mut book = OrderBook::new("TEST", 100);
+        assert_eq!(book.market_depth_ladder(3), (vec![], vec![]));
+        book.add_order(Order::new(1, 9990, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 9988, 7, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 10010, 4, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        let (bids, asks) = book.market_depth_ladder(4);
+        assert_eq!(bids, vec![(9990, 5), (9989, 0), (9988, 7), (9987, 0)]);
+        assert_eq!(asks, vec![(10010, 4), (10011, 0), (10012, 0), (10013, 0)]);
+
+        // The ladder stops at the edge of the grid
+        let (bids, _) = book.market_depth_ladder(2000);
+        assert_eq!(bids.len(), 1024 - 10);
+        assert_eq!(bids.last(), Some(&(8977, 0)));
+    }
+
+    #[test]
+    fn test_order_pool_grows_up_to_the_cap() {
+        let mut pool = OrderPool::new(1);
+        let first = pool.allocate(Order::new(1, 9990, 1, Side::Buy, OrderType::Limit));
+        assert!(
+            pool.allocate(Order::new(2, 9990, 1, Side::Buy, OrderType::Limit))
+                .is_none()
+        );
+        pool.grow(2);
+        assert_eq!((pool.total_capacity(), pool.available_capacity()), (3, 2));
+        assert_eq!(unsafe { pool.get(first.unwrap()) }.order_id, 1);
+
+        let fixed = OrderBook::new("TEST", 2);
+        let config = BookConfig {
+            max_pool_capacity: Some(6),
+            ..fixed.config()
+        };
+        let mut book = OrderBook::from_config("TEST", 2, config);
+        for id in 1..=6 {
+            book.add_order(Order::new(id, 10000 - id, 1, Side::Buy, OrderType::Limit))
+                .unwrap();
+        }
+        assert_eq!(book.market_depth(10).0.len(), 6);
+        assert_eq!(
+            book.add_order(Order::new(7, 9990, 1, Side::Buy, OrderType::Limit)),
+            Err(OrderError::PoolExhausted)
+        );
+
+        // Without a cap configured the pool stays fixed
+        let mut fixed = fixed;
+        for id in 1..=2 {
+            fixed
+                .add_order(Order::new(id, 9990, 1, Side::Buy, OrderType::Limit))
+                .unwrap();
+        }
+        assert_eq!(
+            fixed.add_order(Order::new(3, 9990, 1, Side::Buy, OrderType::Limit)),
+            Err(OrderError::PoolExhausted)
+        );
+    }
+
+    #[test]
+    fn test_total_side_quantities_track_the_book() {
+        let mut book = OrderBook::new("TEST", 100);
+        let shown = |book: &OrderBook| {
+            let (bids, asks) = book.market_depth(100);
+            let sum = |side: &[(u64, u64)]| side.iter().map(|&(_, qty)| qty).sum::<u64>();
+            (sum(&bids), sum(&asks))
+        };
+        book.add_order(Order::new(1, 9990, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 9980, 7, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(3, 10010, 4, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(
+            Order::new(4, 10020, 10, Side::Sell, OrderType::Limit).with_display_quantity(3),
+        )
+        .unwrap();
+        assert_eq!(
+            (book.total_bid_quantity(), book.total_ask_quantity()),
+            (12, 7)
+        );
+
+        // A partial match, including an iceberg showing its next slice
+        book.add_order(Order::new(
+            5,
+            10020,
+            8,
+            Side::Buy,
+            OrderType::ImmediateOrCancel,
+        ))
+        .unwrap();
+        assert_eq!(
+            (book.total_bid_quantity(), book.total_ask_quantity()),
+            (12, 2)
+        );
+        assert_eq!(shown(&book), (12, 2));
+
+        book.cancel_order(2).unwrap();
+        assert_eq!(
+            (book.total_bid_quantity(), book.total_ask_quantity()),
+            (5, 2)
+        );
+        assert_eq!(shown(&book), (5, 2));
+        book.clear();
+        assert_eq!(
+            (book.total_bid_quantity(), book.total_ask_quantity()),
+            (0, 0)
+        );
+    }
+
+    #[test]
+    fn test_all_or_none_order_is_passed_by_small_aggressors() {
+        let mut book = OrderBook::new("TEST", 100);
+        let aon = Order::new(1, 10010, 10, Side::Sell, OrderType::Limit).with_all_or_none();
+        assert!(aon.all_or_none());
+        assert_eq!(aon.flags() & types::RESERVED_FLAGS, 0);
+        book.add_order(aon).unwrap();
+        book.add_order(Order::new(2, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        // Too small for the all-or-none order, so it trades with the one behind
+        let executions = book
+            .add_order(Order::new(3, 10010, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        let fills: Vec<_> = executions
+            .iter()
+            .map(|e| (e.maker_order_id, e.quantity))
+            .collect();
+        assert_eq!(fills, vec![(2, 5)]);
+        assert_eq!(book.get_order(1).unwrap().quantity, 10);
+
+        // A FOK that could only complete by splitting it is killed
+        let fok = Order::new(4, 10010, 6, Side::Buy, OrderType::FillOrKill);
+        assert!(book.add_order(fok).unwrap().is_empty());
+        assert_eq!(book.get_order(1).unwrap().quantity, 10);
+
+        let executions = book
+            .add_order(Order::new(5, 10010, 10, Side::Buy, OrderType::Limit))
+            .unwrap();
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].maker_order_id, 1);
+        assert!(book.get_order(1).is_none());
+    }
+
+    #[test]
+    fn test_order_layout_for_default_widths() {
+        // Checked at compile time; update the layout comment on `Order` with it
+        const _: () = assert!(core::mem::size_of::<Order>() == 112);
+        assert_eq!(core::mem::size_of::<Price>(), 8);
+        assert_eq!(core::mem::size_of::<Quantity>(), 8);
+        assert_eq!(core::mem::size_of::<OrderId>(), 8);
+    }
+
+    #[test]
+    fn test_housekeeping_expires_and_triggers_in_one_tick() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.add_order(Order::new(1, 9990, 5, Side::Buy, OrderType::Limit).with_expiry(100))
+            .unwrap();
+        book.add_order(Order::new(2, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+        let stop = Order::new(3, 0, 3, Side::Buy, OrderType::StopMarket).with_stop_price(10005);
+        assert!(book.add_order(stop).unwrap().is_empty());
+
+        // Nothing is due yet
+        assert_eq!(book.housekeeping(50, 10000), HousekeepingReport::default());
+        assert_eq!(book.stop_order_count(), 1);
+
+        let report = book.housekeeping(100, 10005);
+        assert_eq!(
+            report,
+            HousekeepingReport {
+                expired: 1,
+                triggered: 1,
+                executions: 1,
+            }
+        );
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(book.stop_order_count(), 0);
+        assert_eq!(book.get_order(2).unwrap().quantity, 2);
+        assert_eq!(book.last_trade(), Some((10010, 3)));
+    }
+
+    #[test]
+    fn test_limit_with_unrestable_remainder_is_refused_before_matching() {
+        let mut book = OrderBook::new("TEST", 100);
+        book.add_order(Order::new(1, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        // A buy at 10020 can trade but has no bid level to rest on; it used
+        // to fill 5 and then fail, leaving the ask consumed behind an error
+        let order = Order::new(2, 10020, 8, Side::Buy, OrderType::Limit);
+        assert_eq!(
+            book.validate_order(&order),
+            Err(OrderError::PriceOutOfRange(10020))
+        );
+        assert_eq!(
+            book.add_order(order),
+            Err(OrderError::PriceOutOfRange(10020))
+        );
+        assert_eq!(book.market_depth(10).1, vec![(10010, 5)]);
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(book.last_trade(), None);
+
+        // One that fills completely is still accepted
+        let executions = book
+            .add_order(Order::new(3, 10020, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        assert_eq!(executions.len(), 1);
+        assert_eq!(book.best_ask(), None);
+
+        // When self-trade prevention cuts the fill short, the trades stand
+        // and the remainder is dropped
+        book.add_order(Order::new(4, 10010, 2, Side::Sell, OrderType::Limit).with_account(7))
+            .unwrap();
+        book.add_order(Order::new(5, 10010, 3, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.set_self_trade_policy(SelfTradePolicy::CancelResting);
+        let executions = book
+            .add_order(Order::new(6, 10020, 5, Side::Buy, OrderType::Limit).with_account(7))
+            .unwrap();
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].maker_order_id, 5);
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(book.best_ask(), None);
+    }
+
+    #[test]
+    fn test_uncross_clears_at_one_price() {
+        let mut book = OrderBook::new("TEST", 1000);
+        let orders = [
+            (1, 10020, 10, Side::Buy),
+            (2, 10010, 5, Side::Buy),
+            (3, 9990, 10, Side::Buy),
+            (4, 9980, 8, Side::Sell),
+            (5, 10000, 6, Side::Sell),
+            (6, 10030, 5, Side::Sell),
+        ];
+        for (id, price, quantity, side) in orders {
+            book.add_auction_order(Order::new(id, price, quantity, side, OrderType::Limit))
+                .unwrap();
+        }
+        assert_eq!(
+            book.add_auction_order(Order::new(7, 0, 5, Side::Buy, OrderType::Market)),
+            Err(OrderError::NotAuctionOrder(7))
+        );
+        assert_eq!(
+            book.add_order(Order::new(3, 9980, 5, Side::Buy, OrderType::Limit)),
+            Err(OrderError::DuplicateOrderId(3))
+        );
+        // The queue crosses, but the grid book is untouched
+        assert_eq!(book.auction_order_count(), 6);
+        assert_eq!(book.best_bid(), None);
+        assert!(!book.is_crossed());
+
+        // 10000 and 10010 both match 14 with a surplus of 1; the lower wins
+        let (price, executions) = book.uncross();
+        assert_eq!(price, 10000);
+        let fills: Vec<_> = executions
+            .iter()
+            .map(|exec| {
+                (
+                    exec.maker_order_id,
+                    exec.taker_order_id,
+                    exec.price,
+                    exec.quantity,
+                )
+            })
+            .collect();
+        assert_eq!(
+            fills,
+            vec![(1, 4, 10000, 8), (1, 5, 10000, 2), (2, 5, 10000, 4)]
+        );
+        assert_eq!(book.summary().total_quantity_matched, 14);
+        assert_eq!(book.last_trade(), Some((10000, 4)));
+
+        // Order 2's last unit and the orders that did not cross stay queued
+        assert_eq!(book.auction_order_count(), 3);
+        assert_eq!(book.uncross(), (0, Vec::new()));
+        book.cancel_order(6).unwrap();
+        book.add_auction_order(Order::new(8, 9990, 11, Side::Sell, OrderType::Limit))
+            .unwrap();
+        let (price, executions) = book.uncross();
+        assert_eq!(price, 9990);
+        assert_eq!(
+            executions
+                .iter()
+                .map(|exec| exec.quantity)
+                .sum::<Quantity>(),
+            11
+        );
+        assert_eq!(book.auction_order_count(), 0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_journal_replays_auction() {
+        let path =
+            std::env::temp_dir().join(format!("orderbook-auction-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut book = OrderBook::new("TEST", 1000);
+        book.set_journal(Box::new(FileJournal::open(&path).unwrap()));
+        book.add_auction_order(Order::new(1, 10010, 5, Side::Buy, OrderType::Limit))
+            .unwrap();
+        book.add_auction_order(
+            Order::new(2, 9990, 3, Side::Sell, OrderType::Limit).with_account(4),
+        )
+        .unwrap();
+        book.uncross();
+
+        let mut journal = book.take_journal().unwrap();
+        let ops: Vec<_> = journal
+            .entries()
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.op)
+            .collect();
+        assert!(matches!(
+            ops[..],
+            [
+                JournalOp::AuctionOrder { .. },
+                JournalOp::AuctionOrder { .. },
+                JournalOp::Uncross
+            ]
+        ));
+
+        let replayed = OrderBook::new("TEST", 1000)
+            .replay(journal.as_mut())
+            .unwrap();
+        assert_eq!(replayed.last_trade(), Some((9990, 3)));
+        assert_eq!(replayed.auction_order_count(), 1);
+        assert_eq!(
+            replayed.snapshot().auction_orders,
+            book.snapshot().auction_orders
+        );
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_liquidity_saturates_over_near_max_levels() {
+        let mut book = OrderBook::new("TEST", 100);
+        let near_max = Quantity::MAX - 1;
+        book.add_order(Order::new(1, 10010, near_max, Side::Sell, OrderType::Limit))
+            .unwrap();
+        book.add_order(Order::new(2, 10020, near_max, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        assert_eq!(book.liquidity_up_to(Side::Buy, 10020).0, Quantity::MAX);
+        // Fill-or-kill sizes the depth before matching
+        let order = Order::new(3, 10020, Quantity::MAX, Side::Buy, OrderType::FillOrKill);
+        let executions = book.add_order(order).unwrap();
+        assert_eq!(
+            executions
+                .iter()
+                .map(|exec| exec.quantity)
+                .collect::<Vec<_>>(),
+            vec![near_max, 1]
+        );
+        assert_eq!(book.get_order(2).unwrap().quantity, near_max - 1);
+    }
+
+    #[test]
+    fn test_simulate_fill_passes_iceberg_all_or_none() {
+        let mut book = OrderBook::new("TEST", 1000);
+        let iceberg = Order::new(1, 10010, 10, Side::Sell, OrderType::Limit)
+            .with_display_quantity(4)
+            .with_all_or_none();
+        book.add_order(iceberg).unwrap();
+        book.add_order(Order::new(2, 10010, 5, Side::Sell, OrderType::Limit))
+            .unwrap();
+
+        // Order 1 is judged on its whole 10, not a 4-lot peak, and passed by
+        let sim = book.simulate_fill(Side::Buy, 4, Some(10010));
+        let executions = book
+            .add_order(Order::new(3, 10010, 4, Side::Buy, OrderType::Limit))
+            .unwrap();
+        let fills = |executions: &[Execution]| {
+            executions
+                .iter()
+                .map(|e| (e.maker_order_id, e.quantity))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(fills(&executions), vec![(2, 4)]);
+        assert_eq!(fills(&sim.executions), fills(&executions));
+
+        // Enough for all of it fills it in both
+        let sim = book.simulate_fill(Side::Buy, 11, Some(10010));
+        let executions = book
+            .add_order(Order::new(4, 10010, 11, Side::Buy, OrderType::Limit))
+            .unwrap();
+        assert_eq!(fills(&executions), vec![(1, 10), (2, 1)]);
+        assert_eq!(fills(&sim.executions), fills(&executions));
+    }
+
+    #[test]
+    fn test_kill_switch_holds_triggered_stops() {
+        let mut book = OrderBook::new("TEST", 1000);
+        book.add_order(Order::new(1, 10010, 10, Side::Sell, OrderType::Limit))
+            .unwrap();
+        let stop_market =
+            Order::new(2, 0, 3, Side::Buy, OrderType::StopMarket).with_stop_price(10005);
+        let stop_limit =
+            Order::new(3, 10010, 2, Side::Buy, OrderType::StopLimit).with_stop_price(10005);
+        book.add_order(stop_market).unwrap();
+        book.add_order(stop_limit).unwrap();
+
+        // Both kinds stay dormant while the book is disabled
+        book.disable();
+        assert_eq!(book.housekeeping(0, 10005), HousekeepingReport::default());
+        assert_eq!(book.stop_order_count(), 2);
+        assert!(book.take_rejected_stops().is_empty());
+        assert_eq!(book.get_order(1).unwrap().quantity, 10);
+
+        book.enable();
+        let report = book.housekeeping(0, 10005);
+        assert_eq!((report.triggered, report.executions), (2, 2));
+        assert_eq!(book.stop_order_count(), 0);
+        assert_eq!(book.get_order(1).unwrap().quantity, 5);
+    }
 }
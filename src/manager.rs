@@ -0,0 +1,88 @@
+//! Routing layer owning one order book per symbol
+
+use std::collections::HashMap;
+
+use crate::orderbook::{OrderBook, OrderBookSummary};
+use crate::types::{Execution, Order, OrderError, OrderId};
+
+/// Owns a set of independent order books keyed by symbol
+///
+/// Each book keeps its own price grid and state; the manager only routes
+/// calls. Use [`book_mut`](Self::book_mut) for anything symbol-specific that
+/// is not forwarded here.
+#[derive(Default)]
+pub struct OrderBookManager {
+    books: HashMap<String, OrderBook>,
+}
+
+impl OrderBookManager {
+    /// Create an empty manager
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the book for `symbol`, creating it with the default price grid and
+    /// the given order capacity if it does not exist yet
+    pub fn get_or_create(&mut self, symbol: &str, capacity: usize) -> &mut OrderBook {
+        self.books
+            .entry(symbol.to_string())
+            .or_insert_with(|| OrderBook::new(symbol, capacity))
+    }
+
+    /// Register a preconfigured book under its own symbol, returning any book
+    /// it replaces
+    pub fn insert(&mut self, book: OrderBook) -> Option<OrderBook> {
+        self.books.insert(book.symbol().to_string(), book)
+    }
+
+    /// Remove and return the book for `symbol`
+    pub fn remove(&mut self, symbol: &str) -> Option<OrderBook> {
+        self.books.remove(symbol)
+    }
+
+    /// Get the book for `symbol`
+    pub fn book(&self, symbol: &str) -> Option<&OrderBook> {
+        self.books.get(symbol)
+    }
+
+    /// Get mutable access to the book for `symbol`
+    pub fn book_mut(&mut self, symbol: &str) -> Option<&mut OrderBook> {
+        self.books.get_mut(symbol)
+    }
+
+    /// Add an order to the book for `symbol`
+    pub fn add_order(&mut self, symbol: &str, order: Order) -> Result<Vec<Execution>, OrderError> {
+        self.routed(symbol)?.add_order(order)
+    }
+
+    /// Cancel an order on the book for `symbol`
+    pub fn cancel_order(&mut self, symbol: &str, order_id: OrderId) -> Result<(), OrderError> {
+        self.routed(symbol)?.cancel_order(order_id)
+    }
+
+    /// Summaries of every managed book, in no particular order
+    pub fn summaries(&self) -> impl Iterator<Item = OrderBookSummary> + '_ {
+        self.books.values().map(OrderBook::summary)
+    }
+
+    /// Symbols with a registered book, in no particular order
+    pub fn symbols(&self) -> impl Iterator<Item = &str> {
+        self.books.keys().map(String::as_str)
+    }
+
+    /// Number of managed books
+    pub fn len(&self) -> usize {
+        self.books.len()
+    }
+
+    /// Whether no books are registered
+    pub fn is_empty(&self) -> bool {
+        self.books.is_empty()
+    }
+
+    fn routed(&mut self, symbol: &str) -> Result<&mut OrderBook, OrderError> {
+        self.books
+            .get_mut(symbol)
+            .ok_or_else(|| OrderError::UnknownSymbol(symbol.to_string()))
+    }
+}
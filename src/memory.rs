@@ -1,8 +1,8 @@
 //! Memory management utilities for high-performance allocation
 
-use std::mem::MaybeUninit;
-use std::simd::Simd;
-use std::simd::cmp::SimdPartialEq;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem::MaybeUninit;
 
 use crate::types::Order;
 
@@ -10,6 +10,8 @@ use crate::types::Order;
 pub struct OrderPool {
     pool: Vec<MaybeUninit<Order>>,
     free_indices: Vec<usize>,
+    // One bit per slot, set while the slot holds a live order
+    allocated: Vec<u64>,
 }
 
 impl OrderPool {
@@ -24,34 +26,92 @@ impl OrderPool {
             free_indices.push(i);
         }
 
-        Self { pool, free_indices }
+        Self {
+            pool,
+            free_indices,
+            allocated: vec![0; capacity.div_ceil(64)],
+        }
     }
 
     #[inline]
     pub fn allocate(&mut self, order: Order) -> Option<usize> {
         if let Some(index) = self.free_indices.pop() {
             self.pool[index] = MaybeUninit::new(order);
+            self.allocated[index / 64] |= 1 << (index % 64);
             Some(index)
         } else {
             None
         }
     }
 
+    /// Return a slot to the free list
+    ///
+    /// Returns `false` and leaves the pool untouched if the slot is not
+    /// currently allocated, so a double free can never hand one slot out twice.
     #[inline]
-    pub fn deallocate(&mut self, index: usize) {
+    pub fn deallocate(&mut self, index: usize) -> bool {
+        if !self.is_allocated(index) {
+            return false;
+        }
+        self.allocated[index / 64] &= !(1 << (index % 64));
         self.free_indices.push(index);
+        true
+    }
+
+    /// Check whether `index` currently holds a live order
+    #[inline]
+    pub fn is_allocated(&self, index: usize) -> bool {
+        self.allocated
+            .get(index / 64)
+            .is_some_and(|word| word & (1 << (index % 64)) != 0)
     }
 
+    /// Safe lookup that returns `None` for free or out-of-range slots
+    #[inline]
+    pub fn try_get(&self, index: usize) -> Option<&Order> {
+        if self.is_allocated(index) {
+            Some(unsafe { self.pool[index].assume_init_ref() })
+        } else {
+            None
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `index` must refer to a slot that is currently allocated.
     #[inline]
     pub unsafe fn get(&self, index: usize) -> &Order {
         unsafe { self.pool[index].assume_init_ref() }
     }
 
+    /// # Safety
+    ///
+    /// `index` must refer to a slot that is currently allocated.
     #[inline]
     pub unsafe fn get_mut(&mut self, index: usize) -> &mut Order {
         unsafe { self.pool[index].assume_init_mut() }
     }
 
+    /// Add `additional` free slots after the existing ones
+    ///
+    /// Live orders keep their indices; the new slots are handed out lowest
+    /// first once the current free slots run out.
+    pub fn grow(&mut self, additional: usize) {
+        let old_capacity = self.pool.len();
+        let new_capacity = old_capacity + additional;
+        self.pool.resize_with(new_capacity, MaybeUninit::uninit);
+        self.free_indices
+            .splice(0..0, (old_capacity..new_capacity).rev());
+        self.allocated.resize(new_capacity.div_ceil(64), 0);
+    }
+
+    /// Free every slot, as if the pool had just been created
+    pub fn reset(&mut self) {
+        self.free_indices.clear();
+        self.free_indices.extend((0..self.pool.len()).rev());
+        self.allocated.fill(0);
+    }
+
     #[inline]
     pub fn available_capacity(&self) -> usize {
         self.free_indices.len()
@@ -63,146 +123,73 @@ impl OrderPool {
     }
 }
 
-/// SIMD-accelerated price lookup table
-/// Provides O(1) access to price levels for fast matching
-pub struct PriceLookupTable {
-    prices: Vec<Simd<u64, 4>>,
-    indices: Vec<Simd<u32, 4>>,
-    size: usize,
-}
+impl Clone for OrderPool {
+    fn clone(&self) -> Self {
+        // Only live slots are initialized, so copy those and leave the rest empty
+        let pool = self
+            .pool
+            .iter()
+            .enumerate()
+            .map(|(index, slot)| {
+                if self.is_allocated(index) {
+                    MaybeUninit::new(unsafe { slot.assume_init_ref() }.clone())
+                } else {
+                    MaybeUninit::uninit()
+                }
+            })
+            .collect();
 
-impl PriceLookupTable {
-    pub fn new(capacity: usize) -> Self {
-        // Round up to the nearest multiple of 4 for SIMD alignment
-        let vec_capacity = (capacity + 3) / 4;
         Self {
-            prices: vec![Simd::splat(0); vec_capacity],
-            indices: vec![Simd::splat(0); vec_capacity],
-            size: 0,
+            pool,
+            free_indices: self.free_indices.clone(),
+            allocated: self.allocated.clone(),
         }
     }
+}
 
-    #[inline]
-    pub fn insert(&mut self, price: u64, index: u32) {
-        let simd_idx = self.size / 4;
-        let lane = self.size % 4;
-
-        if simd_idx >= self.prices.len() {
-            // Resize if needed
-            self.prices.push(Simd::splat(0));
-            self.indices.push(Simd::splat(0));
-        }
-
-        // Update individual lane
-        let mut price_vec = self.prices[simd_idx].to_array();
-        let mut index_vec = self.indices[simd_idx].to_array();
-
-        price_vec[lane] = price;
-        index_vec[lane] = index;
-
-        self.prices[simd_idx] = Simd::from_array(price_vec);
-        self.indices[simd_idx] = Simd::from_array(index_vec);
+/// Occupancy bitmap over price-level indices
+///
+/// One bit per level, set while the level holds orders, so the next occupied
+/// level is found a word at a time with `trailing_zeros` instead of probing
+/// every slot.
+#[derive(Debug, Clone)]
+pub struct LevelBitmap {
+    words: Vec<u64>,
+}
 
-        self.size += 1;
+impl LevelBitmap {
+    pub fn new(levels: usize) -> Self {
+        Self {
+            words: vec![0; levels.div_ceil(64)],
+        }
     }
 
     #[inline]
-    pub fn find(&self, price: u64) -> Option<u32> {
-        let search_val = Simd::splat(price);
-
-        for i in 0..(self.size + 3) / 4 {
-            let price_vec = self.prices[i];
-            let index_vec = self.indices[i];
-
-            // SIMD comparison - creates a mask where price matches
-            let mask = price_vec.simd_eq(search_val);
-
-            if !mask.any() {
-                continue;
-            }
-
-            // Extract the matching lane index
-            for lane in 0..4 {
-                if mask.test(lane) && lane < self.size % 4 {
-                    return Some(index_vec.as_array()[lane]);
-                }
-            }
-        }
-
-        None
+    pub fn set(&mut self, idx: usize) {
+        self.words[idx / 64] |= 1 << (idx % 64);
     }
 
     #[inline]
-    pub fn remove(&mut self, price: u64) -> bool {
-        // Find the price
-        let mut found = false;
-        let mut idx = 0;
-        let mut lane = 0;
-
-        'outer: for i in 0..(self.size + 3) / 4 {
-            let price_vec = self.prices[i];
-            let search_val = Simd::splat(price);
-
-            // SIMD comparison
-            let mask = price_vec.simd_eq(search_val);
-
-            if !mask.any() {
-                continue;
-            }
+    pub fn clear(&mut self, idx: usize) {
+        self.words[idx / 64] &= !(1 << (idx % 64));
+    }
 
-            // Find which lane matched
-            for l in 0..4 {
-                if mask.test(l) && l < self.size % 4 {
-                    idx = i;
-                    lane = l;
-                    found = true;
-                    break 'outer;
-                }
-            }
+    /// Lowest occupied index at or after `start`
+    #[inline]
+    pub fn next_set(&self, start: usize) -> Option<usize> {
+        let mut word_idx = start / 64;
+        if word_idx >= self.words.len() {
+            return None;
         }
 
-        if found {
-            // Remove by swapping with the last element
-            let last_simd_idx = (self.size - 1) / 4;
-            let last_lane = (self.size - 1) % 4;
-
-            if idx == last_simd_idx && lane == last_lane {
-                // It's the last element, just decrement size
-                self.size -= 1;
-                return true;
+        // Mask off bits below `start` in its word
+        let mut word = self.words[word_idx] & (!0u64 << (start % 64));
+        loop {
+            if word != 0 {
+                return Some(word_idx * 64 + word.trailing_zeros() as usize);
             }
-
-            // Get the last element
-            let last_price_vec = self.prices[last_simd_idx].to_array();
-            let last_index_vec = self.indices[last_simd_idx].to_array();
-
-            let last_price = last_price_vec[last_lane];
-            let last_index = last_index_vec[last_lane];
-
-            // Replace the removed element with the last one
-            let mut price_vec = self.prices[idx].to_array();
-            let mut index_vec = self.indices[idx].to_array();
-
-            price_vec[lane] = last_price;
-            index_vec[lane] = last_index;
-
-            self.prices[idx] = Simd::from_array(price_vec);
-            self.indices[idx] = Simd::from_array(index_vec);
-
-            self.size -= 1;
-            return true;
+            word_idx += 1;
+            word = *self.words.get(word_idx)?;
         }
-
-        false
-    }
-
-    #[inline]
-    pub fn is_empty(&self) -> bool {
-        self.size == 0
-    }
-
-    #[inline]
-    pub fn len(&self) -> usize {
-        self.size
     }
 }
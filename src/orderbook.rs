@@ -1,58 +1,335 @@
 //! Core orderbook implementation using Vec instead of BTreeMap
 
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+use core::sync::atomic::{AtomicBool, Ordering};
 #[cfg(feature = "perf")]
-use std::time::{Duration, Instant};
+use core::time::Duration;
+#[cfg(feature = "perf")]
+use std::time::Instant;
 
-use crate::memory::OrderPool;
-use crate::types::{Execution, Order, OrderType, PriceLevel, Side, precise_time_ns};
+use crate::journal::JournalOp;
+#[cfg(feature = "std")]
+use crate::journal::{Journal, JournalEntry};
+use crate::memory::{LevelBitmap, OrderPool};
+use crate::types::{
+    DepthLevel, DepthUpdate, Event, Execution, Order, OrderError, OrderId, OrderType, OrderView,
+    Price, PriceLevel, Quantity, Side, clock_ns, monotonic_ns, vwap,
+};
 
 /// Configuration constants
-const PRICE_LEVELS: usize = 1024;
+const DEFAULT_PRICE_LEVELS: usize = 1024;
 const DEFAULT_ORDERS_PER_LEVEL: usize = 1024;
 
+/// One side of a market depth snapshot as `(price, quantity)` pairs
+pub type DepthSide = Vec<(Price, Quantity)>;
+
+/// One side of an order-by-order snapshot: each level's price and its
+/// `(order_id, quantity)` queue in time priority
+pub type L3Side = Vec<(Price, Vec<(OrderId, Quantity)>)>;
+
+/// Result of submitting an order: the executions it generated or why it was rejected
+pub type OrderOutcome = Result<Vec<Execution>, OrderError>;
+
+/// Callback receiving periodic statistics snapshots
+pub type StatsCallback = Box<dyn FnMut(&StatsSnapshot) + Send + Sync>;
+
+/// Callback receiving every execution as it is generated
+pub type ExecutionCallback = Box<dyn FnMut(&Execution) + Send + Sync>;
+
+/// Callback receiving the new best bid and ask whenever either changes
+pub type BboCallback = Box<dyn FnMut(Option<Price>, Option<Price>) + Send + Sync>;
+
+/// Callback receiving the levels an operation changed, bids then asks
+pub type DepthCallback = Box<dyn FnMut(&[DepthUpdate]) + Send + Sync>;
+
+/// Callback receiving each rejected order along with the reason
+pub type RejectCallback = Box<dyn FnMut(&Order, &OrderError) + Send + Sync>;
+
+/// Price grid an [`OrderBook`] is built on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BookConfig {
+    /// Boundary between the sides: buys rest below it, sells at or above
+    pub base_price: Price,
+    /// Price increment between adjacent levels
+    pub tick_size: Price,
+    /// Number of levels on each side of `base_price`
+    pub price_levels: usize,
+    /// Integer price units per whole unit of the quoted currency, e.g. 100
+    /// for prices in cents; only used when converting to and from decimals
+    pub price_scale: u64,
+    /// Book price that stands for zero, letting instruments that trade below
+    /// zero use prices under it; 0 keeps every price non-negative
+    pub price_offset: u64,
+    /// Largest quantity a single order may have
+    pub max_order_quantity: Option<Quantity>,
+    /// Largest price * quantity a single order may have, in book price units
+    pub max_order_notional: Option<u128>,
+    /// Furthest a limit order may be priced from the mid, or the last trade
+    /// while one side is empty, in basis points of that reference
+    pub price_collar_bps: Option<u64>,
+    /// Order slots each new price level allocates up front; a level holding
+    /// more grows its queue
+    pub orders_per_level: usize,
+    /// Largest the order pool may grow to, doubling each time it fills up;
+    /// `None` keeps the capacity the book was created with
+    pub max_pool_capacity: Option<usize>,
+}
+
+impl Default for BookConfig {
+    fn default() -> Self {
+        Self {
+            base_price: 10_000,
+            tick_size: 1,
+            price_levels: DEFAULT_PRICE_LEVELS,
+            price_scale: 1,
+            price_offset: 0,
+            max_order_quantity: None,
+            max_order_notional: None,
+            price_collar_bps: None,
+            orders_per_level: DEFAULT_ORDERS_PER_LEVEL,
+            max_pool_capacity: None,
+        }
+    }
+}
+
+/// What to do when an aggressor would trade against its own account's order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SelfTradePolicy {
+    /// Cancel the resting order and keep matching
+    #[default]
+    CancelResting,
+    /// Cancel the rest of the aggressor, leaving the resting order
+    CancelAggressing,
+    /// Cancel both orders
+    CancelBoth,
+}
+
+/// How an aggressor's quantity is shared out among the orders at one price
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MatchingPolicy {
+    /// Fill resting orders strictly in arrival order
+    #[default]
+    PriceTimeFifo,
+    /// Split fills in proportion to each resting order's visible size
+    ///
+    /// Shares are rounded down and the leftover units go one each to the
+    /// largest fractional remainders (largest-remainder method); equal
+    /// remainders are settled in time priority. A level the aggressor can
+    /// consume entirely is simply filled in full.
+    ProRata,
+}
+
+/// Registered stats callback plus the latencies gathered since the last snapshot
+struct StatsHook {
+    interval: u64,
+    callback: StatsCallback,
+    operations: u64,
+    latencies_ns: Vec<u64>,
+}
+
+/// Registered BBO callback plus the best bid and ask it last reported
+struct BboHook {
+    callback: BboCallback,
+    last: (Option<Price>, Option<Price>),
+}
+
+/// Registered depth callback, the quantity it last reported at each level
+/// and the levels touched since
+struct DepthHook {
+    callback: DepthCallback,
+    last_bids: Vec<Quantity>,
+    last_asks: Vec<Quantity>,
+    touched: Vec<(Side, usize)>,
+}
+
 /// High-performance orderbook implementation
 /// Uses a Vec-based approach for O(1) price level access
 pub struct OrderBook {
     symbol: String,
     order_pool: OrderPool,
     order_id_to_index: Vec<Option<usize>>, // Using a Vec for order_id -> index mapping
-    max_order_id: u64,
+    max_order_id: OrderId,
 
     // Vec-based price levels instead of BTreeMap
     buy_levels: Vec<Option<PriceLevel>>,
     sell_levels: Vec<Option<PriceLevel>>,
 
-    // Base price and tick size for price level indexing
-    base_price: u64,
-    tick_size: u64,
+    // Base price, tick size and level count for price level indexing
+    base_price: Price,
+    tick_size: Price,
+    price_levels: usize,
+
+    // Integer price units per whole unit and the price standing for zero,
+    // for decimal and signed conversions
+    price_scale: u64,
+    price_offset: u64,
+
+    // Per-order size limits and the fat-finger price collar
+    max_order_quantity: Option<Quantity>,
+    max_order_notional: Option<u128>,
+    price_collar_bps: Option<u64>,
+
+    // Queue slots allocated by each new price level
+    orders_per_level: usize,
+    // Ceiling for growing a full order pool, if it may grow at all
+    max_pool_capacity: Option<usize>,
 
     // Cache best prices for O(1) lookup
     best_bid_idx: Option<usize>,
     best_ask_idx: Option<usize>,
 
+    // Occupied level indices, for finding the next best level without a scan
+    buy_occupied: LevelBitmap,
+    sell_occupied: LevelBitmap,
+
+    // Minimum price overlap required before a limit order crosses
+    min_cross_increment: Price,
+
+    // Action taken when two orders from the same account would match
+    self_trade_policy: SelfTradePolicy,
+
+    // How fills are allocated within a price level
+    matching_policy: MatchingPolicy,
+
+    // Cap on a level's inline order queue before orders spill, if any
+    level_inline_capacity: Option<usize>,
+
+    // Dormant stop orders in arrival order, waiting for their trigger
+    stop_orders: Vec<Order>,
+
+    // Stop-limit orders cancelled on trigger, with the reason
+    rejected_stops: Vec<(OrderId, OrderError)>,
+
+    // Call-auction orders in arrival order, held off the grid until uncrossed
+    auction_orders: Vec<Order>,
+
+    // Price and size of the most recent execution; the price triggers stops
+    last_trade_price: Option<Price>,
+    last_trade_quantity: Option<Quantity>,
+
+    // Operator kill switch - rejects all new orders while set
+    disabled: AtomicBool,
+
+    // Optional telemetry callback fired every N operations
+    stats_hook: Option<StatsHook>,
+
+    // Optional trade feed fired for each execution in match order
+    execution_hook: Option<ExecutionCallback>,
+
+    // Optional quote feed fired when an operation moves the best bid or ask
+    bbo_hook: Option<BboHook>,
+
+    // Optional incremental depth feed fired with the levels an operation changed
+    depth_hook: Option<DepthHook>,
+
+    // Optional rejection feed fired whenever a submitted order is refused
+    reject_hook: Option<RejectCallback>,
+
+    // Optional write-ahead journal and the sequence number of its last entry
+    #[cfg(feature = "std")]
+    journal: Option<Box<dyn Journal>>,
+    journal_sequence: u64,
+
+    // Timestamp supplied through add_order_at, used in place of the clock
+    clock: Option<u64>,
+
+    // Net position and traded volume per account, once tracking is on
+    positions: Option<BTreeMap<u64, AccountPosition>>,
+
     // Performance monitoring
     #[cfg(feature = "perf")]
-    order_count: usize,
-    #[cfg(feature = "perf")]
     last_insert_time: Duration,
     #[cfg(feature = "perf")]
     last_match_time: Duration,
     #[cfg(feature = "perf")]
     last_cancel_time: Duration,
 
+    // Resting order counts, in total and per side
+    live_order_count: usize,
+    bid_order_count: usize,
+    ask_order_count: usize,
+    // Sum of `total_quantity` over each side's levels, wide enough that no
+    // number of full levels can overflow it
+    bid_quantity: u128,
+    ask_quantity: u128,
+
     // Statistics counters
     total_orders_processed: u64,
-    total_quantity_matched: u64,
+    total_quantity_matched: Quantity,
+    // Sum of price * quantity over every execution
+    total_notional_matched: u128,
+    // ID of the most recent execution; trade IDs start at 1
+    last_trade_id: u64,
+    // Last time-priority sequence handed out; sequences start at 1
+    sequence: u64,
+    // Quantity ever traded at each price level, parallel to the level arrays
+    buy_traded_volume: Vec<Quantity>,
+    sell_traded_volume: Vec<Quantity>,
 }
 
 impl OrderBook {
     /// Create a new orderbook with the given symbol and capacity
     pub fn new(symbol: &str, capacity: usize) -> Self {
-        let mut buy_levels = Vec::with_capacity(PRICE_LEVELS);
-        let mut sell_levels = Vec::with_capacity(PRICE_LEVELS);
+        Self::from_config(symbol, capacity, BookConfig::default())
+    }
+
+    /// Create an orderbook whose price grid starts at `base_price` and steps by
+    /// `tick_size`
+    ///
+    /// Buys rest below `base_price` and sells at or above it; prices that are
+    /// not a whole number of ticks from the base are rejected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tick_size` is zero.
+    pub fn with_config(symbol: &str, capacity: usize, base_price: Price, tick_size: Price) -> Self {
+        Self::from_config(
+            symbol,
+            capacity,
+            BookConfig {
+                base_price,
+                tick_size,
+                ..BookConfig::default()
+            },
+        )
+    }
+
+    /// Create an orderbook from a full [`BookConfig`]
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tick_size`, `price_levels` or `price_scale` is zero.
+    pub fn from_config(symbol: &str, capacity: usize, config: BookConfig) -> Self {
+        let BookConfig {
+            base_price,
+            tick_size,
+            price_levels,
+            price_scale,
+            price_offset,
+            max_order_quantity,
+            max_order_notional,
+            price_collar_bps,
+            orders_per_level,
+            max_pool_capacity,
+        } = config;
+        assert!(tick_size > 0, "tick_size must be positive");
+        assert!(price_levels > 0, "price_levels must be positive");
+        assert!(price_scale > 0, "price_scale must be positive");
+
+        let mut buy_levels = Vec::with_capacity(price_levels);
+        let mut sell_levels = Vec::with_capacity(price_levels);
 
         // Pre-allocate price level vectors
-        for _ in 0..PRICE_LEVELS {
+        for _ in 0..price_levels {
             buy_levels.push(None);
             sell_levels.push(None);
         }
@@ -70,33 +347,74 @@ impl OrderBook {
             max_order_id: 0,
             buy_levels,
             sell_levels,
-            base_price: 10_000,
-            tick_size: 1,
+            base_price,
+            tick_size,
+            price_levels,
+            price_scale,
+            price_offset,
+            max_order_quantity,
+            max_order_notional,
+            price_collar_bps,
+            orders_per_level,
+            max_pool_capacity,
             best_bid_idx: None,
             best_ask_idx: None,
-            #[cfg(feature = "perf")]
-            order_count: 0,
+            buy_occupied: LevelBitmap::new(price_levels),
+            sell_occupied: LevelBitmap::new(price_levels),
+            min_cross_increment: 0,
+            self_trade_policy: SelfTradePolicy::default(),
+            matching_policy: MatchingPolicy::default(),
+            level_inline_capacity: None,
+            stop_orders: Vec::new(),
+            rejected_stops: Vec::new(),
+            auction_orders: Vec::new(),
+            last_trade_price: None,
+            last_trade_quantity: None,
+            disabled: AtomicBool::new(false),
+            stats_hook: None,
+            execution_hook: None,
+            bbo_hook: None,
+            depth_hook: None,
+            reject_hook: None,
+            #[cfg(feature = "std")]
+            journal: None,
+            journal_sequence: 0,
+            clock: None,
+            positions: None,
             #[cfg(feature = "perf")]
             last_insert_time: Duration::default(),
             #[cfg(feature = "perf")]
             last_match_time: Duration::default(),
             #[cfg(feature = "perf")]
             last_cancel_time: Duration::default(),
+            live_order_count: 0,
+            bid_order_count: 0,
+            ask_order_count: 0,
+            bid_quantity: 0,
+            ask_quantity: 0,
             total_orders_processed: 0,
             total_quantity_matched: 0,
+            total_notional_matched: 0,
+            buy_traded_volume: vec![0; price_levels],
+            sell_traded_volume: vec![0; price_levels],
+            last_trade_id: 0,
+            sequence: 0,
         }
     }
 
     /// Convert price to index for buy_levels
     #[inline]
-    fn buy_price_to_idx(&self, price: u64) -> Option<usize> {
+    fn buy_price_to_idx(&self, price: Price) -> Option<usize> {
         // Ensure price is in valid range
         if price >= self.base_price {
             return None; // Price too high
         }
+        if !(self.base_price - price).is_multiple_of(self.tick_size) {
+            return None; // Off the tick grid
+        }
 
         let idx = ((self.base_price - price) / self.tick_size) as usize;
-        if idx < PRICE_LEVELS {
+        if idx < self.price_levels {
             Some(idx)
         } else {
             None // Out of range
@@ -105,249 +423,1110 @@ impl OrderBook {
 
     /// Convert price to index for sell_levels
     #[inline]
-    fn sell_price_to_idx(&self, price: u64) -> Option<usize> {
+    fn sell_price_to_idx(&self, price: Price) -> Option<usize> {
         // Ensure price is in valid range
         if price < self.base_price {
             return None; // Price too low
         }
+        if !(price - self.base_price).is_multiple_of(self.tick_size) {
+            return None; // Off the tick grid
+        }
 
         let idx = ((price - self.base_price) / self.tick_size) as usize;
-        if idx < PRICE_LEVELS {
+        if idx < self.price_levels {
             Some(idx)
         } else {
             None // Out of range
         }
     }
 
+    /// Convert price to index for the given side's levels
+    #[inline]
+    fn price_to_idx(&self, side: Side, price: Price) -> Option<usize> {
+        match side {
+            Side::Buy => self.buy_price_to_idx(price),
+            Side::Sell => self.sell_price_to_idx(price),
+        }
+    }
+
+    /// Why `price` has no level on an order's side: it is either between
+    /// ticks or past the range that side of the grid covers
+    #[cold]
+    fn off_grid_error(&self, price: Price) -> OrderError {
+        if price
+            .abs_diff(self.base_price)
+            .is_multiple_of(self.tick_size)
+        {
+            OrderError::PriceOutOfRange(price)
+        } else {
+            OrderError::NotTickAligned(price)
+        }
+    }
+
     /// Convert buy_levels index to price
     #[inline]
-    fn buy_idx_to_price(&self, idx: usize) -> u64 {
-        self.base_price - (idx as u64 * self.tick_size)
+    fn buy_idx_to_price(&self, idx: usize) -> Price {
+        self.base_price - (idx as Price * self.tick_size)
     }
 
     /// Convert sell_levels index to price
     #[inline]
-    fn sell_idx_to_price(&self, idx: usize) -> u64 {
-        self.base_price + (idx as u64 * self.tick_size)
+    fn sell_idx_to_price(&self, idx: usize) -> Price {
+        self.base_price + (idx as Price * self.tick_size)
     }
 
     /// Find the index of the best bid (highest buy price)
     #[inline]
     fn find_best_bid_idx(&self) -> Option<usize> {
         // For buy, we want the lowest index (highest price)
-        for i in 0..PRICE_LEVELS {
-            if self.buy_levels[i].is_some() {
-                return Some(i);
-            }
-        }
-        None
+        self.buy_occupied.next_set(0)
     }
 
     /// Find the index of the best ask (lowest sell price)
     #[inline]
     fn find_best_ask_idx(&self) -> Option<usize> {
         // For sell, we want the lowest index (lowest price)
-        for i in 0..PRICE_LEVELS {
-            if self.sell_levels[i].is_some() {
-                return Some(i);
-            }
-        }
-        None
+        self.sell_occupied.next_set(0)
     }
 
     /// Add a new order to the book
+    ///
+    /// A market-to-limit order that would leave a remainder is refused with
+    /// [`OrderError::UnrestableRemainder`], as that remainder can neither rest
+    /// nor be reported here; submit it with
+    /// [`add_market_order`](Self::add_market_order) instead.
+    #[inline]
+    pub fn add_order(&mut self, order: Order) -> Result<Vec<Execution>, OrderError> {
+        if order.order_type() == OrderType::MarketToLimit && self.strands_remainder(&order) {
+            let error = OrderError::UnrestableRemainder(order.order_id);
+            self.report_reject(&order, &error);
+            return Err(error);
+        }
+        self.submit(order, None, |_| false)
+            .map(|(executions, _)| executions)
+    }
+
+    /// Whether a market-to-limit order would not fill completely at the best
+    /// opposing price, which its own side of the grid cannot rest at
+    fn strands_remainder(&self, order: &Order) -> bool {
+        let side = order.side();
+        self.opposing_best(side).is_some_and(|price| {
+            self.price_to_idx(side, price).is_none()
+                && !self.fills_at_least(side, order.quantity, Some(price), order.quantity)
+        })
+    }
+
+    /// Add a new order stamped with a caller-supplied timestamp
+    ///
+    /// The order and every execution it generates carry `timestamp_ns`
+    /// instead of a reading of the clock. This is the entry point for
+    /// `no_std` builds, which have no clock: there the timestamp also stays in
+    /// effect for later amends and for orders added without one.
+    pub fn add_order_at(
+        &mut self,
+        mut order: Order,
+        timestamp_ns: u64,
+    ) -> Result<Vec<Execution>, OrderError> {
+        order.timestamp = timestamp_ns;
+        let previous = self.clock.replace(timestamp_ns);
+        let result = self.add_order(order);
+        if cfg!(feature = "std") {
+            self.clock = previous;
+        }
+        result
+    }
+
+    /// Hand out the next time-priority sequence
+    #[inline]
+    fn next_sequence(&mut self) -> u64 {
+        self.sequence += 1;
+        self.sequence
+    }
+
+    /// Current time in nanoseconds for stamping executions and amends
+    #[inline]
+    fn now(&self) -> u64 {
+        self.clock.unwrap_or_else(clock_ns)
+    }
+
+    /// Add a batch of orders in sequence, returning each order's outcome in
+    /// submission order
+    ///
+    /// Equivalent to calling [`add_order`](Self::add_order) for each order in
+    /// turn; a rejected order does not stop the rest of the batch.
+    pub fn add_orders(&mut self, orders: impl IntoIterator<Item = Order>) -> Vec<OrderOutcome> {
+        let orders = orders.into_iter();
+        let mut outcomes = Vec::with_capacity(orders.size_hint().0);
+        for order in orders {
+            outcomes.push(self.add_order(order));
+        }
+        outcomes
+    }
+
+    /// Submit a market order, reporting how much of it could not be filled
+    ///
+    /// Liquidity is taken until the order is filled or the opposite side is
+    /// exhausted; whatever is left over is returned as `unfilled_quantity`
+    /// rather than resting. A market-to-limit order is accepted too, and its
+    /// remainder after the best opposing level is likewise unfilled.
+    #[inline]
+    pub fn add_market_order(&mut self, order: Order) -> Result<MarketOrderResult, OrderError> {
+        self.add_market_order_until(order, |_| false)
+    }
+
+    /// Submit a market order that never trades at a price worse than `worst_price`
+    ///
+    /// A buy stops before any level priced above `worst_price` and a sell
+    /// before any level below it; the remainder is reported as unfilled and
+    /// the worse levels are left untouched.
+    pub fn add_market_order_protected(
+        &mut self,
+        order: Order,
+        worst_price: Price,
+    ) -> Result<MarketOrderResult, OrderError> {
+        self.submit_market(order, Some(worst_price), |_| false)
+    }
+
+    /// Submit a market order that the caller may abandon mid-sweep
+    ///
+    /// `should_stop` is consulted with the executions so far each time the
+    /// sweep is about to move to the next price level. Returning `true` halts
+    /// matching and reports the remainder as unfilled instead of consuming
+    /// deeper liquidity.
+    pub fn add_market_order_until<F>(
+        &mut self,
+        order: Order,
+        should_stop: F,
+    ) -> Result<MarketOrderResult, OrderError>
+    where
+        F: FnMut(&[Execution]) -> bool,
+    {
+        self.submit_market(order, None, should_stop)
+    }
+
+    /// Submit an order through the market-only entry points
+    fn submit_market<F>(
+        &mut self,
+        order: Order,
+        worst_price: Option<Price>,
+        should_stop: F,
+    ) -> Result<MarketOrderResult, OrderError>
+    where
+        F: FnMut(&[Execution]) -> bool,
+    {
+        let accepted = match order.order_type() {
+            OrderType::Market => true,
+            OrderType::MarketToLimit => worst_price.is_none(),
+            _ => false,
+        };
+        if !accepted {
+            let error = OrderError::NotMarketOrder(order.order_id);
+            self.report_reject(&order, &error);
+            return Err(error);
+        }
+
+        self.submit(order, worst_price, should_stop)
+            .map(|(executions, unfilled_quantity)| MarketOrderResult {
+                executions,
+                unfilled_quantity,
+            })
+    }
+
+    /// Process an order, reporting it to the reject callback if it is refused
+    ///
+    /// Returns the executions and the quantity that neither traded nor rested.
+    #[inline]
+    fn submit<F>(
+        &mut self,
+        order: Order,
+        worst_price: Option<Price>,
+        should_stop: F,
+    ) -> Result<(Vec<Execution>, Quantity), OrderError>
+    where
+        F: FnMut(&[Execution]) -> bool,
+    {
+        let submitted = self.reject_hook.is_some().then(|| order.clone());
+        let result = self.submit_timed(order, worst_price, should_stop);
+        if let (Err(error), Some(order)) = (&result, submitted) {
+            self.report_reject(&order, error);
+        }
+        result
+    }
+
+    #[inline]
+    fn report_reject(&mut self, order: &Order, error: &OrderError) {
+        if let Some(callback) = self.reject_hook.as_mut() {
+            callback(order, error);
+        }
+    }
+
+    /// Process an order, timing it when a stats callback is registered
+    #[inline]
+    fn submit_timed<F>(
+        &mut self,
+        order: Order,
+        worst_price: Option<Price>,
+        should_stop: F,
+    ) -> Result<(Vec<Execution>, Quantity), OrderError>
+    where
+        F: FnMut(&[Execution]) -> bool,
+    {
+        let result = if self.stats_hook.is_none() {
+            self.process_order(order, worst_price, should_stop)
+        } else {
+            let start = monotonic_ns();
+            let result = self.process_order(order, worst_price, should_stop);
+            self.record_operation(monotonic_ns() - start);
+            result
+        };
+        self.debug_check_uncrossed();
+        self.notify_market_data();
+        result
+    }
+
     #[inline]
-    pub fn add_order(&mut self, order: Order) -> Result<Vec<Execution>, String> {
+    fn process_order<F>(
+        &mut self,
+        mut order: Order,
+        worst_price: Option<Price>,
+        should_stop: F,
+    ) -> Result<(Vec<Execution>, Quantity), OrderError>
+    where
+        F: FnMut(&[Execution]) -> bool,
+    {
+        self.validate_order(&order)?;
+        #[cfg(feature = "std")]
+        if self.journal.is_some() {
+            self.journal_op(JournalOp::AddOrder {
+                order: OrderRecord::from(&order),
+                worst_price,
+            })?;
+        }
+
         #[cfg(feature = "perf")]
         let start_time = Instant::now();
 
-        // Ensure order ID is within our capacity
-        if order.order_id >= self.order_id_to_index.len() as u64 {
-            if order.order_id > self.max_order_id {
-                self.max_order_id = order.order_id;
+        self.grow_order_ids(order.order_id);
 
-                // Expand order ID lookup vector if needed
-                while self.order_id_to_index.len() <= order.order_id as usize {
-                    self.order_id_to_index.push(None);
-                }
-            }
+        self.total_orders_processed += 1;
+
+        if order.reduce_only() {
+            order.quantity = order.quantity.min(self.reducible_quantity(&order)?);
         }
 
-        // Check if order ID already exists
-        if self
-            .order_id_to_index
-            .get(order.order_id as usize)
-            .map(|opt| opt.is_some())
-            .unwrap_or(false)
+        // A minimum fill the book cannot supply kills the order untouched
+        if order.min_quantity > 0
+            && matches!(
+                order.order_type(),
+                OrderType::Market | OrderType::ImmediateOrCancel
+            )
         {
-            return Err(format!("Order ID {} already exists", order.order_id));
+            let limit = match order.order_type() {
+                OrderType::Market => worst_price,
+                _ => Some(order.price),
+            };
+            if !self.fills_at_least(order.side(), order.quantity, limit, order.min_quantity) {
+                return Ok((Vec::new(), order.quantity));
+            }
         }
 
-        self.total_orders_processed += 1;
+        // Stop orders wait off-book; one may already be triggered by the last trade
+        if matches!(
+            order.order_type(),
+            OrderType::StopMarket | OrderType::StopLimit
+        ) {
+            self.stop_orders.push(order);
+            let mut executions = Vec::new();
+            self.run_triggered_stops(None, &mut executions);
+            return Ok((executions, 0));
+        }
 
         // Handle market orders immediately
         if order.order_type() == OrderType::Market {
-            let executions = self.match_market_order(order);
+            let mut result = self.match_market_order(order, worst_price, should_stop);
+            self.record_last_trade(&result.executions);
+            self.run_triggered_stops(None, &mut result.executions);
             #[cfg(feature = "perf")]
             {
                 self.last_match_time = start_time.elapsed();
             }
-            return Ok(executions);
+            return Ok((result.executions, result.unfilled_quantity));
+        }
+
+        // A market-to-limit order takes the best opposing price as its limit
+        if order.order_type() == OrderType::MarketToLimit {
+            order.price = self
+                .opposing_best(order.side())
+                .ok_or(OrderError::NoOpposingPrice(order.order_id))?;
+        }
+
+        let (mut executions, unfilled_quantity) = self.execute_limit_order(order)?;
+
+        self.record_last_trade(&executions);
+        self.run_triggered_stops(None, &mut executions);
+
+        #[cfg(feature = "perf")]
+        {
+            self.last_insert_time = start_time.elapsed();
+        }
+        Ok((executions, unfilled_quantity))
+    }
+
+    /// Check an order the way [`add_order`](Self::add_order) does, without
+    /// changing the book
+    ///
+    /// Rejects orders while the kill switch is set, duplicate IDs, orders
+    /// over the configured size limits or too large for the level at their
+    /// price to hold, orders outside the price collar, stop
+    /// orders without a stop price, market-to-limit orders facing an empty
+    /// side, reduce-only orders with no position to reduce, and limit,
+    /// immediate-or-cancel and post-only orders priced off the grid that
+    /// cannot trade on entry. A limit order priced off the grid is only
+    /// accepted if it would fill completely, so that it is refused before
+    /// trading rather than after, when its remainder finds nowhere to rest.
+    /// Immediate-or-cancel orders never rest and may fill partially.
+    ///
+    /// The notional limit values market orders at the last trade price,
+    /// market-to-limit orders at the best opposing price and stop-market
+    /// orders at their stop price; a market order is only held to the
+    /// quantity limit before anything has traded.
+    pub fn validate_order(&self, order: &Order) -> Result<(), OrderError> {
+        if self.is_disabled() {
+            return Err(OrderError::Disabled);
+        }
+        self.check_order_id(order.order_id)?;
+        self.check_order_size(order)?;
+        self.check_level_room(order)?;
+        self.check_price_collar(order)?;
+        if order.reduce_only() {
+            self.reducible_quantity(order)?;
+        }
+
+        let side = order.side();
+        match order.order_type() {
+            OrderType::StopMarket | OrderType::StopLimit if order.stop_price == 0 => {
+                Err(OrderError::MissingStopPrice(order.order_id))
+            }
+            OrderType::MarketToLimit if self.opposing_best(side).is_none() => {
+                Err(OrderError::NoOpposingPrice(order.order_id))
+            }
+            OrderType::Limit | OrderType::ImmediateOrCancel | OrderType::PostOnly
+                if self.price_to_idx(side, order.price).is_none()
+                    && !self.is_marketable(side, order.price) =>
+            {
+                Err(self.off_grid_error(order.price))
+            }
+            OrderType::Limit
+                if self.price_to_idx(side, order.price).is_none()
+                    && !self.fills_at_least(
+                        side,
+                        order.quantity,
+                        Some(order.price),
+                        order.quantity,
+                    ) =>
+            {
+                Err(self.off_grid_error(order.price))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Enforce the configured per-order quantity and notional limits
+    fn check_order_size(&self, order: &Order) -> Result<(), OrderError> {
+        if self
+            .max_order_quantity
+            .is_some_and(|max| order.quantity > max)
+        {
+            return Err(OrderError::QuantityTooLarge(order.order_id));
+        }
+
+        let Some(max_notional) = self.max_order_notional else {
+            return Ok(());
+        };
+        let reference_price = match order.order_type() {
+            OrderType::Market => self.last_trade_price,
+            OrderType::MarketToLimit => self.opposing_best(order.side()),
+            OrderType::StopMarket => Some(order.stop_price),
+            _ => Some(order.price),
+        };
+        if reference_price
+            .is_some_and(|price| price as u128 * order.quantity as u128 > max_notional)
+        {
+            return Err(OrderError::NotionalTooLarge(order.order_id));
+        }
+        Ok(())
+    }
+
+    /// Reject an order whose quantity would overflow the depth, shown and
+    /// hidden, of an existing level at its price
+    fn check_level_room(&self, order: &Order) -> Result<(), OrderError> {
+        self.check_level_room_for(order.side(), order.price, order.quantity, order.order_id)
+    }
+
+    fn check_level_room_for(
+        &self,
+        side: Side,
+        price: Price,
+        quantity: Quantity,
+        order_id: OrderId,
+    ) -> Result<(), OrderError> {
+        let Some(level) = self.level(side, price) else {
+            return Ok(());
+        };
+        let room = Quantity::MAX - level.total_quantity.saturating_add(level.hidden_quantity);
+        if quantity > room {
+            return Err(OrderError::QuantityOverflow(order_id));
+        }
+        Ok(())
+    }
+
+    /// Reject a limit-priced order too far from the reference price
+    ///
+    /// The reference is the mid, or the last trade while a side is empty;
+    /// with neither there is nothing to collar against. The deviation is
+    /// measured against the signed reference, so it respects any price offset.
+    fn check_price_collar(&self, order: &Order) -> Result<(), OrderError> {
+        let Some(collar_bps) = self.price_collar_bps else {
+            return Ok(());
+        };
+        if !matches!(
+            order.order_type(),
+            OrderType::Limit
+                | OrderType::ImmediateOrCancel
+                | OrderType::FillOrKill
+                | OrderType::PostOnly
+        ) {
+            return Ok(());
+        }
+        let Some(reference) = self
+            .mid_price()
+            .or(self.last_trade_price.map(|price| price as f64))
+        else {
+            return Ok(());
+        };
+
+        let scale = (reference - self.price_offset as f64).abs();
+        let deviation_bps = (order.price as f64 - reference).abs() * 10_000.0 / scale;
+        if scale > 0.0 && deviation_bps > collar_bps as f64 {
+            return Err(OrderError::OutsideCollar(order.order_id));
+        }
+        Ok(())
+    }
+
+    /// Largest quantity a reduce-only order may trade, the whole order while
+    /// positions are not tracked
+    fn reducible_quantity(&self, order: &Order) -> Result<Quantity, OrderError> {
+        let Some(positions) = &self.positions else {
+            return Ok(order.quantity);
+        };
+        let position = order
+            .account_id
+            .and_then(|account| positions.get(&account))
+            .map_or(0, |account| account.net);
+
+        // A sell reduces a long position, a buy a short one
+        let reducible = match order.side() {
+            Side::Buy => position.min(0).unsigned_abs(),
+            Side::Sell => position.max(0).unsigned_abs(),
+        };
+        if reducible == 0 {
+            return Err(OrderError::WouldIncreasePosition(order.order_id));
         }
+        Ok(Quantity::try_from(reducible).unwrap_or(Quantity::MAX))
+    }
 
+    /// Match a limit-priced order and rest or drop whatever is left
+    ///
+    /// A market-to-limit order arrives here already priced at the best
+    /// opposing level. Since bids sit below the base price and asks at or
+    /// above it, its remainder can only be left once that level is used up,
+    /// at a price its own side cannot hold; it is then reported as unfilled.
+    ///
+    /// Returns the executions and the quantity that neither traded nor rested.
+    fn execute_limit_order(
+        &mut self,
+        order: Order,
+    ) -> Result<(Vec<Execution>, Quantity), OrderError> {
         // For limit orders, try to match first
         let side = order.side();
+        let order_type = order.order_type();
         let price = order.price;
+        // Fill-or-kill checks available liquidity before touching any resting order
+        if order_type == OrderType::FillOrKill
+            && !self.fills_at_least(side, order.quantity, Some(price), order.quantity)
+        {
+            return Ok((Vec::new(), order.quantity));
+        }
+
         let mut remaining_order = order.clone();
         let mut executions = Vec::with_capacity(10);
 
         // Try to match the order
-        match side {
-            Side::Buy => {
-                if let Some(best_ask_idx) = self.best_ask_idx {
-                    let best_ask = self.sell_idx_to_price(best_ask_idx);
-                    if price >= best_ask {
-                        executions = self.match_limit_order(&mut remaining_order);
-                    }
-                }
-            }
-            Side::Sell => {
-                if let Some(best_bid_idx) = self.best_bid_idx {
-                    let best_bid = self.buy_idx_to_price(best_bid_idx);
-                    if price <= best_bid {
-                        executions = self.match_limit_order(&mut remaining_order);
-                    }
-                }
+        if self.is_marketable(side, price) {
+            // Post-only orders must add liquidity, never take it
+            if order_type == OrderType::PostOnly {
+                return Err(OrderError::WouldCross(order.order_id));
             }
+            executions = self.match_limit_order(&mut remaining_order);
         }
 
-        // If there's remaining quantity, add to the book
-        if remaining_order.quantity > 0 {
-            // Convert price to index
-            let price_idx = match side {
-                Side::Buy => self.buy_price_to_idx(price),
-                Side::Sell => self.sell_price_to_idx(price),
-            };
+        // Catch price-formation regressions in the matching loops
+        #[cfg(any(debug_assertions, feature = "strict"))]
+        if let Err(e) = check_execution_prices(side, price, &executions) {
+            panic!("{}", e);
+        }
 
-            // Check if price is within range
-            if price_idx.is_none() {
-                return Err(format!("Price {} is outside the allowed range", price));
+        // If there's remaining quantity, add to the book. Immediate-or-cancel
+        // orders never rest, so their remainder is dropped instead.
+        let mut unfilled_quantity = 0;
+        if remaining_order.quantity > 0 {
+            if order_type == OrderType::ImmediateOrCancel {
+                if executions.is_empty() && self.price_to_idx(side, price).is_none() {
+                    return Err(self.off_grid_error(price));
+                }
+                unfilled_quantity = remaining_order.quantity;
+            } else if self.price_to_idx(side, price).is_none() {
+                // Nowhere to rest: a market-to-limit order is priced at the
+                // opposing best, across the base price, or self-trade
+                // prevention left a limit order short of the full fill
+                // validation found. The trades stand, so the remainder is
+                // reported as unfilled rather than failing; add_order refuses
+                // market-to-limit orders that would end up here.
+                unfilled_quantity = remaining_order.quantity;
+            } else {
+                remaining_order.conceal_reserve();
+                self.insert_resting(remaining_order)?;
             }
+        }
 
-            let price_idx = price_idx.unwrap();
+        Ok((executions, unfilled_quantity))
+    }
 
-            // Allocate from the memory pool
-            if let Some(index) = self.order_pool.allocate(remaining_order.clone()) {
-                self.order_id_to_index[remaining_order.order_id as usize] = Some(index);
+    /// Resting quantity, iceberg reserves included, that an order on `side`
+    /// could take, up to `limit_price` if one is given, saturating at
+    /// `Quantity::MAX`
+    fn available_quantity(&self, side: Side, limit_price: Option<Price>) -> Quantity {
+        let total = |(_, level): (Price, &PriceLevel)| level.total_quantity + level.hidden_quantity;
+        let sum = |quantity: Quantity, level| quantity.saturating_add(total(level));
+        match limit_price {
+            Some(limit_price) => self.opposite_levels(side, limit_price).fold(0, sum),
+            None => self.all_opposite_levels(side).fold(0, sum),
+        }
+    }
 
-                // Add to the appropriate side of the book
-                match side {
-                    Side::Buy => {
-                        // Get or create price level
-                        let price_level = self.buy_levels[price_idx].get_or_insert_with(|| {
-                            PriceLevel::new(price, DEFAULT_ORDERS_PER_LEVEL)
-                        });
+    /// Whether an order of `quantity` on `side`, limited at `limit_price` if
+    /// one is given, would fill at least `needed` on entry
+    ///
+    /// The opposing depth settles it when it falls short; otherwise the fill
+    /// is simulated, since all-or-none orders in that depth may be passed by.
+    fn fills_at_least(
+        &self,
+        side: Side,
+        quantity: Quantity,
+        limit_price: Option<Price>,
+        needed: Quantity,
+    ) -> bool {
+        if self.available_quantity(side, limit_price) < needed {
+            return false;
+        }
+        let unfilled = self
+            .simulate_fill(side, quantity, limit_price)
+            .unfilled_quantity;
+        quantity - unfilled >= needed
+    }
 
-                        if !price_level.add_order(index, remaining_order.quantity) {
-                            return Err("Price level full".to_string());
-                        }
+    /// Best price on the side an order on `side` trades against
+    #[inline]
+    fn opposing_best(&self, side: Side) -> Option<Price> {
+        match side {
+            Side::Buy => self.best_ask(),
+            Side::Sell => self.best_bid(),
+        }
+    }
 
-                        // Update best bid cache
-                        if self.best_bid_idx.is_none() || price_idx < self.best_bid_idx.unwrap() {
-                            self.best_bid_idx = Some(price_idx);
-                        }
-                    }
-                    Side::Sell => {
-                        // Get or create price level
-                        let price_level = self.sell_levels[price_idx].get_or_insert_with(|| {
-                            PriceLevel::new(price, DEFAULT_ORDERS_PER_LEVEL)
-                        });
+    /// Whether a limit order at `price` would trade against the opposing best,
+    /// including the minimum cross increment
+    #[inline]
+    fn is_marketable(&self, side: Side, price: Price) -> bool {
+        match side {
+            Side::Buy => self.best_ask_idx.is_some_and(|best_ask_idx| {
+                price >= self.sell_idx_to_price(best_ask_idx) + self.min_cross_increment
+            }),
+            Side::Sell => self.best_bid_idx.is_some_and(|best_bid_idx| {
+                price >= self.min_cross_increment
+                    && price - self.min_cross_increment <= self.buy_idx_to_price(best_bid_idx)
+            }),
+        }
+    }
 
-                        if !price_level.add_order(index, remaining_order.quantity) {
-                            return Err("Price level full".to_string());
-                        }
+    /// Remember the price and size of the final execution in a matching pass
+    #[inline]
+    fn record_last_trade(&mut self, executions: &[Execution]) {
+        if let Some(last) = executions.last() {
+            self.last_trade_price = Some(last.price);
+            self.last_trade_quantity = Some(last.quantity);
+        }
+    }
 
-                        // Update best ask cache
-                        if self.best_ask_idx.is_none() || price_idx < self.best_ask_idx.unwrap() {
-                            self.best_ask_idx = Some(price_idx);
-                        }
-                    }
+    /// Fire every stop the last trade price, or the `reference` price when
+    /// one is given, has moved through, appending the resulting executions
+    /// and returning how many stops fired
+    ///
+    /// Stops are checked in arrival order and fired one at a time: stop-market
+    /// orders sweep as market orders, while stop-limit orders become limit
+    /// orders at their own price, matching immediately if marketable and
+    /// resting otherwise. A stop-limit that would be refused as a new limit
+    /// order, such as one that can neither fill completely nor rest at its
+    /// price, is cancelled and reported through
+    /// [`take_rejected_stops`](Self::take_rejected_stops).
+    ///
+    /// Each fired stop's own executions move the last trade price before the
+    /// next check, so a stop can trigger another: the cascade runs until no
+    /// dormant stop is triggered, with a stop triggered by an earlier stop's
+    /// execution firing after it. It always ends because every iteration
+    /// removes a stop.
+    ///
+    /// While the kill switch is set nothing fires, stop-market or stop-limit:
+    /// triggered stops stay dormant and fire at the first check after the
+    /// book is enabled again.
+    fn run_triggered_stops(
+        &mut self,
+        reference: Option<Price>,
+        executions: &mut Vec<Execution>,
+    ) -> usize {
+        let mut triggered = 0;
+        while let Some(pos) = self.next_triggered_stop(reference) {
+            let mut stop = self.stop_orders.remove(pos);
+            triggered += 1;
+
+            let fired = if stop.order_type() == OrderType::StopLimit {
+                let order_id = stop.order_id;
+                stop.set_order_type(OrderType::Limit);
+                // Checked as a fresh limit order: a marketable one trades
+                // right away, even priced off its own side of the grid
+                if let Err(reason) = self.validate_order(&stop) {
+                    self.rejected_stops.push((order_id, reason));
+                    continue;
                 }
 
-                #[cfg(feature = "perf")]
-                {
-                    self.order_count += 1;
+                match self.execute_limit_order(stop) {
+                    Ok((fired, _)) => fired,
+                    Err(reason) => {
+                        self.rejected_stops.push((order_id, reason));
+                        continue;
+                    }
                 }
             } else {
-                return Err("Order pool full".to_string());
-            }
-        }
+                self.match_market_order(stop, None, |_| false).executions
+            };
 
-        // Update execution statistics
-        for exec in &executions {
-            self.total_quantity_matched += exec.quantity;
+            self.record_last_trade(&fired);
+            executions.extend(fired);
         }
+        triggered
+    }
 
-        #[cfg(feature = "perf")]
-        {
-            self.last_insert_time = start_time.elapsed();
+    /// Drain the stop-limit orders cancelled when they fired, with the reason
+    pub fn take_rejected_stops(&mut self) -> Vec<(OrderId, OrderError)> {
+        core::mem::take(&mut self.rejected_stops)
+    }
+
+    /// Position of the earliest dormant stop triggered by the last trade or
+    /// by `reference`
+    ///
+    /// Buy stops trigger once the market trades at or above their stop price,
+    /// sell stops at or below it. None is triggered while the kill switch is
+    /// set.
+    #[inline]
+    fn next_triggered_stop(&self, reference: Option<Price>) -> Option<usize> {
+        if self.is_disabled() {
+            return None;
         }
-        Ok(executions)
+        let prices = [self.last_trade_price, reference];
+        self.stop_orders.iter().position(|stop| {
+            prices.into_iter().flatten().any(|last| match stop.side() {
+                Side::Buy => last >= stop.stop_price,
+                Side::Sell => last <= stop.stop_price,
+            })
+        })
     }
 
-    /// Cancel an existing order
+    /// Number of stop orders waiting for their trigger
     #[inline]
-    pub fn cancel_order(&mut self, order_id: u64) -> Result<(), String> {
-        #[cfg(feature = "perf")]
-        let start_time = Instant::now();
+    pub fn stop_order_count(&self) -> usize {
+        self.stop_orders.len()
+    }
+
+    /// Make room for `order_id` in the lookup table and reject duplicates
+    fn reserve_order_id(&mut self, order_id: OrderId) -> Result<(), OrderError> {
+        self.check_order_id(order_id)?;
+        self.grow_order_ids(order_id);
+        Ok(())
+    }
+
+    /// Make room for `order_id` in the lookup table
+    fn grow_order_ids(&mut self, order_id: OrderId) {
+        // Ensure order ID is within our capacity
+        if order_id >= self.order_id_to_index.len() as OrderId && order_id > self.max_order_id {
+            self.max_order_id = order_id;
 
-        if order_id >= self.order_id_to_index.len() as u64 {
-            return Err(format!("Order {} not found", order_id));
+            // Expand order ID lookup vector if needed
+            while self.order_id_to_index.len() <= order_id as usize {
+                self.order_id_to_index.push(None);
+            }
         }
+    }
 
-        let index_opt = self.order_id_to_index[order_id as usize];
+    /// Whether `order_id` is resting, held as a dormant stop or waiting for
+    /// an auction
+    fn has_order(&self, order_id: OrderId) -> bool {
+        self.resting_index(order_id).is_ok()
+            || self
+                .stop_orders
+                .iter()
+                .chain(&self.auction_orders)
+                .any(|order| order.order_id == order_id)
+    }
 
-        if let Some(index) = index_opt {
-            let order = unsafe { self.order_pool.get(index) };
-            let side = order.side();
-            let price = order.price;
-            let quantity = order.quantity;
+    /// Reject an ID that is already resting, held as a dormant stop or
+    /// waiting for an auction
+    fn check_order_id(&self, order_id: OrderId) -> Result<(), OrderError> {
+        if self.has_order(order_id) {
+            return Err(OrderError::DuplicateOrderId(order_id));
+        }
 
-            // Remove from the appropriate side
-            match side {
-                Side::Buy => {
-                    if let Some(price_idx) = self.buy_price_to_idx(price) {
-                        if let Some(ref mut price_level) = self.buy_levels[price_idx] {
-                            if !price_level.remove_order(index, quantity) {
-                                return Err(format!("Failed to remove order from price level"));
-                            }
+        Ok(())
+    }
 
-                            // Remove empty price level and update best bid if needed
-                            if price_level.is_empty() {
-                                self.buy_levels[price_idx] = None;
+    /// Place an order on its side of the book without matching it
+    ///
+    /// Any iceberg reserve must already be split off into `hidden_quantity`.
+    /// The order is stamped with the next sequence, which puts it behind
+    /// everything already resting at its price.
+    fn insert_resting(&mut self, mut order: Order) -> Result<(), OrderError> {
+        let side = order.side();
+        let price = order.price;
 
-                                // Update best bid cache
+        // Convert price to index
+        let price_idx = self.price_to_idx(side, price);
+
+        // Check if price is within range
+        if price_idx.is_none() {
+            return Err(self.off_grid_error(price));
+        }
+
+        let price_idx = price_idx.unwrap();
+        self.check_level_room(&order)?;
+
+        let (inline_capacity, orders_per_level) =
+            (self.level_inline_capacity, self.orders_per_level);
+        let new_level = || match inline_capacity {
+            Some(capacity) => PriceLevel::with_inline_capacity(price, capacity),
+            None => PriceLevel::new(price, orders_per_level),
+        };
+
+        // Icebergs only show their peak; the rest waits in the level's reserve
+        let order_id = order.order_id;
+        let quantity = order.visible_quantity();
+        let hidden = order.hidden_quantity;
+        let dark = if order.hidden() { quantity } else { 0 };
+        order.sequence = self.next_sequence();
+        if self.order_pool.available_capacity() == 0 {
+            self.grow_order_pool();
+        }
+
+        // Allocate from the memory pool
+        if let Some(index) = self.order_pool.allocate(order) {
+            self.order_id_to_index[order_id as usize] = Some(index);
+            self.touch_level(side, price_idx);
+
+            // Add to the appropriate side of the book
+            match side {
+                Side::Buy => {
+                    // Get or create price level
+                    let price_level = self.buy_levels[price_idx].get_or_insert_with(new_level);
+                    self.buy_occupied.set(price_idx);
+
+                    if !price_level.add_order(index, quantity) {
+                        return Err(OrderError::PriceLevelFull);
+                    }
+                    price_level.hidden_quantity =
+                        price_level.hidden_quantity.saturating_add(hidden);
+                    price_level.dark_quantity += dark;
+                    self.bid_order_count += 1;
+                    self.bid_quantity += quantity as u128;
+
+                    // Update best bid cache
+                    if self.best_bid_idx.is_none() || price_idx < self.best_bid_idx.unwrap() {
+                        self.best_bid_idx = Some(price_idx);
+                    }
+                }
+                Side::Sell => {
+                    // Get or create price level
+                    let price_level = self.sell_levels[price_idx].get_or_insert_with(new_level);
+                    self.sell_occupied.set(price_idx);
+
+                    if !price_level.add_order(index, quantity) {
+                        return Err(OrderError::PriceLevelFull);
+                    }
+                    price_level.hidden_quantity =
+                        price_level.hidden_quantity.saturating_add(hidden);
+                    price_level.dark_quantity += dark;
+                    self.ask_order_count += 1;
+                    self.ask_quantity += quantity as u128;
+
+                    // Update best ask cache
+                    if self.best_ask_idx.is_none() || price_idx < self.best_ask_idx.unwrap() {
+                        self.best_ask_idx = Some(price_idx);
+                    }
+                }
+            }
+
+            self.live_order_count += 1;
+        } else {
+            return Err(OrderError::PoolExhausted);
+        }
+
+        Ok(())
+    }
+
+    /// Cancel an existing order
+    #[inline]
+    pub fn cancel_order(&mut self, order_id: OrderId) -> Result<(), OrderError> {
+        if !self.has_order(order_id) {
+            return Err(OrderError::OrderNotFound(order_id));
+        }
+        self.journal_op(JournalOp::CancelOrder { order_id })?;
+
+        let result = if self.stats_hook.is_none() {
+            self.process_cancel(order_id)
+        } else {
+            let start = monotonic_ns();
+            let result = self.process_cancel(order_id);
+            self.record_operation(monotonic_ns() - start);
+            result
+        };
+        self.notify_market_data();
+        result
+    }
+
+    /// Cancel every resting order, dormant stop and auction order, returning
+    /// how many
+    ///
+    /// Works while the kill switch is set. If the journal cannot be written
+    /// nothing is cancelled and 0 is returned.
+    pub fn cancel_all(&mut self) -> usize {
+        self.mass_cancel(None, None)
+    }
+
+    /// Cancel every order on `side`, resting or dormant, returning how many
+    pub fn cancel_side(&mut self, side: Side) -> usize {
+        self.mass_cancel(Some(side), None)
+    }
+
+    /// Cancel every order owned by `account_id`, resting or dormant,
+    /// returning how many
+    pub fn cancel_account(&mut self, account_id: u64) -> usize {
+        self.mass_cancel(None, Some(account_id))
+    }
+
+    /// Cancel the orders matching `side` and `account_id`, `None` matching
+    /// anything, by walking the levels once and freeing slots directly
+    fn mass_cancel(&mut self, side: Option<Side>, account_id: Option<u64>) -> usize {
+        if self
+            .journal_op(JournalOp::MassCancel { side, account_id })
+            .is_err()
+        {
+            return 0;
+        }
+        let selected = |order: &Order| {
+            side.is_none_or(|side| order.side() == side)
+                && account_id.is_none_or(|account| order.account_id == Some(account))
+        };
+
+        let dormant = self.stop_orders.len() + self.auction_orders.len();
+        self.stop_orders.retain(|stop| !selected(stop));
+        self.auction_orders.retain(|queued| !selected(queued));
+        let mut cancelled = dormant - self.stop_orders.len() - self.auction_orders.len();
+        let mut touched = Vec::new();
+
+        for book_side in [Side::Buy, Side::Sell] {
+            if side.is_some_and(|side| side != book_side) {
+                continue;
+            }
+            let (levels, occupied, resting_count, resting_quantity) = match book_side {
+                Side::Buy => (
+                    &mut self.buy_levels,
+                    &mut self.buy_occupied,
+                    &mut self.bid_order_count,
+                    &mut self.bid_quantity,
+                ),
+                Side::Sell => (
+                    &mut self.sell_levels,
+                    &mut self.sell_occupied,
+                    &mut self.ask_order_count,
+                    &mut self.ask_quantity,
+                ),
+            };
+
+            let mut current_idx = occupied.next_set(0);
+            while let Some(idx) = current_idx {
+                current_idx = occupied.next_set(idx + 1);
+                let Some(level) = levels[idx].as_mut() else {
+                    continue;
+                };
+
+                let mut position = 0;
+                while let Some(index) = level.get(position) {
+                    let order = unsafe { self.order_pool.get(index) };
+                    if !selected(order) {
+                        position += 1;
+                        continue;
+                    }
+                    if touched.last() != Some(&(book_side, idx)) {
+                        touched.push((book_side, idx));
+                    }
+                    level.total_quantity -= order.visible_quantity();
+                    level.hidden_quantity -= order.hidden_quantity;
+                    if order.hidden() {
+                        level.dark_quantity -= order.visible_quantity();
+                    }
+                    *resting_quantity -= order.visible_quantity() as u128;
+                    self.order_id_to_index[order.order_id as usize] = None;
+                    level.unlink_at(position);
+                    let freed = self.order_pool.deallocate(index);
+                    debug_assert!(freed, "order pool slot {} freed twice", index);
+                    *resting_count -= 1;
+                    self.live_order_count -= 1;
+                    cancelled += 1;
+                }
+
+                if level.is_empty() {
+                    levels[idx] = None;
+                    occupied.clear(idx);
+                }
+            }
+        }
+
+        for (side, idx) in touched {
+            self.touch_level(side, idx);
+        }
+        self.best_bid_idx = self.find_best_bid_idx();
+        self.best_ask_idx = self.find_best_ask_idx();
+        self.notify_market_data();
+        cancelled
+    }
+
+    /// Cancel `reduce_by` of a resting order's remaining quantity
+    ///
+    /// The rest of the order keeps its place in the queue; reducing by the
+    /// whole remainder or more cancels it. Returns the quantity still resting.
+    pub fn reduce_order(
+        &mut self,
+        order_id: OrderId,
+        reduce_by: Quantity,
+    ) -> Result<Quantity, OrderError> {
+        self.resting_index(order_id)?;
+        self.journal_op(JournalOp::Reduce {
+            order_id,
+            quantity: reduce_by,
+        })?;
+
+        let remaining = self
+            .order_id_to_index
+            .get(order_id as usize)
+            .copied()
+            .flatten()
+            .map_or(0, |index| unsafe { self.order_pool.get(index) }.quantity);
+        let reduced = self.reduce_resting(order_id, reduce_by);
+        self.notify_market_data();
+        Ok(remaining - reduced?)
+    }
+
+    #[inline]
+    fn process_cancel(&mut self, order_id: OrderId) -> Result<(), OrderError> {
+        #[cfg(feature = "perf")]
+        let start_time = Instant::now();
+
+        // Dormant stops live outside the price levels
+        if let Some(pos) = self
+            .stop_orders
+            .iter()
+            .position(|stop| stop.order_id == order_id)
+        {
+            self.stop_orders.remove(pos);
+            return Ok(());
+        }
+        if let Some(pos) = self
+            .auction_orders
+            .iter()
+            .position(|queued| queued.order_id == order_id)
+        {
+            self.auction_orders.remove(pos);
+            return Ok(());
+        }
+
+        if order_id >= self.order_id_to_index.len() as OrderId {
+            return Err(OrderError::OrderNotFound(order_id));
+        }
+
+        let index_opt = self.order_id_to_index[order_id as usize];
+
+        if let Some(index) = index_opt {
+            let order = unsafe { self.order_pool.get(index) };
+            let side = order.side();
+            let price = order.price;
+            let quantity = order.visible_quantity();
+            let hidden = order.hidden_quantity;
+            let dark = if order.hidden() { quantity } else { 0 };
+
+            // Remove from the appropriate side
+            match side {
+                Side::Buy => {
+                    if let Some(price_idx) = self.buy_price_to_idx(price) {
+                        self.touch_level(side, price_idx);
+                        if let Some(ref mut price_level) = self.buy_levels[price_idx] {
+                            if !price_level.remove_order(index, quantity) {
+                                return Err(OrderError::OrderNotFound(order_id));
+                            }
+                            price_level.hidden_quantity -= hidden;
+                            price_level.dark_quantity -= dark;
+                            self.bid_order_count -= 1;
+                            self.bid_quantity -= quantity as u128;
+
+                            // Remove empty price level and update best bid if needed
+                            if price_level.is_empty() {
+                                self.buy_levels[price_idx] = None;
+                                self.buy_occupied.clear(price_idx);
+
+                                // Update best bid cache
                                 if Some(price_idx) == self.best_bid_idx {
                                     self.best_bid_idx = self.find_best_bid_idx();
                                 }
                             }
                         } else {
-                            return Err(format!("Price level {} not found", price));
+                            return Err(OrderError::PriceLevelNotFound(price));
                         }
                     } else {
-                        return Err(format!("Price {} is outside the allowed range", price));
+                        return Err(OrderError::PriceOutOfRange(price));
                     }
                 }
                 Side::Sell => {
                     if let Some(price_idx) = self.sell_price_to_idx(price) {
+                        self.touch_level(side, price_idx);
                         if let Some(ref mut price_level) = self.sell_levels[price_idx] {
                             if !price_level.remove_order(index, quantity) {
-                                return Err(format!("Failed to remove order from price level"));
+                                return Err(OrderError::OrderNotFound(order_id));
                             }
+                            price_level.hidden_quantity -= hidden;
+                            price_level.dark_quantity -= dark;
+                            self.ask_order_count -= 1;
+                            self.ask_quantity -= quantity as u128;
 
                             // Remove empty price level and update best ask if needed
                             if price_level.is_empty() {
                                 self.sell_levels[price_idx] = None;
+                                self.sell_occupied.clear(price_idx);
 
                                 // Update best ask cache
                                 if Some(price_idx) == self.best_ask_idx {
@@ -355,23 +1534,21 @@ impl OrderBook {
                                 }
                             }
                         } else {
-                            return Err(format!("Price level {} not found", price));
+                            return Err(OrderError::PriceLevelNotFound(price));
                         }
                     } else {
-                        return Err(format!("Price {} is outside the allowed range", price));
+                        return Err(OrderError::PriceOutOfRange(price));
                     }
                 }
             }
 
             // Deallocate from the memory pool
-            self.order_pool.deallocate(index);
+            let freed = self.order_pool.deallocate(index);
+            debug_assert!(freed, "order pool slot {} freed twice", index);
             self.order_id_to_index[order_id as usize] = None;
-            #[cfg(feature = "perf")]
-            {
-                self.order_count -= 1;
-            }
+            self.live_order_count -= 1;
         } else {
-            return Err(format!("Order {} not found", order_id));
+            return Err(OrderError::OrderNotFound(order_id));
         }
 
         #[cfg(feature = "perf")]
@@ -381,457 +1558,2142 @@ impl OrderBook {
         Ok(())
     }
 
+    /// Cancel every order whose expiry is at or before `now_ns`
+    ///
+    /// Covers resting orders and dormant stops alike. Returns the IDs of the
+    /// cancelled orders, resting ones first in ID order.
+    pub fn expire_orders(&mut self, now_ns: u64) -> Vec<OrderId> {
+        let expired = self.expired_orders(now_ns);
+        if !expired.is_empty() && self.journal_op(JournalOp::Expire { now_ns }).is_err() {
+            return Vec::new();
+        }
+        for &order_id in &expired {
+            self.process_cancel(order_id)
+                .expect("expired order is on the book");
+        }
+        self.notify_market_data();
+
+        expired
+    }
+
+    /// Run one maintenance tick: expire every order due at `now_ns`, then fire
+    /// the stops that `last_price` or the book's own last trade has moved
+    /// through
+    ///
+    /// This is what a timer would call periodically, with `last_price` taken
+    /// from the reference market. Stop-limit orders that could not fire are
+    /// reported through [`take_rejected_stops`](Self::take_rejected_stops) as
+    /// usual. A tick that finds nothing to do is not journaled.
+    pub fn housekeeping(&mut self, now_ns: u64, last_price: Price) -> HousekeepingReport {
+        let expired = self.expired_orders(now_ns);
+        let due = !expired.is_empty() || self.next_triggered_stop(Some(last_price)).is_some();
+        if due
+            && self
+                .journal_op(JournalOp::Housekeeping { now_ns, last_price })
+                .is_err()
+        {
+            return HousekeepingReport::default();
+        }
+
+        for &order_id in &expired {
+            self.process_cancel(order_id)
+                .expect("expired order is on the book");
+        }
+        let mut executions = Vec::new();
+        let triggered = self.run_triggered_stops(Some(last_price), &mut executions);
+        self.debug_check_uncrossed();
+        self.notify_market_data();
+
+        HousekeepingReport {
+            expired: expired.len(),
+            triggered,
+            executions: executions.len(),
+        }
+    }
+
+    /// IDs of the orders whose expiry is at or before `now_ns`, resting ones
+    /// first in ID order, then dormant stops
+    fn expired_orders(&self, now_ns: u64) -> Vec<OrderId> {
+        let is_expired = |order: &Order| order.expires_at.is_some_and(|at| at <= now_ns);
+
+        let mut expired: Vec<OrderId> = self
+            .order_id_to_index
+            .iter()
+            .flatten()
+            .map(|&index| unsafe { self.order_pool.get(index) })
+            .filter(|order| is_expired(order))
+            .map(|order| order.order_id)
+            .collect();
+        expired.extend(
+            self.stop_orders
+                .iter()
+                .filter(|stop| is_expired(stop))
+                .map(|stop| stop.order_id),
+        );
+        expired
+    }
+
+    /// Change the price and quantity of a resting order, keeping its ID
+    ///
+    /// A pure quantity decrease keeps the order's place in the queue; a price
+    /// change or quantity increase sends it to the back of the level at the new
+    /// price. Amending to a price that would trade against the opposite side
+    /// is rejected and leaves the order untouched - cancel and resubmit to
+    /// take liquidity instead. Anything but a pure decrease is held to the
+    /// configured size, notional and collar limits as if newly added.
+    pub fn amend_order(
+        &mut self,
+        order_id: OrderId,
+        new_price: Price,
+        new_quantity: Quantity,
+    ) -> Result<(), OrderError> {
+        if self.disabled.load(Ordering::Relaxed) {
+            return Err(OrderError::Disabled);
+        }
+        self.validate_amend(order_id, new_price, new_quantity)?;
+        self.journal_op(JournalOp::Amend {
+            order_id,
+            price: new_price,
+            quantity: new_quantity,
+        })?;
+
+        let result = self.modify_resting(order_id, new_price, new_quantity);
+        self.notify_market_data();
+        result
+    }
+
+    /// Apply a decoded market-data event to the book
+    ///
+    /// This drives a mirror of an external venue's book, so nothing is matched
+    /// here: adds rest as-is and the venue's executions reduce resting orders
+    /// directly. Events are not journaled, so a journal attached to a mirror
+    /// cannot replay it; rebuild the mirror from the feed instead.
+    pub fn apply_event(&mut self, event: Event) -> Result<(), OrderError> {
+        let result = self.process_event(event);
+        self.notify_market_data();
+        result
+    }
+
+    fn process_event(&mut self, event: Event) -> Result<(), OrderError> {
+        match event {
+            Event::Add(mut order) => {
+                self.reserve_order_id(order.order_id)?;
+                self.total_orders_processed += 1;
+                order.conceal_reserve();
+                self.insert_resting(order)
+            }
+            Event::Cancel { order_id } => self.process_cancel(order_id),
+            Event::Modify {
+                order_id,
+                price,
+                quantity,
+            } => self.modify_resting(order_id, price, quantity),
+            Event::Execution { order_id, quantity } => {
+                let price = self.get_order(order_id).map(|order| order.price);
+                let filled = self.reduce_resting(order_id, quantity)?;
+                self.total_quantity_matched = self.total_quantity_matched.saturating_add(filled);
+                self.total_notional_matched = self
+                    .total_notional_matched
+                    .saturating_add(price.unwrap_or(0) as u128 * filled as u128);
+                if let Some(traded) = price.and_then(|price| self.traded_volume_slot(price)) {
+                    *traded = traded.saturating_add(filled);
+                }
+                Ok(())
+            }
+            Event::LevelDelete { side, price } => {
+                let indices: Vec<usize> = match self.level_mut(side, price) {
+                    Some(level) => level.iter().collect(),
+                    None => return Err(OrderError::PriceLevelNotFound(price)),
+                };
+
+                for index in indices {
+                    let order_id = unsafe { self.order_pool.get(index) }.order_id;
+                    self.process_cancel(order_id)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Double the full order pool, up to the configured maximum
+    #[cold]
+    fn grow_order_pool(&mut self) {
+        let Some(max) = self.max_pool_capacity else {
+            return;
+        };
+        let capacity = self.order_pool.total_capacity();
+        let additional = capacity.max(1).min(max.saturating_sub(capacity));
+        if additional > 0 {
+            self.order_pool.grow(additional);
+        }
+    }
+
+    /// The level at `price` on `side`, if it has been created
+    pub(crate) fn level(&self, side: Side, price: Price) -> Option<&PriceLevel> {
+        let idx = self.price_to_idx(side, price)?;
+        match side {
+            Side::Buy => self.buy_levels[idx].as_ref(),
+            Side::Sell => self.sell_levels[idx].as_ref(),
+        }
+    }
+
+    fn level_mut(&mut self, side: Side, price: Price) -> Option<&mut PriceLevel> {
+        let idx = self.price_to_idx(side, price)?;
+        self.touch_level(side, idx);
+        match side {
+            Side::Buy => self.buy_levels[idx].as_mut(),
+            Side::Sell => self.sell_levels[idx].as_mut(),
+        }
+    }
+
+    /// Take up to `quantity` off a resting order in place, removing it once
+    /// nothing remains. Returns the quantity actually removed.
+    fn reduce_resting(
+        &mut self,
+        order_id: OrderId,
+        quantity: Quantity,
+    ) -> Result<Quantity, OrderError> {
+        let index = self.resting_index(order_id)?;
+        let order = unsafe { self.order_pool.get_mut(index) };
+        let reduce_by = core::cmp::min(quantity, order.quantity);
+
+        // Take from the visible peak first, then from any iceberg reserve
+        let from_visible = core::cmp::min(reduce_by, order.visible_quantity());
+        let from_hidden = reduce_by - from_visible;
+        order.quantity -= reduce_by;
+        order.hidden_quantity -= from_hidden;
+        let slice = if order.visible_quantity() == 0 {
+            self.sequence += 1;
+            order.sequence = self.sequence;
+            order.reveal_slice()
+        } else {
+            0
+        };
+        let (side, price, remaining) = (order.side(), order.price, order.quantity);
+        let from_dark = if order.hidden() { from_visible } else { 0 };
+
+        if let Some(level) = self.level_mut(side, price) {
+            level.total_quantity -= from_visible;
+            level.hidden_quantity -= from_hidden;
+            level.dark_quantity -= from_dark;
+            if slice > 0 {
+                level.replenish(index, slice);
+            }
+            let side_quantity = match side {
+                Side::Buy => &mut self.bid_quantity,
+                Side::Sell => &mut self.ask_quantity,
+            };
+            *side_quantity = *side_quantity + slice as u128 - from_visible as u128;
+        }
+
+        if remaining == 0 {
+            self.process_cancel(order_id)?;
+        }
+
+        Ok(reduce_by)
+    }
+
+    /// Pool index of a resting order
+    fn resting_index(&self, order_id: OrderId) -> Result<usize, OrderError> {
+        self.order_id_to_index
+            .get(order_id as usize)
+            .copied()
+            .flatten()
+            .ok_or(OrderError::OrderNotFound(order_id))
+    }
+
+    /// Reject an amend the book would refuse to apply, before it is journaled
+    fn validate_amend(
+        &self,
+        order_id: OrderId,
+        price: Price,
+        quantity: Quantity,
+    ) -> Result<(), OrderError> {
+        let order = unsafe { self.order_pool.get(self.resting_index(order_id)?) };
+
+        if price != order.price && self.is_marketable(order.side(), price) {
+            return Err(OrderError::WouldCross(order_id));
+        }
+        if price == order.price && quantity <= order.quantity {
+            return Ok(());
+        }
+        // A new price or a larger quantity faces the same limits as an add
+        let mut amended = order.clone();
+        amended.price = price;
+        amended.quantity = quantity;
+        self.check_order_size(&amended)?;
+        self.check_price_collar(&amended)?;
+        self.check_repricing(order, price, quantity)
+    }
+
+    /// Check that `order` can move to `price` and `quantity` at the back of
+    /// the queue: the price must be on the grid and the level must have room
+    fn check_repricing(
+        &self,
+        order: &Order,
+        price: Price,
+        quantity: Quantity,
+    ) -> Result<(), OrderError> {
+        if quantity > 0 && self.price_to_idx(order.side(), price).is_none() {
+            return Err(self.off_grid_error(price));
+        }
+        // The order's own quantity leaves its level along with it
+        let added = if price == order.price {
+            quantity - order.quantity
+        } else {
+            quantity
+        };
+        self.check_level_room_for(order.side(), price, added, order.order_id)
+    }
+
+    /// Change a resting order's price and quantity
+    ///
+    /// A pure quantity decrease keeps time priority; anything else moves the
+    /// order to the back of the queue at its (new) price.
+    fn modify_resting(
+        &mut self,
+        order_id: OrderId,
+        price: Price,
+        quantity: Quantity,
+    ) -> Result<(), OrderError> {
+        let index = self.resting_index(order_id)?;
+        let mut order = unsafe { self.order_pool.get(index) }.clone();
+
+        if price == order.price && quantity <= order.quantity {
+            self.reduce_resting(order_id, order.quantity - quantity)?;
+            return Ok(());
+        }
+
+        // Validate before cancelling so a bad price cannot lose the order
+        self.check_repricing(&order, price, quantity)?;
+
+        self.process_cancel(order_id)?;
+        order.price = price;
+        order.quantity = quantity;
+        order.timestamp = self.now();
+        order.conceal_reserve();
+        if quantity > 0 {
+            self.reserve_order_id(order_id)?;
+            self.insert_resting(order)?;
+        }
+
+        Ok(())
+    }
+
     /// Match a new limit order against the book
     #[inline]
     fn match_limit_order(&mut self, order: &mut Order) -> Vec<Execution> {
         #[cfg(feature = "perf")]
         let start_time = Instant::now();
-        let mut executions = Vec::with_capacity(10);
 
-        match order.side() {
-            Side::Buy => {
-                // Match against sells starting from the lowest price
-                let mut current_idx = self.best_ask_idx;
+        // Only trade at prices that overlap the limit by the minimum increment
+        let (side, limit, increment) = (order.side(), order.price, self.min_cross_increment);
+        let acceptable = move |price: Price| match side {
+            Side::Buy => price + increment <= limit,
+            Side::Sell => price >= limit + increment,
+        };
+        let executions = self.sweep(order, acceptable, |_| false);
 
-                while let Some(idx) = current_idx {
-                    if order.quantity == 0 {
-                        break;
-                    }
+        #[cfg(feature = "perf")]
+        {
+            self.last_match_time = start_time.elapsed();
+        }
+        executions
+    }
 
-                    let price = self.sell_idx_to_price(idx);
+    /// Match a new market order against the book, never consuming a level
+    /// worse than `worst_price` when one is given
+    #[inline]
+    fn match_market_order<F>(
+        &mut self,
+        mut order: Order,
+        worst_price: Option<Price>,
+        should_stop: F,
+    ) -> MarketOrderResult
+    where
+        F: FnMut(&[Execution]) -> bool,
+    {
+        // For market orders, we don't care about price constraints
+        // We just match against the best available prices until filled or liquidity exhausted
+        let side = order.side();
+        let acceptable = move |price: Price| match (side, worst_price) {
+            (_, None) => true,
+            (Side::Buy, Some(worst)) => price <= worst,
+            (Side::Sell, Some(worst)) => price >= worst,
+        };
+        let executions = self.sweep(&mut order, acceptable, should_stop);
+
+        MarketOrderResult {
+            executions,
+            unfilled_quantity: order.quantity,
+        }
+    }
 
-                    // Check if the price is acceptable
-                    if price > order.price {
-                        break;
-                    }
+    /// Walk the opposite side best level first, filling `order` until it is
+    /// done, the next level's price is not `acceptable`, or `should_stop`
+    /// asks to abandon the sweep between levels
+    fn sweep<A, F>(
+        &mut self,
+        order: &mut Order,
+        acceptable: A,
+        mut should_stop: F,
+    ) -> Vec<Execution>
+    where
+        A: Fn(Price) -> bool,
+        F: FnMut(&[Execution]) -> bool,
+    {
+        let mut executions = Vec::with_capacity(10);
+        let book_side = match order.side() {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        };
+        let mut current_idx = match book_side {
+            Side::Buy => self.best_bid_idx,
+            Side::Sell => self.best_ask_idx,
+        };
+
+        let mut swept_level = false;
+
+        while let Some(idx) = current_idx {
+            if order.quantity == 0 {
+                break;
+            }
 
-                    // Get a mutable reference to the price level
-                    if let Some(ref mut level) = self.sell_levels[idx] {
-                        // Process all orders at this level
-                        let resting_indices = level.order_indices.clone();
+            // Let the caller abandon the sweep between levels
+            if swept_level && should_stop(&executions) {
+                break;
+            }
+            swept_level = true;
 
-                        for resting_idx in resting_indices {
-                            if order.quantity == 0 {
-                                break;
-                            }
+            let price = match book_side {
+                Side::Buy => self.buy_idx_to_price(idx),
+                Side::Sell => self.sell_idx_to_price(idx),
+            };
+            if !acceptable(price) {
+                break;
+            }
 
-                            let resting_order = unsafe { self.order_pool.get_mut(resting_idx) };
-                            let match_qty = std::cmp::min(resting_order.quantity, order.quantity);
-
-                            // Update quantities
-                            resting_order.quantity -= match_qty;
-                            order.quantity -= match_qty;
-                            level.total_quantity -= match_qty;
-
-                            // Update matched quantity statistic
-                            self.total_quantity_matched += match_qty;
-
-                            // Create execution report
-                            executions.push(Execution {
-                                order_id: resting_order.order_id,
-                                price,
-                                quantity: match_qty,
-                                timestamp: precise_time_ns(),
-                                side: resting_order.side(),
-                            });
-
-                            // If resting order is fully matched, remove it
-                            if resting_order.quantity == 0 {
-                                level.order_indices.retain(|&idx| idx != resting_idx);
-                                self.order_id_to_index[resting_order.order_id as usize] = None;
-                                self.order_pool.deallocate(resting_idx);
-                                #[cfg(feature = "perf")]
-                                {
-                                    self.order_count -= 1;
-                                }
-                            }
+            let emptied = self.fill_level(book_side, idx, price, order, &mut executions);
+
+            // Move to the next price level, dropping this one if nothing is left
+            let occupied = match book_side {
+                Side::Buy => &mut self.buy_occupied,
+                Side::Sell => &mut self.sell_occupied,
+            };
+            if emptied {
+                occupied.clear(idx);
+            }
+            current_idx = occupied.next_set(idx + 1);
+
+            if emptied {
+                match book_side {
+                    Side::Buy => {
+                        self.buy_levels[idx] = None;
+                        if Some(idx) == self.best_bid_idx {
+                            self.best_bid_idx = current_idx;
                         }
+                    }
+                    Side::Sell => {
+                        self.sell_levels[idx] = None;
+                        if Some(idx) == self.best_ask_idx {
+                            self.best_ask_idx = current_idx;
+                        }
+                    }
+                }
+            }
+        }
 
-                        // If the level is now empty, remove it
-                        if level.is_empty() {
-                            self.sell_levels[idx] = None;
+        executions
+    }
 
-                            // Find the next price level
-                            current_idx = None;
-                            for i in (idx + 1)..PRICE_LEVELS {
-                                if self.sell_levels[i].is_some() {
-                                    current_idx = Some(i);
-                                    break;
-                                }
-                            }
+    /// Fill `order` against the resting orders of one level on `book_side`,
+    /// in time priority. Returns whether the level is now empty.
+    fn fill_level(
+        &mut self,
+        book_side: Side,
+        idx: usize,
+        price: Price,
+        order: &mut Order,
+        executions: &mut Vec<Execution>,
+    ) -> bool {
+        self.touch_level(book_side, idx);
+        let (level, resting_count, resting_quantity, traded_volume) = match book_side {
+            Side::Buy => (
+                &mut self.buy_levels[idx],
+                &mut self.bid_order_count,
+                &mut self.bid_quantity,
+                &mut self.buy_traded_volume[idx],
+            ),
+            Side::Sell => (
+                &mut self.sell_levels[idx],
+                &mut self.ask_order_count,
+                &mut self.ask_quantity,
+                &mut self.sell_traded_volume[idx],
+            ),
+        };
+        let Some(level) = level.as_mut() else {
+            return true;
+        };
+        // Fills take quantity off the level and replenished icebergs put some
+        // back; the side total follows the net change
+        let shown_before = level.total_quantity;
+
+        // Process all orders at this level. An iceberg showing a new slice
+        // moves to the back of the queue, so keep sweeping while slices are
+        // being replenished.
+        let mut replenished = true;
+        while replenished && order.quantity > 0 {
+            replenished = false;
+
+            // Pro-rata only changes anything when the level cannot be taken whole.
+            // Orders from the aggressor's own account get no share.
+            let shares = if self.matching_policy == MatchingPolicy::ProRata {
+                let sizes: Vec<Quantity> = level
+                    .iter()
+                    .map(|index| {
+                        let resting = unsafe { self.order_pool.get(index) };
+                        let own =
+                            order.account_id.is_some() && resting.account_id == order.account_id;
+                        if own { 0 } else { resting.visible_quantity() }
+                    })
+                    .collect();
+                (order.quantity < sizes.iter().sum::<Quantity>())
+                    .then(|| pro_rata_shares(order.quantity, &sizes))
+            } else {
+                None
+            };
 
-                            // Update best ask if needed
-                            if Some(idx) == self.best_ask_idx {
-                                self.best_ask_idx = current_idx;
-                            }
-                        }
-                    } else {
-                        // This price level should not be empty if we have an index
-                        // Move to the next price level
-                        current_idx = None;
-                        for i in (idx + 1)..PRICE_LEVELS {
-                            if self.sell_levels[i].is_some() {
-                                current_idx = Some(i);
-                                break;
-                            }
+            // Walk the queue in place with a cursor. Unlinking a filled or
+            // cancelled order, or sending a replenished iceberg behind the rest
+            // of the pass, brings the next order under the cursor.
+            let mut position = 0;
+            for visit in 0..level.order_count() {
+                if order.quantity == 0 {
+                    break;
+                }
+                let Some(resting_idx) = level.get(position) else {
+                    break;
+                };
+
+                let resting_order = unsafe { self.order_pool.get_mut(resting_idx) };
+
+                // Self-trade prevention: never cross two orders of one account
+                if order.account_id.is_some() && resting_order.account_id == order.account_id {
+                    let policy = self.self_trade_policy;
+                    if policy != SelfTradePolicy::CancelAggressing {
+                        level.total_quantity -= resting_order.visible_quantity();
+                        level.hidden_quantity -= resting_order.hidden_quantity;
+                        if resting_order.hidden() {
+                            level.dark_quantity -= resting_order.visible_quantity();
                         }
+                        level.unlink_at(position);
+                        self.order_id_to_index[resting_order.order_id as usize] = None;
+                        let freed = self.order_pool.deallocate(resting_idx);
+                        debug_assert!(freed, "order pool slot {} freed twice", resting_idx);
+                        *resting_count -= 1;
+                        self.live_order_count -= 1;
                     }
+                    if policy != SelfTradePolicy::CancelResting {
+                        order.quantity = 0;
+                    }
+                    continue;
+                }
+
+                let match_qty = match &shares {
+                    Some(shares) => shares[visit],
+                    None => core::cmp::min(resting_order.visible_quantity(), order.quantity),
+                };
+                // An all-or-none order is passed by unless it fills completely
+                if match_qty == 0
+                    || (resting_order.all_or_none() && match_qty < resting_order.quantity)
+                {
+                    position += 1;
+                    continue;
+                }
+
+                // Update quantities
+                resting_order.quantity -= match_qty;
+                order.quantity -= match_qty;
+                level.total_quantity -= match_qty;
+                if resting_order.hidden() {
+                    level.dark_quantity -= match_qty;
+                }
+                self.total_quantity_matched = self.total_quantity_matched.saturating_add(match_qty);
+                self.total_notional_matched = self
+                    .total_notional_matched
+                    .saturating_add(price as u128 * match_qty as u128);
+                *traded_volume = traded_volume.saturating_add(match_qty);
+                self.last_trade_id += 1;
+
+                // Create execution report
+                let execution = Execution {
+                    order_id: resting_order.order_id,
+                    price,
+                    quantity: match_qty,
+                    timestamp: self.clock.unwrap_or_else(clock_ns),
+                    side: resting_order.side(),
+                    trade_id: self.last_trade_id,
+                    maker_order_id: resting_order.order_id,
+                    taker_order_id: order.order_id,
+                    aggressor_side: order.side(),
+                };
+                if let Some(callback) = self.execution_hook.as_mut() {
+                    callback(&execution);
                 }
+                executions.push(execution);
+
+                if let Some(positions) = self.positions.as_mut() {
+                    update_position(positions, resting_order.account_id, book_side, match_qty);
+                    update_position(positions, order.account_id, order.side(), match_qty);
+                }
+
+                // If resting order is fully matched, remove it
+                if resting_order.quantity == 0 {
+                    level.unlink_at(position);
+                    self.order_id_to_index[resting_order.order_id as usize] = None;
+                    let freed = self.order_pool.deallocate(resting_idx);
+                    debug_assert!(freed, "order pool slot {} freed twice", resting_idx);
+                    *resting_count -= 1;
+                    self.live_order_count -= 1;
+                } else if resting_order.visible_quantity() == 0 {
+                    let slice = resting_order.reveal_slice();
+                    self.sequence += 1;
+                    resting_order.sequence = self.sequence;
+                    level.replenish(resting_idx, slice);
+                    replenished = true;
+                } else {
+                    position += 1;
+                }
+            }
+        }
+
+        *resting_quantity = *resting_quantity + level.total_quantity as u128 - shown_before as u128;
+        level.is_empty()
+    }
+
+    /// Remove every order and reset the statistics without reallocating
+    ///
+    /// The book is left as a freshly constructed one of the same capacity, so
+    /// previously used order IDs are accepted again. Its price grid, policies,
+    /// kill switch and registered callbacks are kept.
+    pub fn clear(&mut self) {
+        self.order_pool.reset();
+        self.order_id_to_index.fill(None);
+        self.max_order_id = 0;
+
+        if let Some(hook) = self.depth_hook.as_mut() {
+            for (side, levels) in [
+                (Side::Buy, &self.buy_levels),
+                (Side::Sell, &self.sell_levels),
+            ] {
+                let occupied = levels
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, level)| level.is_some());
+                hook.touched.extend(occupied.map(|(idx, _)| (side, idx)));
+            }
+        }
+        self.buy_levels.fill(None);
+        self.sell_levels.fill(None);
+        self.best_bid_idx = None;
+        self.best_ask_idx = None;
+        self.buy_occupied = LevelBitmap::new(self.price_levels);
+        self.sell_occupied = LevelBitmap::new(self.price_levels);
+
+        self.stop_orders.clear();
+        self.rejected_stops.clear();
+        self.auction_orders.clear();
+        self.last_trade_price = None;
+        self.last_trade_quantity = None;
+        if let Some(positions) = self.positions.as_mut() {
+            positions.clear();
+        }
+
+        self.bid_order_count = 0;
+        self.ask_order_count = 0;
+        self.bid_quantity = 0;
+        self.ask_quantity = 0;
+        self.total_orders_processed = 0;
+        self.total_quantity_matched = 0;
+        self.total_notional_matched = 0;
+        self.last_trade_id = 0;
+        self.sequence = 0;
+        self.buy_traded_volume.fill(0);
+        self.sell_traded_volume.fill(0);
+        self.live_order_count = 0;
+        #[cfg(feature = "perf")]
+        {
+            self.last_insert_time = Duration::default();
+            self.last_match_time = Duration::default();
+            self.last_cancel_time = Duration::default();
+        }
+        self.notify_market_data();
+    }
+
+    /// Create an independent copy of the book for what-if analysis
+    pub fn fork(&self) -> OrderBook {
+        self.clone()
+    }
+
+    /// Apply `order` to a fork of the book and return what would have happened
+    ///
+    /// The real book is left untouched.
+    pub fn simulate(&self, order: Order) -> OrderOutcome {
+        self.fork().add_order(order)
+    }
+
+    /// Work out the fills an aggressor of `quantity` on `side` would get,
+    /// without copying or changing the book
+    ///
+    /// The opposing levels are walked the way the matching loop walks them,
+    /// up to `limit_price` if one is given, including iceberg replenishment
+    /// and the pro-rata policy. There is no account, so self-trade prevention
+    /// never applies, and stops the trades would trigger are not run. The
+    /// executions carry the trade IDs they would be assigned and a taker
+    /// order ID of 0.
+    pub fn simulate_fill(
+        &self,
+        side: Side,
+        quantity: Quantity,
+        limit_price: Option<Price>,
+    ) -> SimResult {
+        let levels: Vec<(Price, &PriceLevel)> = match limit_price {
+            Some(limit_price) => self.opposite_levels(side, limit_price).collect(),
+            None => self.all_opposite_levels(side).collect(),
+        };
+        let book_side = match side {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        };
+        let timestamp = self.now();
+        let mut remaining = quantity;
+        let mut executions = Vec::new();
+
+        for (price, level) in levels {
+            if remaining == 0 {
+                break;
             }
-            Side::Sell => {
-                // Match against buys starting from the highest price
-                let mut current_idx = self.best_bid_idx;
 
-                while let Some(idx) = current_idx {
-                    if order.quantity == 0 {
+            // (order ID, visible, hidden, display, all-or-none) in time priority
+            let mut queue: Vec<(OrderId, Quantity, Quantity, Quantity, bool)> = level
+                .iter()
+                .map(|index| {
+                    let resting = unsafe { self.order_pool.get(index) };
+                    (
+                        resting.order_id,
+                        resting.visible_quantity(),
+                        resting.hidden_quantity,
+                        resting.display_quantity,
+                        resting.all_or_none(),
+                    )
+                })
+                .collect();
+
+            let mut replenished = true;
+            while replenished && remaining > 0 {
+                replenished = false;
+
+                let shares = if self.matching_policy == MatchingPolicy::ProRata {
+                    let sizes: Vec<Quantity> =
+                        queue.iter().map(|&(_, visible, ..)| visible).collect();
+                    (remaining < sizes.iter().sum::<Quantity>())
+                        .then(|| pro_rata_shares(remaining, &sizes))
+                } else {
+                    None
+                };
+
+                let mut position = 0;
+                for visit in 0..queue.len() {
+                    if remaining == 0 {
                         break;
                     }
+                    let Some(resting) = queue.get_mut(position) else {
+                        break;
+                    };
+
+                    let match_qty = match &shares {
+                        Some(shares) => shares[visit],
+                        None => core::cmp::min(resting.1, remaining),
+                    };
+                    // All-or-none is judged on the whole remainder, reserve
+                    // included, as in the matching loop
+                    if match_qty == 0 || (resting.4 && match_qty < resting.1 + resting.2) {
+                        position += 1;
+                        continue;
+                    }
+
+                    resting.1 -= match_qty;
+                    remaining -= match_qty;
+                    executions.push(Execution {
+                        order_id: resting.0,
+                        price,
+                        quantity: match_qty,
+                        timestamp,
+                        side: book_side,
+                        trade_id: self.last_trade_id + executions.len() as u64 + 1,
+                        maker_order_id: resting.0,
+                        taker_order_id: 0,
+                        aggressor_side: side,
+                    });
+
+                    if resting.1 == 0 && resting.2 == 0 {
+                        queue.remove(position);
+                    } else if resting.1 == 0 {
+                        let slice = core::cmp::min(resting.3, resting.2);
+                        resting.2 -= slice;
+                        resting.1 = slice;
+                        let iceberg = queue.remove(position);
+                        queue.push(iceberg);
+                        replenished = true;
+                    } else {
+                        position += 1;
+                    }
+                }
+            }
+        }
+
+        SimResult {
+            average_price: vwap(&executions),
+            executions,
+            unfilled_quantity: remaining,
+        }
+    }
+
+    /// The first `max_fills` resting orders a `side` aggressor of `quantity`
+    /// limited at `limit_price` would hit, as `(maker_order_id, price,
+    /// quantity)` in matching order
+    ///
+    /// Nothing is executed; see [`simulate_fill`](Self::simulate_fill) for
+    /// what the preview does and does not account for.
+    pub fn match_preview(
+        &self,
+        side: Side,
+        limit_price: Price,
+        quantity: Quantity,
+        max_fills: usize,
+    ) -> Vec<(OrderId, Price, Quantity)> {
+        self.simulate_fill(side, quantity, Some(limit_price))
+            .executions
+            .into_iter()
+            .take(max_fills)
+            .map(|execution| {
+                (
+                    execution.maker_order_id,
+                    execution.price,
+                    execution.quantity,
+                )
+            })
+            .collect()
+    }
+
+    /// Register a callback that receives a statistics snapshot every `interval`
+    /// add/cancel operations
+    ///
+    /// The callback runs inside the operation that crosses the boundary.
+    /// Operation latencies are only measured while a callback is registered,
+    /// and read as zero without `std`.
+    pub fn on_stats(&mut self, interval: u64, callback: StatsCallback) {
+        self.stats_hook = Some(StatsHook {
+            interval: interval.max(1),
+            callback,
+            operations: 0,
+            latencies_ns: Vec::with_capacity(interval.max(1) as usize),
+        });
+    }
+
+    /// Remove the stats callback and stop measuring latencies
+    pub fn clear_stats_callback(&mut self) {
+        self.stats_hook = None;
+    }
+
+    /// Register a callback invoked for every execution as it is created
+    ///
+    /// Executions are delivered in match order - best price first, time
+    /// priority within a level - for limit, market and triggered stop orders
+    /// alike, and are still returned to the submitter as usual. Forks and
+    /// simulations do not inherit the callback.
+    pub fn on_execution(&mut self, callback: ExecutionCallback) {
+        self.execution_hook = Some(callback);
+    }
+
+    /// Remove the execution callback
+    pub fn clear_execution_callback(&mut self) {
+        self.execution_hook = None;
+    }
+
+    /// Register a callback invoked whenever a submitted order is rejected
+    ///
+    /// It receives the order as submitted and the error the submitting call
+    /// returns, for every entry point that adds orders. Stop-limit orders
+    /// cancelled when they fire are not submissions and are reported through
+    /// [`take_rejected_stops`](Self::take_rejected_stops) instead. Forks and
+    /// simulations do not inherit the callback.
+    pub fn on_reject(&mut self, callback: RejectCallback) {
+        self.reject_hook = Some(callback);
+    }
+
+    /// Remove the reject callback
+    pub fn clear_reject_callback(&mut self) {
+        self.reject_hook = None;
+    }
+
+    /// Register a callback invoked with the new best bid and ask whenever an
+    /// operation leaves either different from before
+    ///
+    /// Operations that leave the top of book where it was - a deeper insert,
+    /// a partial fill of the best level - fire nothing. Forks and simulations
+    /// do not inherit the callback.
+    pub fn on_bbo_change(&mut self, callback: BboCallback) {
+        self.bbo_hook = Some(BboHook {
+            callback,
+            last: (self.best_bid(), self.best_ask()),
+        });
+    }
+
+    /// Remove the BBO callback
+    pub fn clear_bbo_callback(&mut self) {
+        self.bbo_hook = None;
+    }
+
+    /// Register a callback invoked after each operation with the aggregate
+    /// levels it changed
+    ///
+    /// Each update carries a level's new visible quantity, 0 once the level
+    /// is gone, and is relative to the book as it stood when the callback was
+    /// registered: seed a mirror from [`market_depth`](Self::market_depth)
+    /// and apply every batch to keep it in step. Levels an operation touched
+    /// but left at the same quantity are not reported, and operations that
+    /// change no level fire nothing. Forks and simulations do not inherit the
+    /// callback.
+    pub fn on_depth_update(&mut self, callback: DepthCallback) {
+        let visible =
+            |level: &Option<PriceLevel>| level.as_ref().map_or(0, PriceLevel::displayed_quantity);
+        self.depth_hook = Some(DepthHook {
+            callback,
+            last_bids: self.buy_levels.iter().map(visible).collect(),
+            last_asks: self.sell_levels.iter().map(visible).collect(),
+            touched: Vec::new(),
+        });
+    }
+
+    /// Remove the depth callback
+    pub fn clear_depth_callback(&mut self) {
+        self.depth_hook = None;
+    }
+
+    /// Note that the level at `idx` on `side` may have changed
+    #[inline]
+    fn touch_level(&mut self, side: Side, idx: usize) {
+        if let Some(hook) = self.depth_hook.as_mut() {
+            hook.touched.push((side, idx));
+        }
+    }
+
+    /// Publish the operation's effect to the depth and BBO callbacks
+    #[inline]
+    fn notify_market_data(&mut self) {
+        self.notify_depth();
+        self.notify_bbo();
+    }
+
+    /// Report the levels touched since the last report that changed quantity
+    fn notify_depth(&mut self) {
+        let Some(mut hook) = self.depth_hook.take() else {
+            return;
+        };
+        hook.touched
+            .sort_unstable_by_key(|&(side, idx)| (side == Side::Sell, idx));
+        hook.touched.dedup();
+
+        let mut updates = Vec::new();
+        for &(side, idx) in &hook.touched {
+            let (level, last, price) = match side {
+                Side::Buy => (
+                    &self.buy_levels[idx],
+                    &mut hook.last_bids[idx],
+                    self.buy_idx_to_price(idx),
+                ),
+                Side::Sell => (
+                    &self.sell_levels[idx],
+                    &mut hook.last_asks[idx],
+                    self.sell_idx_to_price(idx),
+                ),
+            };
+            let quantity = level.as_ref().map_or(0, PriceLevel::displayed_quantity);
+            if quantity != *last {
+                *last = quantity;
+                updates.push(DepthUpdate {
+                    side,
+                    price,
+                    quantity,
+                });
+            }
+        }
+        hook.touched.clear();
+        if !updates.is_empty() {
+            (hook.callback)(&updates);
+        }
+        self.depth_hook = Some(hook);
+    }
+
+    /// Report the top of book to the BBO callback if it moved
+    #[inline]
+    fn notify_bbo(&mut self) {
+        if self.bbo_hook.is_none() {
+            return;
+        }
+        let current = (self.best_bid(), self.best_ask());
+        if let Some(hook) = self.bbo_hook.as_mut()
+            && hook.last != current
+        {
+            hook.last = current;
+            (hook.callback)(current.0, current.1);
+        }
+    }
+
+    /// Start tracking each account's net position from its executions
+    ///
+    /// Both the resting and the aggressing account of every match move. While
+    /// tracking is on, reduce-only orders are capped at submission to the
+    /// position their account can reduce, and rejected with
+    /// [`OrderError::WouldIncreasePosition`] if it has none on that side.
+    /// Without tracking the reduce-only flag is ignored. Positions start flat,
+    /// and executions applied through [`apply_event`](Self::apply_event) are
+    /// not counted.
+    pub fn enable_position_tracking(&mut self) {
+        self.positions.get_or_insert_with(BTreeMap::new);
+    }
+
+    /// Net position of `account_id`, buys positive and sells negative
+    ///
+    /// Zero for accounts that have not traded or while tracking is off.
+    pub fn position(&self, account_id: u64) -> i64 {
+        self.account_position(account_id).net
+    }
+
+    /// Total quantity `account_id` has traded on either side
+    ///
+    /// Zero for accounts that have not traded or while tracking is off.
+    pub fn traded_volume(&self, account_id: u64) -> Quantity {
+        self.account_position(account_id).traded_volume
+    }
+
+    fn account_position(&self, account_id: u64) -> AccountPosition {
+        self.positions
+            .as_ref()
+            .and_then(|positions| positions.get(&account_id))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Journal every order submission, auction order, uncross, cancel (single
+    /// or bulk), reduction, amend and expiry sweep to `journal` before it is
+    /// applied
+    ///
+    /// Operations are journaled once they pass validation, so orders, cancels
+    /// and amends the book rejects outright leave no entry. If an entry cannot
+    /// be written the operation fails with [`OrderError::Journal`] without
+    /// touching the book, and an expiry sweep expires nothing. The stop
+    /// condition of [`add_market_order_until`](Self::add_market_order_until)
+    /// cannot be journaled; such orders replay as plain market orders. Neither
+    /// is [`clear`](Self::clear), so attach a fresh journal after clearing.
+    /// Feed events given to [`apply_event`](Self::apply_event) are not
+    /// journaled either: a mirrored book is rebuilt from its venue's feed,
+    /// not replayed.
+    #[cfg(feature = "std")]
+    pub fn set_journal(&mut self, journal: Box<dyn Journal>) {
+        self.journal = Some(journal);
+    }
+
+    /// Detach the journal, returning it
+    #[cfg(feature = "std")]
+    pub fn take_journal(&mut self) -> Option<Box<dyn Journal>> {
+        self.journal.take()
+    }
+
+    /// Re-apply every entry of `journal` to this book, in sequence order
+    ///
+    /// Starting from an empty book built with the same configuration and
+    /// policies as the journaled one, the result has the same orders, depth
+    /// and statistics; only the timestamps amends put on orders come from the
+    /// replaying clock. Replayed operations are not journaled again.
+    #[cfg(feature = "std")]
+    pub fn replay(mut self, journal: &mut dyn Journal) -> std::io::Result<OrderBook> {
+        let own_journal = self.journal.take();
+        for entry in journal.entries()? {
+            // Operations that failed while being applied fail identically
+            match entry.op {
+                JournalOp::AddOrder { order, worst_price } => {
+                    let _ = self.submit(order.to_order(), worst_price, |_| false);
+                }
+                JournalOp::CancelOrder { order_id } => {
+                    let _ = self.cancel_order(order_id);
+                }
+                JournalOp::Reduce { order_id, quantity } => {
+                    let _ = self.reduce_order(order_id, quantity);
+                }
+                JournalOp::MassCancel { side, account_id } => {
+                    self.mass_cancel(side, account_id);
+                }
+                JournalOp::Amend {
+                    order_id,
+                    price,
+                    quantity,
+                } => {
+                    let _ = self.amend_order(order_id, price, quantity);
+                }
+                JournalOp::Expire { now_ns } => {
+                    self.expire_orders(now_ns);
+                }
+                JournalOp::Housekeeping { now_ns, last_price } => {
+                    self.housekeeping(now_ns, last_price);
+                }
+                JournalOp::AuctionOrder { order } => {
+                    let _ = self.add_auction_order(order.to_order());
+                }
+                JournalOp::Uncross => {
+                    self.uncross();
+                }
+            }
+            self.journal_sequence = entry.sequence;
+        }
+        self.journal = own_journal;
+        Ok(self)
+    }
+
+    /// Append `op` to the journal, if one is attached
+    #[cfg(feature = "std")]
+    fn journal_op(&mut self, op: JournalOp) -> Result<(), OrderError> {
+        let timestamp = self.now();
+        let Some(journal) = self.journal.as_mut() else {
+            return Ok(());
+        };
+        let entry = JournalEntry {
+            sequence: self.journal_sequence + 1,
+            timestamp,
+            op,
+        };
+        journal
+            .append(&entry)
+            .map_err(|err| OrderError::Journal(err.to_string()))?;
+        self.journal_sequence = entry.sequence;
+        Ok(())
+    }
+
+    /// Journals need `std`; without it there is never one attached
+    #[cfg(not(feature = "std"))]
+    #[inline]
+    fn journal_op(&mut self, _op: JournalOp) -> Result<(), OrderError> {
+        Ok(())
+    }
+
+    /// Deliver a statistics snapshot to the registered callback immediately
+    pub fn emit_stats(&mut self) {
+        if let Some(mut hook) = self.stats_hook.take() {
+            self.deliver_stats(&mut hook);
+            self.stats_hook = Some(hook);
+        }
+    }
+
+    /// Record one operation's latency and fire the callback on the interval
+    fn record_operation(&mut self, latency_ns: u64) {
+        if let Some(mut hook) = self.stats_hook.take() {
+            hook.operations += 1;
+            hook.latencies_ns.push(latency_ns);
 
-                    let price = self.buy_idx_to_price(idx);
+            if hook.operations % hook.interval == 0 {
+                self.deliver_stats(&mut hook);
+            }
+            self.stats_hook = Some(hook);
+        }
+    }
+
+    fn deliver_stats(&self, hook: &mut StatsHook) {
+        let latencies = &mut hook.latencies_ns;
+        latencies.sort_unstable();
 
-                    // Check if the price is acceptable
-                    if price < order.price {
+        let percentile = |pct: usize| {
+            if latencies.is_empty() {
+                0
+            } else {
+                latencies[(latencies.len() * pct / 100).min(latencies.len() - 1)]
+            }
+        };
+
+        let snapshot = StatsSnapshot {
+            summary: self.summary(),
+            operations: hook.operations,
+            sample_count: latencies.len(),
+            p50_latency_ns: percentile(50),
+            p99_latency_ns: percentile(99),
+            max_latency_ns: latencies.last().copied().unwrap_or(0),
+        };
+
+        latencies.clear();
+        (hook.callback)(&snapshot);
+    }
+
+    /// Require limit orders to overlap the opposite side by at least
+    /// `increment` before they trade
+    ///
+    /// With the default of 0 an order trades as soon as it touches the
+    /// opposite best price. A larger increment suppresses dust-level crosses
+    /// caused by fixed-point rounding; such orders are handled as non-marketable
+    /// and go straight to the resting path. Market orders are unaffected.
+    pub fn set_min_cross_increment(&mut self, increment: Price) {
+        self.min_cross_increment = increment;
+    }
+
+    /// Choose how matching treats an aggressor meeting a resting order from
+    /// the same account
+    ///
+    /// Only orders tagged with an account take part; untagged orders always
+    /// trade. Whatever is cancelled under the policy produces no execution,
+    /// and a cancelled aggressor remainder neither rests nor counts as
+    /// unfilled.
+    pub fn set_self_trade_policy(&mut self, policy: SelfTradePolicy) {
+        self.self_trade_policy = policy;
+    }
+
+    /// Choose how fills are allocated among the orders at one price level
+    pub fn set_matching_policy(&mut self, policy: MatchingPolicy) {
+        self.matching_policy = policy;
+    }
+
+    /// Cap the inline order queue of newly created price levels
+    ///
+    /// Orders beyond `capacity` at a level go to a secondary spill queue,
+    /// keeping shallow levels compact while still accepting pathological
+    /// depth. `None` (the default) lets each level grow a single queue.
+    /// Existing levels keep the setting they were created with.
+    pub fn set_level_inline_capacity(&mut self, capacity: Option<usize>) {
+        self.level_inline_capacity = capacity;
+    }
+
+    /// Reject every new order until `enable` is called
+    ///
+    /// This is an operational kill switch: resting orders stay in the book and
+    /// can still be cancelled.
+    pub fn disable(&self) {
+        self.disabled.store(true, Ordering::Relaxed);
+    }
+
+    /// Accept new orders again after `disable`
+    pub fn enable(&self) {
+        self.disabled.store(false, Ordering::Relaxed);
+    }
+
+    /// Check whether the kill switch is engaged
+    pub fn is_disabled(&self) -> bool {
+        self.disabled.load(Ordering::Relaxed)
+    }
+
+    /// Get a snapshot of market depth
+    ///
+    /// Hidden orders are left out of the quantities, and a level holding
+    /// nothing but hidden orders is skipped.
+    pub fn market_depth(&self, levels: usize) -> (DepthSide, DepthSide) {
+        let mut bids = Vec::with_capacity(levels);
+        let mut asks = Vec::with_capacity(levels);
+
+        // Both sides are indexed best first, so start at the cached best and
+        // hop between occupied slots instead of scanning the empty ones
+        let mut current_idx = self.best_bid_idx;
+        while let Some(idx) = current_idx.filter(|_| bids.len() < levels) {
+            if let Some(level) = self.buy_levels[idx]
+                .as_ref()
+                .filter(|level| level.displayed_quantity() > 0)
+            {
+                bids.push((self.buy_idx_to_price(idx), level.displayed_quantity()));
+            }
+            current_idx = self.buy_occupied.next_set(idx + 1);
+        }
+
+        let mut current_idx = self.best_ask_idx;
+        while let Some(idx) = current_idx.filter(|_| asks.len() < levels) {
+            if let Some(level) = self.sell_levels[idx]
+                .as_ref()
+                .filter(|level| level.displayed_quantity() > 0)
+            {
+                asks.push((self.sell_idx_to_price(idx), level.displayed_quantity()));
+            }
+            current_idx = self.sell_occupied.next_set(idx + 1);
+        }
+
+        (bids, asks)
+    }
+
+    /// Get market depth on a fixed ladder of `levels` consecutive ticks from
+    /// each side's best price
+    ///
+    /// Ticks with nothing shown are reported with quantity 0, so the prices
+    /// only move when the best price does. A side stops short at the edge of
+    /// the price grid and is empty while the side has no orders.
+    pub fn market_depth_ladder(&self, levels: usize) -> (DepthSide, DepthSide) {
+        let ladder = |best_idx: Option<usize>,
+                      side_levels: &[Option<PriceLevel>],
+                      idx_to_price: fn(&Self, usize) -> Price| {
+            best_idx.map_or_else(Vec::new, |best| {
+                (best..self.price_levels.min(best.saturating_add(levels)))
+                    .map(|idx| {
+                        let quantity = side_levels[idx]
+                            .as_ref()
+                            .map_or(0, PriceLevel::displayed_quantity);
+                        (idx_to_price(self, idx), quantity)
+                    })
+                    .collect()
+            })
+        };
+        (
+            ladder(self.best_bid_idx, &self.buy_levels, Self::buy_idx_to_price),
+            ladder(
+                self.best_ask_idx,
+                &self.sell_levels,
+                Self::sell_idx_to_price,
+            ),
+        )
+    }
+
+    /// Get the depth of every level priced within `[low, high]`
+    ///
+    /// Bids come best (highest) first and asks best (lowest) first, as in
+    /// [`market_depth`](Self::market_depth). Only the slots between the
+    /// bounds are scanned.
+    pub fn depth_in_range(&self, low: Price, high: Price) -> (DepthSide, DepthSide) {
+        let (mut bids, mut asks) = (Vec::new(), Vec::new());
+        if low > high {
+            return (bids, asks);
+        }
+        let (base, tick) = (self.base_price, self.tick_size);
+        let last_idx = self.price_levels - 1;
+
+        // Bid slots get cheaper as the index grows, ask slots dearer
+        if low <= base {
+            let first = (base - high.min(base)).div_ceil(tick) as usize;
+            let last = (((base - low) / tick) as usize).min(last_idx);
+            let mut current_idx = self.buy_occupied.next_set(first);
+            while let Some(idx) = current_idx.filter(|&idx| idx <= last) {
+                if let Some(level) = self.buy_levels[idx]
+                    .as_ref()
+                    .filter(|level| level.displayed_quantity() > 0)
+                {
+                    bids.push((self.buy_idx_to_price(idx), level.displayed_quantity()));
+                }
+                current_idx = self.buy_occupied.next_set(idx + 1);
+            }
+        }
+        if high >= base {
+            let first = (low.max(base) - base).div_ceil(tick) as usize;
+            let last = (((high - base) / tick) as usize).min(last_idx);
+            let mut current_idx = self.sell_occupied.next_set(first);
+            while let Some(idx) = current_idx.filter(|&idx| idx <= last) {
+                if let Some(level) = self.sell_levels[idx]
+                    .as_ref()
+                    .filter(|level| level.displayed_quantity() > 0)
+                {
+                    asks.push((self.sell_idx_to_price(idx), level.displayed_quantity()));
+                }
+                current_idx = self.sell_occupied.next_set(idx + 1);
+            }
+        }
+
+        (bids, asks)
+    }
+
+    /// Get market depth with prices rounded for display
+    ///
+    /// Each level's price is rounded to the nearest multiple of `round_to_ticks`
+    /// ticks and quantities landing on the same rounded price are summed. Up to
+    /// `levels` rounded prices are returned per side.
+    pub fn display_depth(&self, levels: usize, round_to_ticks: Price) -> (DepthSide, DepthSide) {
+        let step = round_to_ticks.max(1) * self.tick_size;
+
+        let bids = self
+            .buy_levels
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, level)| {
+                level
+                    .as_ref()
+                    .map(|level| (self.buy_idx_to_price(idx), level.displayed_quantity()))
+            })
+            .filter(|&(_, quantity)| quantity > 0);
+        let asks = self
+            .sell_levels
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, level)| {
+                level
+                    .as_ref()
+                    .map(|level| (self.sell_idx_to_price(idx), level.displayed_quantity()))
+            })
+            .filter(|&(_, quantity)| quantity > 0);
+
+        (
+            Self::round_levels(bids, levels, step),
+            Self::round_levels(asks, levels, step),
+        )
+    }
+
+    /// Collapse price-ordered levels into buckets rounded to `step`
+    fn round_levels(
+        prices: impl Iterator<Item = (Price, Quantity)>,
+        levels: usize,
+        step: Price,
+    ) -> DepthSide {
+        let mut rounded_levels: DepthSide = Vec::with_capacity(levels);
+
+        for (price, quantity) in prices {
+            let rounded = (price + step / 2) / step * step;
+
+            match rounded_levels.last_mut() {
+                Some(last) if last.0 == rounded => last.1 += quantity,
+                _ => {
+                    if rounded_levels.len() >= levels {
                         break;
                     }
+                    rounded_levels.push((rounded, quantity));
+                }
+            }
+        }
 
-                    // Get a mutable reference to the price level
-                    if let Some(ref mut level) = self.buy_levels[idx] {
-                        // Process all orders at this level
-                        let resting_indices = level.order_indices.clone();
+        rounded_levels
+    }
 
-                        for resting_idx in resting_indices {
-                            if order.quantity == 0 {
-                                break;
-                            }
+    /// Get a snapshot of market depth as typed levels
+    pub fn market_depth_typed(&self, levels: usize) -> (Vec<DepthLevel>, Vec<DepthLevel>) {
+        let (bids, asks) = self.market_depth(levels);
+        (
+            bids.into_iter().map(DepthLevel::from).collect(),
+            asks.into_iter().map(DepthLevel::from).collect(),
+        )
+    }
 
-                            let resting_order = unsafe { self.order_pool.get_mut(resting_idx) };
-                            let match_qty = std::cmp::min(resting_order.quantity, order.quantity);
-
-                            // Update quantities
-                            resting_order.quantity -= match_qty;
-                            order.quantity -= match_qty;
-                            level.total_quantity -= match_qty;
-
-                            // Update matched quantity statistic
-                            self.total_quantity_matched += match_qty;
-
-                            // Create execution report
-                            executions.push(Execution {
-                                order_id: resting_order.order_id,
-                                price,
-                                quantity: match_qty,
-                                timestamp: precise_time_ns(),
-                                side: resting_order.side(),
-                            });
-
-                            // If resting order is fully matched, remove it
-                            if resting_order.quantity == 0 {
-                                level.order_indices.retain(|&idx| idx != resting_idx);
-                                self.order_id_to_index[resting_order.order_id as usize] = None;
-                                self.order_pool.deallocate(resting_idx);
-                                #[cfg(feature = "perf")]
-                                {
-                                    self.order_count -= 1;
-                                }
-                            }
-                        }
+    /// Get an order-by-order (L3) snapshot of the top `levels` of each side
+    ///
+    /// Each level lists its orders in the sequence they would be matched, with
+    /// their visible quantity; hidden iceberg reserve is not shown, and
+    /// neither are hidden orders or levels holding nothing else.
+    pub fn order_book_l3(&self, levels: usize) -> (L3Side, L3Side) {
+        let queues = |side_levels: &[Option<PriceLevel>],
+                      idx_to_price: fn(&Self, usize) -> Price| {
+            side_levels
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, level)| {
+                    let level = level.as_ref()?;
+                    let queue: Vec<(OrderId, Quantity)> = level
+                        .iter()
+                        .map(|index| unsafe { self.order_pool.get(index) })
+                        .filter(|order| !order.hidden())
+                        .map(|order| (order.order_id, order.visible_quantity()))
+                        .collect();
+                    (!queue.is_empty()).then(|| (idx_to_price(self, idx), queue))
+                })
+                .take(levels)
+                .collect()
+        };
+        (
+            queues(&self.buy_levels, Self::buy_idx_to_price),
+            queues(&self.sell_levels, Self::sell_idx_to_price),
+        )
+    }
+
+    /// Get a snapshot of market depth including the number of resting orders
+    /// at each level
+    pub fn market_depth_detailed(&self, levels: usize) -> (Vec<DepthLevel>, Vec<DepthLevel>) {
+        let detail = |side_levels: &[Option<PriceLevel>],
+                      idx_to_price: fn(&Self, usize) -> Price| {
+            side_levels
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, level)| {
+                    let level = level
+                        .as_ref()
+                        .filter(|level| level.displayed_quantity() > 0)?;
+                    Some(DepthLevel {
+                        price: idx_to_price(self, idx),
+                        quantity: level.displayed_quantity(),
+                        order_count: self.lit_order_count(level),
+                    })
+                })
+                .take(levels)
+                .collect()
+        };
+        (
+            detail(&self.buy_levels, Self::buy_idx_to_price),
+            detail(&self.sell_levels, Self::sell_idx_to_price),
+        )
+    }
+
+    /// Orders at `level` that are not hidden
+    fn lit_order_count(&self, level: &PriceLevel) -> usize {
+        if level.dark_quantity == 0 {
+            level.order_count()
+        } else {
+            level
+                .iter()
+                .filter(|&index| !unsafe { self.order_pool.get(index) }.hidden())
+                .count()
+        }
+    }
+
+    /// Write a price ladder of the top `levels` of each side to `w`
+    ///
+    /// Asks are listed above bids, both from the highest price down, with
+    /// each level's quantity and order count and a spread marker centred
+    /// between the sides. Meant for debugging; the layout may change.
+    pub fn render(&self, w: &mut impl core::fmt::Write, levels: usize) -> core::fmt::Result {
+        let (bids, asks) = self.market_depth_detailed(levels);
+        let price =
+            |level: &DepthLevel| format_price(level.price, self.price_offset, self.price_scale);
+        let width = bids
+            .iter()
+            .chain(&asks)
+            .map(|level| price(level).len())
+            .max()
+            .unwrap_or(0)
+            .max("Price".len());
+        let row = |w: &mut dyn core::fmt::Write,
+                   price: &str,
+                   quantity: &dyn core::fmt::Display,
+                   orders: &dyn core::fmt::Display| {
+            writeln!(w, "{:>width$} | {:>10} | {:>6}", price, quantity, orders)
+        };
+
+        row(w, "Price", &"Quantity", &"Orders")?;
+        for level in asks.iter().rev() {
+            row(w, &price(level), &level.quantity, &level.order_count)?;
+        }
+        let marker = match self.spread() {
+            Some(spread) => format!(" spread {} ", format_price(spread, 0, self.price_scale)),
+            None => " no spread ".to_string(),
+        };
+        writeln!(w, "{:-^total$}", marker, total = width + 22)?;
+        for level in &bids {
+            row(w, &price(level), &level.quantity, &level.order_count)?;
+        }
+        Ok(())
+    }
+
+    /// Get performance statistics
+    #[cfg(feature = "perf")]
+    pub fn performance_stats(&self) -> (Duration, Duration, Duration, usize) {
+        (
+            self.last_insert_time,
+            self.last_match_time,
+            self.last_cancel_time,
+            self.live_order_count,
+        )
+    }
+
+    /// Quantity shown on the bid side, hidden orders included; iceberg
+    /// reserves are not counted until a slice of them is shown. Saturates at
+    /// `Quantity::MAX`.
+    #[inline]
+    pub fn total_bid_quantity(&self) -> Quantity {
+        Quantity::try_from(self.bid_quantity).unwrap_or(Quantity::MAX)
+    }
+
+    /// Quantity shown on the ask side, counted like
+    /// [`total_bid_quantity`](Self::total_bid_quantity). Saturates at
+    /// `Quantity::MAX`.
+    #[inline]
+    pub fn total_ask_quantity(&self) -> Quantity {
+        Quantity::try_from(self.ask_quantity).unwrap_or(Quantity::MAX)
+    }
+
+    /// Number of resting orders on the book, not counting dormant stops
+    pub fn len(&self) -> usize {
+        self.live_order_count
+    }
+
+    /// Whether no orders are resting on the book
+    pub fn is_empty(&self) -> bool {
+        self.live_order_count == 0
+    }
+
+    /// Get the symbol for this orderbook
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// The price grid and order limits the book was built with
+    pub fn config(&self) -> BookConfig {
+        BookConfig {
+            base_price: self.base_price,
+            tick_size: self.tick_size,
+            price_levels: self.price_levels,
+            price_scale: self.price_scale,
+            price_offset: self.price_offset,
+            max_order_quantity: self.max_order_quantity,
+            max_order_notional: self.max_order_notional,
+            price_collar_bps: self.price_collar_bps,
+            orders_per_level: self.orders_per_level,
+            max_pool_capacity: self.max_pool_capacity,
+        }
+    }
+
+    /// Whether `other` is in the same logical state as this book
+    ///
+    /// Compares the symbol, the configuration, every resting order level by
+    /// level in time priority (hidden reserves, timestamps and attributes
+    /// included), the dormant stop orders and the best bid and ask. Policies,
+    /// statistics, callbacks and timing fields are ignored, as is where each
+    /// order sits in the pool.
+    pub fn state_eq(&self, other: &OrderBook) -> bool {
+        self.symbol == other.symbol
+            && self.config() == other.config()
+            && self.best_bid() == other.best_bid()
+            && self.best_ask() == other.best_ask()
+            && self.resting_records().eq(other.resting_records())
+            && self
+                .stop_orders
+                .iter()
+                .map(OrderRecord::from)
+                .eq(other.stop_orders.iter().map(OrderRecord::from))
+    }
+
+    /// Resting orders level by level, best price first, in time priority
+    fn resting_records(&self) -> impl Iterator<Item = OrderRecord> + '_ {
+        self.buy_levels
+            .iter()
+            .chain(&self.sell_levels)
+            .flatten()
+            .flat_map(move |level| {
+                level
+                    .iter()
+                    .map(move |index| OrderRecord::from(unsafe { self.order_pool.get(index) }))
+            })
+    }
 
-                        // If the level is now empty, remove it
-                        if level.is_empty() {
-                            self.buy_levels[idx] = None;
+    /// Capture the complete state of the book
+    ///
+    /// Resting orders are recorded level by level, best price first, in time
+    /// priority within each level, so [`restore`](Self::restore) can rebuild
+    /// the same queues.
+    pub fn snapshot(&self) -> BookSnapshot {
+        let orders = self.resting_records().collect();
+        let buy_prices = (0..self.price_levels).map(|idx| self.buy_idx_to_price(idx));
+        let sell_prices = (0..self.price_levels).map(|idx| self.sell_idx_to_price(idx));
+        let traded_volume = buy_prices
+            .zip(self.buy_traded_volume.iter().copied())
+            .chain(sell_prices.zip(self.sell_traded_volume.iter().copied()))
+            .filter(|&(_, quantity)| quantity > 0)
+            .collect();
+
+        BookSnapshot {
+            symbol: self.symbol.clone(),
+            capacity: self.order_pool.total_capacity(),
+            config: self.config(),
+            min_cross_increment: self.min_cross_increment,
+            self_trade_policy: self.self_trade_policy,
+            matching_policy: self.matching_policy,
+            level_inline_capacity: self.level_inline_capacity,
+            total_orders_processed: self.total_orders_processed,
+            total_quantity_matched: self.total_quantity_matched,
+            total_notional_matched: self.total_notional_matched,
+            last_trade_id: self.last_trade_id,
+            last_trade_price: self.last_trade_price,
+            last_trade_quantity: self.last_trade_quantity,
+            traded_volume,
+            orders,
+            stop_orders: self.stop_orders.iter().map(OrderRecord::from).collect(),
+            auction_orders: self.auction_orders.iter().map(OrderRecord::from).collect(),
+        }
+    }
 
-                            // Find the next price level
-                            current_idx = None;
-                            for i in (idx + 1)..PRICE_LEVELS {
-                                if self.buy_levels[i].is_some() {
-                                    current_idx = Some(i);
-                                    break;
-                                }
-                            }
+    /// Rebuild a book from a [`BookSnapshot`]
+    ///
+    /// Fails if the snapshot holds an order the configured book cannot hold,
+    /// such as a duplicate ID or an out-of-range price.
+    pub fn restore(snapshot: BookSnapshot) -> Result<OrderBook, OrderError> {
+        let mut book = OrderBook::from_config(&snapshot.symbol, snapshot.capacity, snapshot.config);
+        book.min_cross_increment = snapshot.min_cross_increment;
+        book.self_trade_policy = snapshot.self_trade_policy;
+        book.matching_policy = snapshot.matching_policy;
+        book.level_inline_capacity = snapshot.level_inline_capacity;
+
+        for record in &snapshot.orders {
+            book.reserve_order_id(record.order_id)?;
+            book.insert_resting(record.to_order())?;
+        }
+        for record in &snapshot.stop_orders {
+            book.reserve_order_id(record.order_id)?;
+            book.stop_orders.push(record.to_order());
+        }
+        for record in &snapshot.auction_orders {
+            book.reserve_order_id(record.order_id)?;
+            book.auction_orders.push(record.to_order());
+        }
 
-                            // Update best bid if needed
-                            if Some(idx) == self.best_bid_idx {
-                                self.best_bid_idx = current_idx;
-                            }
-                        }
-                    } else {
-                        // This price level should not be empty if we have an index
-                        // Move to the next price level
-                        current_idx = None;
-                        for i in (idx + 1)..PRICE_LEVELS {
-                            if self.buy_levels[i].is_some() {
-                                current_idx = Some(i);
-                                break;
-                            }
-                        }
-                    }
-                }
+        book.total_orders_processed = snapshot.total_orders_processed;
+        book.total_quantity_matched = snapshot.total_quantity_matched;
+        book.total_notional_matched = snapshot.total_notional_matched;
+        book.last_trade_id = snapshot.last_trade_id;
+        book.last_trade_price = snapshot.last_trade_price;
+        book.last_trade_quantity = snapshot.last_trade_quantity;
+        for &(price, quantity) in &snapshot.traded_volume {
+            if let Some(traded) = book.traded_volume_slot(price) {
+                *traded += quantity;
             }
         }
+        Ok(book)
+    }
 
-        #[cfg(feature = "perf")]
-        {
-            self.last_match_time = start_time.elapsed();
-        }
-        executions
+    /// Look up a resting order by ID
+    ///
+    /// Returns `None` once the order has been cancelled or fully filled, and
+    /// for dormant stops that are not on the book yet.
+    pub fn get_order(&self, order_id: OrderId) -> Option<OrderView> {
+        let index = (*self.order_id_to_index.get(order_id as usize)?)?;
+        self.order_pool.try_get(index).map(OrderView::from)
     }
 
-    /// Match a new market order against the book
-    #[inline]
-    fn match_market_order(&mut self, mut order: Order) -> Vec<Execution> {
-        // For market orders, we don't care about price constraints
-        // We just match against the best available prices until filled or liquidity exhausted
-        match order.side() {
-            Side::Buy => {
-                // Match against sells starting from the lowest price
-                let mut executions = Vec::with_capacity(10);
-                let mut current_idx = self.best_ask_idx;
-
-                while let Some(idx) = current_idx {
-                    if order.quantity == 0 {
-                        break;
-                    }
+    /// Quantity that trades before a resting order at its price level
+    ///
+    /// Sums the shown quantity of every order ahead of it in time priority;
+    /// iceberg reserves are left out, since a replenished slice rejoins the
+    /// back of the queue. Returns `None` if the order is not resting.
+    pub fn queue_ahead(&self, order_id: OrderId) -> Option<Quantity> {
+        let index = (*self.order_id_to_index.get(order_id as usize)?)?;
+        let order = self.order_pool.try_get(index)?;
+        let level = match order.side() {
+            Side::Buy => self.buy_levels[self.buy_price_to_idx(order.price)?].as_ref()?,
+            Side::Sell => self.sell_levels[self.sell_price_to_idx(order.price)?].as_ref()?,
+        };
+
+        Some(
+            level
+                .iter()
+                .take_while(|&ahead| ahead != index)
+                .map(|ahead| unsafe { self.order_pool.get(ahead) }.visible_quantity())
+                .sum(),
+        )
+    }
 
-                    let price = self.sell_idx_to_price(idx);
+    /// Price of the most recent execution, if anything has traded
+    pub fn last_trade_price(&self) -> Option<Price> {
+        self.last_trade_price
+    }
 
-                    // Get a mutable reference to the price level
-                    if let Some(ref mut level) = self.sell_levels[idx] {
-                        // Process all orders at this level
-                        let resting_indices = level.order_indices.clone();
+    /// Price and quantity of the most recent execution
+    ///
+    /// After a multi-level sweep this is the final, worst-priced fill.
+    pub fn last_trade(&self) -> Option<(Price, Quantity)> {
+        self.last_trade_price.zip(self.last_trade_quantity)
+    }
 
-                        for resting_idx in resting_indices {
-                            if order.quantity == 0 {
-                                break;
-                            }
+    /// Iterate over every resting order
+    ///
+    /// Bids come first, best (highest) price down, then asks from the lowest
+    /// price up; each level is walked in time priority. Dormant stops are not
+    /// included.
+    pub fn iter_orders(&self) -> impl Iterator<Item = OrderView> + '_ {
+        self.buy_levels
+            .iter()
+            .chain(&self.sell_levels)
+            .flatten()
+            .flat_map(move |level| {
+                level
+                    .iter()
+                    .map(move |index| OrderView::from(unsafe { self.order_pool.get(index) }))
+            })
+    }
 
-                            let resting_order = unsafe { self.order_pool.get_mut(resting_idx) };
-                            let match_qty = std::cmp::min(resting_order.quantity, order.quantity);
-
-                            // Update quantities
-                            resting_order.quantity -= match_qty;
-                            order.quantity -= match_qty;
-                            level.total_quantity -= match_qty;
-                            self.total_quantity_matched += match_qty;
-
-                            // Create execution report
-                            executions.push(Execution {
-                                order_id: resting_order.order_id,
-                                price,
-                                quantity: match_qty,
-                                timestamp: precise_time_ns(),
-                                side: resting_order.side(),
-                            });
-
-                            // If resting order is fully matched, remove it
-                            if resting_order.quantity == 0 {
-                                level.order_indices.retain(|&idx| idx != resting_idx);
-                                self.order_id_to_index[resting_order.order_id as usize] = None;
-                                self.order_pool.deallocate(resting_idx);
-                                #[cfg(feature = "perf")]
-                                {
-                                    self.order_count -= 1;
-                                }
-                            }
-                        }
+    /// Integer price units per whole unit of the quoted currency
+    pub fn price_scale(&self) -> u64 {
+        self.price_scale
+    }
 
-                        // If the level is now empty, remove it
-                        if level.is_empty() {
-                            self.sell_levels[idx] = None;
+    /// Book price that stands for zero
+    pub fn price_offset(&self) -> u64 {
+        self.price_offset
+    }
 
-                            // Find the next price level
-                            current_idx = None;
-                            for i in (idx + 1)..PRICE_LEVELS {
-                                if self.sell_levels[i].is_some() {
-                                    current_idx = Some(i);
-                                    break;
-                                }
-                            }
+    /// Convert a book price to the signed price it represents
+    pub fn to_signed(&self, price: Price) -> i64 {
+        (price as i128 - self.price_offset as i128) as i64
+    }
 
-                            // Update best ask if needed
-                            if Some(idx) == self.best_ask_idx {
-                                self.best_ask_idx = current_idx;
-                            }
-                        }
-                    } else {
-                        // Move to the next price level
-                        current_idx = None;
-                        for i in (idx + 1)..PRICE_LEVELS {
-                            if self.sell_levels[i].is_some() {
-                                current_idx = Some(i);
-                                break;
-                            }
-                        }
-                    }
-                }
+    /// Convert a signed price to the book price representing it
+    ///
+    /// Returns `None` if it falls below the lowest price the offset can
+    /// represent.
+    pub fn from_signed(&self, price: i64) -> Option<Price> {
+        Price::try_from(price as i128 + self.price_offset as i128).ok()
+    }
 
-                executions
-            }
-            Side::Sell => {
-                // Match against buys starting from the highest price
-                let mut executions = Vec::with_capacity(10);
-                let mut current_idx = self.best_bid_idx;
+    /// Convert integer price units to a decimal price
+    pub fn to_decimal(&self, price: Price) -> f64 {
+        self.to_signed(price) as f64 / self.price_scale as f64
+    }
 
-                while let Some(idx) = current_idx {
-                    if order.quantity == 0 {
-                        break;
-                    }
+    /// Convert a decimal price to the nearest integer price units
+    ///
+    /// Returns `None` for non-finite prices and for prices the book cannot
+    /// represent, such as negative ones without a
+    /// [`price_offset`](BookConfig::price_offset). The result still has to sit
+    /// on the book's tick grid to be accepted.
+    pub fn from_decimal(&self, price: f64) -> Option<Price> {
+        let units = core::f64::math::round(price * self.price_scale as f64);
+        if !units.is_finite() || units.abs() >= i64::MAX as f64 {
+            return None;
+        }
+        self.from_signed(units as i64)
+    }
 
-                    let price = self.buy_idx_to_price(idx);
+    /// Get the best bid as a decimal price
+    pub fn best_bid_decimal(&self) -> Option<f64> {
+        self.best_bid().map(|price| self.to_decimal(price))
+    }
 
-                    // Get a mutable reference to the price level
-                    if let Some(ref mut level) = self.buy_levels[idx] {
-                        // Process all orders at this level
-                        let resting_indices = level.order_indices.clone();
+    /// Get the best ask as a decimal price
+    pub fn best_ask_decimal(&self) -> Option<f64> {
+        self.best_ask().map(|price| self.to_decimal(price))
+    }
 
-                        for resting_idx in resting_indices {
-                            if order.quantity == 0 {
-                                break;
-                            }
+    /// Get the best bid price
+    pub fn best_bid(&self) -> Option<Price> {
+        self.best_bid_idx.map(|idx| self.buy_idx_to_price(idx))
+    }
 
-                            let resting_order = unsafe { self.order_pool.get_mut(resting_idx) };
-                            let match_qty = std::cmp::min(resting_order.quantity, order.quantity);
-
-                            // Update quantities
-                            resting_order.quantity -= match_qty;
-                            order.quantity -= match_qty;
-                            level.total_quantity -= match_qty;
-                            self.total_quantity_matched += match_qty;
-
-                            // Create execution report
-                            executions.push(Execution {
-                                order_id: resting_order.order_id,
-                                price,
-                                quantity: match_qty,
-                                timestamp: precise_time_ns(),
-                                side: resting_order.side(),
-                            });
-
-                            // If resting order is fully matched, remove it
-                            if resting_order.quantity == 0 {
-                                level.order_indices.retain(|&idx| idx != resting_idx);
-                                self.order_id_to_index[resting_order.order_id as usize] = None;
-                                self.order_pool.deallocate(resting_idx);
-                                #[cfg(feature = "perf")]
-                                {
-                                    self.order_count -= 1;
-                                }
-                            }
-                        }
+    /// Get the best ask price
+    pub fn best_ask(&self) -> Option<Price> {
+        self.best_ask_idx.map(|idx| self.sell_idx_to_price(idx))
+    }
 
-                        // If the level is now empty, remove it
-                        if level.is_empty() {
-                            self.buy_levels[idx] = None;
+    /// Get the mid price
+    pub fn mid_price(&self) -> Option<f64> {
+        match (self.best_bid(), self.best_ask()) {
+            (Some(bid), Some(ask)) => Some((bid as f64 + ask as f64) / 2.0),
+            _ => None,
+        }
+    }
 
-                            // Find the next price level
-                            current_idx = None;
-                            for i in (idx + 1)..PRICE_LEVELS {
-                                if self.buy_levels[i].is_some() {
-                                    current_idx = Some(i);
-                                    break;
-                                }
-                            }
+    /// Get the quantity-weighted mean price of the top `levels` of both sides
+    ///
+    /// Every level on either side counts its price once per unit resting
+    /// there, so a fat level drags the value towards itself even when the
+    /// top of book is thin. Unlike [`fair_value`](Self::fair_value) the
+    /// sides are not cross-weighted. Returns `None` if either side is empty.
+    pub fn weighted_mid(&self, levels: usize) -> Option<f64> {
+        let (bids, asks) = self.market_depth(levels);
+        if bids.is_empty() || asks.is_empty() {
+            return None;
+        }
 
-                            // Update best bid if needed
-                            if Some(idx) == self.best_bid_idx {
-                                self.best_bid_idx = current_idx;
-                            }
-                        }
-                    } else {
-                        // Move to the next price level
-                        current_idx = None;
-                        for i in (idx + 1)..PRICE_LEVELS {
-                            if self.buy_levels[i].is_some() {
-                                current_idx = Some(i);
-                                break;
-                            }
-                        }
-                    }
-                }
+        let (notional, quantity) =
+            bids.iter()
+                .chain(&asks)
+                .fold((0u128, 0u128), |(notional, quantity), &(price, qty)| {
+                    (
+                        notional + price as u128 * qty as u128,
+                        quantity + qty as u128,
+                    )
+                });
+        Some(notional as f64 / quantity as f64)
+    }
 
-                executions
-            }
+    /// Get a depth-weighted fair value over the top `depth_levels` of each side
+    ///
+    /// Each side is reduced to its volume-weighted price `P` and total quantity
+    /// `Q` over up to `depth_levels` levels, then combined like a microprice:
+    /// `(P_bid * Q_ask + P_ask * Q_bid) / (Q_bid + Q_ask)`. Deeper bids pull the
+    /// value towards the asks and vice versa. A side with fewer levels simply
+    /// contributes what it has. With one level this is the classic microprice.
+    /// Returns `None` if either side is empty.
+    pub fn fair_value(&self, depth_levels: usize) -> Option<f64> {
+        let (bids, asks) = self.market_depth(depth_levels);
+        if bids.is_empty() || asks.is_empty() {
+            return None;
         }
+
+        let side_totals = |levels: &DepthSide| {
+            levels
+                .iter()
+                .fold((0u128, 0u128), |(notional, quantity), &(price, qty)| {
+                    (
+                        notional + price as u128 * qty as u128,
+                        quantity + qty as u128,
+                    )
+                })
+        };
+        let (bid_notional, bid_qty) = side_totals(&bids);
+        let (ask_notional, ask_qty) = side_totals(&asks);
+
+        let bid_vwap = bid_notional as f64 / bid_qty as f64;
+        let ask_vwap = ask_notional as f64 / ask_qty as f64;
+        let (bid_qty, ask_qty) = (bid_qty as f64, ask_qty as f64);
+
+        Some((bid_vwap * ask_qty + ask_vwap * bid_qty) / (bid_qty + ask_qty))
     }
 
-    /// Get a snapshot of market depth
-    pub fn market_depth(&self, levels: usize) -> (Vec<(u64, u64)>, Vec<(u64, u64)>) {
-        let mut bids = Vec::with_capacity(levels);
-        let mut asks = Vec::with_capacity(levels);
+    /// Get the top-of-book microprice,
+    /// `(best_bid * ask_qty + best_ask * bid_qty) / (bid_qty + ask_qty)`
+    ///
+    /// Only the sizes at the best prices are used, so the value leans towards
+    /// the side with less quantity. Like [`market_depth`](Self::market_depth),
+    /// the top of book is the best displayed level, leaving hidden orders
+    /// out. Returns `None` if either side shows nothing.
+    pub fn microprice(&self) -> Option<f64> {
+        let (bids, asks) = self.market_depth(1);
+        let (&(bid_price, bid_qty), &(ask_price, ask_qty)) = (bids.first()?, asks.first()?);
+        let (bid_price, ask_price) = (bid_price as f64, ask_price as f64);
+        let (bid_qty, ask_qty) = (bid_qty as f64, ask_qty as f64);
+
+        Some((bid_price * ask_qty + ask_price * bid_qty) / (bid_qty + ask_qty))
+    }
 
-        // Get bid depth (highest to lowest)
-        let mut count = 0;
-        // For buys, we want to scan from lowest index (highest price) upward
-        for idx in 0..PRICE_LEVELS {
-            if count >= levels {
+    /// Total quantity and notional (sum of price * quantity) an order on
+    /// `side` could reach at prices no worse than `limit_price`
+    ///
+    /// A buy walks the asks upward from the best while `price <= limit_price`,
+    /// a sell walks the bids downward while `price >= limit_price`. Only the
+    /// displayed quantity is counted, leaving out hidden orders and iceberg
+    /// reserves. The quantity saturates at `Quantity::MAX` and the notional
+    /// at `u64::MAX`.
+    pub fn liquidity_up_to(&self, side: Side, limit_price: Price) -> (Quantity, u64) {
+        let (occupied, levels) = match side {
+            Side::Buy => (&self.sell_occupied, &self.sell_levels),
+            Side::Sell => (&self.buy_occupied, &self.buy_levels),
+        };
+
+        let mut quantity: Quantity = 0;
+        let mut notional = 0u128;
+        let mut current_idx = occupied.next_set(0);
+        while let Some(idx) = current_idx {
+            let price = match side {
+                Side::Buy => self.sell_idx_to_price(idx),
+                Side::Sell => self.buy_idx_to_price(idx),
+            };
+            let reachable = match side {
+                Side::Buy => price <= limit_price,
+                Side::Sell => price >= limit_price,
+            };
+            if !reachable {
                 break;
             }
 
-            if let Some(ref level) = self.buy_levels[idx] {
-                bids.push((self.buy_idx_to_price(idx), level.total_quantity));
-                count += 1;
+            if let Some(level) = &levels[idx] {
+                let shown = level.displayed_quantity();
+                quantity = quantity.saturating_add(shown);
+                notional += price as u128 * shown as u128;
             }
+            current_idx = occupied.next_set(idx + 1);
         }
 
-        // Get ask depth (lowest to highest)
-        let mut count = 0;
-        // For sells, we want to scan from lowest index (lowest price) upward
-        for idx in 0..PRICE_LEVELS {
-            if count >= levels {
-                break;
-            }
+        (quantity, u64::try_from(notional).unwrap_or(u64::MAX))
+    }
 
-            if let Some(ref level) = self.sell_levels[idx] {
-                asks.push((self.sell_idx_to_price(idx), level.total_quantity));
-                count += 1;
-            }
+    /// Get the quantity imbalance over the top `levels` of each side,
+    /// `(bid_qty - ask_qty) / (bid_qty + ask_qty)`
+    ///
+    /// Ranges from -1 (all asks) to 1 (all bids). Returns `None` if either side
+    /// is empty.
+    pub fn imbalance(&self, levels: usize) -> Option<f64> {
+        let (bids, asks) = self.market_depth(levels);
+        if bids.is_empty() || asks.is_empty() {
+            return None;
         }
 
-        (bids, asks)
-    }
+        let total =
+            |side: &DepthSide| side.iter().map(|&(_, qty)| qty as u128).sum::<u128>() as f64;
+        let (bid_qty, ask_qty) = (total(&bids), total(&asks));
 
-    /// Get performance statistics
-    #[cfg(feature = "perf")]
-    pub fn performance_stats(&self) -> (Duration, Duration, Duration, usize) {
-        (
-            self.last_insert_time,
-            self.last_match_time,
-            self.last_cancel_time,
-            self.order_count,
-        )
+        Some((bid_qty - ask_qty) / (bid_qty + ask_qty))
     }
 
-    /// Get the symbol for this orderbook
-    pub fn symbol(&self) -> &str {
-        &self.symbol
+    /// CRC32 checksum over the top `levels` of each side, for verifying a
+    /// local copy of the book against a feed
+    ///
+    /// Levels are written as `price:quantity` and interleaved best first: bid
+    /// 1, ask 1, bid 2, ask 2, and so on, all joined with `:`. Once one side
+    /// runs out the other continues alone, so bids `9990x10, 9980x20` and ask
+    /// `10010x5` give `9990:10:10010:5:9980:20`. The checksum is the standard
+    /// CRC-32 (IEEE, as in zlib) of that UTF-8 string.
+    pub fn checksum(&self, levels: usize) -> u32 {
+        let (bids, asks) = self.market_depth(levels);
+        let mut fields = Vec::with_capacity(bids.len() + asks.len());
+        for level in 0..bids.len().max(asks.len()) {
+            for side in [&bids, &asks] {
+                if let Some((price, quantity)) = side.get(level) {
+                    fields.push(format!("{}:{}", price, quantity));
+                }
+            }
+        }
+        crc32(fields.join(":").as_bytes())
     }
 
-    /// Get the best bid price
-    pub fn best_bid(&self) -> Option<u64> {
-        self.best_bid_idx.map(|idx| self.buy_idx_to_price(idx))
+    /// Traded value so far, the sum of price * quantity over every execution
+    ///
+    /// Prices are taken in book units, before any
+    /// [`price_scale`](BookConfig::price_scale) or offset is applied.
+    pub fn total_notional_matched(&self) -> u128 {
+        self.total_notional_matched
     }
 
-    /// Get the best ask price
-    pub fn best_ask(&self) -> Option<u64> {
-        self.best_ask_idx.map(|idx| self.sell_idx_to_price(idx))
+    /// Total quantity ever traded at `price`
+    ///
+    /// Unlike a level's resting quantity this only grows: every execution at
+    /// the price counts, whether or not anything still rests there, until the
+    /// book is cleared. Prices off the grid have never traded and read as 0.
+    pub fn traded_volume_at(&self, price: Price) -> Quantity {
+        if let Some(idx) = self.buy_price_to_idx(price) {
+            self.buy_traded_volume[idx]
+        } else if let Some(idx) = self.sell_price_to_idx(price) {
+            self.sell_traded_volume[idx]
+        } else {
+            0
+        }
     }
 
-    /// Get the mid price
-    pub fn mid_price(&self) -> Option<f64> {
-        match (self.best_bid(), self.best_ask()) {
-            (Some(bid), Some(ask)) => Some((bid as f64 + ask as f64) / 2.0),
-            _ => None,
+    /// Traded volume counter for `price`, if it is on the grid
+    fn traded_volume_slot(&mut self, price: Price) -> Option<&mut Quantity> {
+        if let Some(idx) = self.buy_price_to_idx(price) {
+            Some(&mut self.buy_traded_volume[idx])
+        } else {
+            let idx = self.sell_price_to_idx(price)?;
+            Some(&mut self.sell_traded_volume[idx])
         }
     }
 
     /// Get the spread
-    pub fn spread(&self) -> Option<u64> {
+    pub fn spread(&self) -> Option<Price> {
         match (self.best_bid(), self.best_ask()) {
             (Some(bid), Some(ask)) => Some(ask - bid),
             _ => None,
         }
     }
 
+    /// Get the spread as a number of ticks, the same as [`spread`](Self::spread)
+    /// on a book with unit ticks
+    pub fn spread_ticks(&self) -> Option<Price> {
+        self.spread().map(|spread| spread / self.tick_size)
+    }
+
+    /// Get the spread in basis points of the mid price
+    ///
+    /// The mid is taken as a signed price, relative to any
+    /// [`price_offset`](BookConfig::price_offset). Returns `None` if either
+    /// side is empty or the mid is zero.
+    pub fn spread_bps(&self) -> Option<f64> {
+        let mid = self.mid_price()? - self.price_offset as f64;
+        if mid == 0.0 {
+            return None;
+        }
+        Some(10_000.0 * self.spread()? as f64 / mid)
+    }
+
+    /// Get the expected average fill price for sweeping `quantity` from the book
+    ///
+    /// `side` is the aggressor's side, so a buy walks the asks. The volume
+    /// weighted price is rounded to the nearest integer; returns `None` if the
+    /// opposite side cannot fill the full quantity.
+    pub fn expected_fill(&self, side: Side, quantity: Quantity) -> Option<Price> {
+        if quantity == 0 {
+            return None;
+        }
+
+        let levels = match side {
+            Side::Buy => &self.sell_levels,
+            Side::Sell => &self.buy_levels,
+        };
+
+        let mut remaining = quantity;
+        let mut notional: u128 = 0;
+
+        for (idx, level) in levels.iter().enumerate() {
+            if let Some(level) = level {
+                let price = match side {
+                    Side::Buy => self.sell_idx_to_price(idx),
+                    Side::Sell => self.buy_idx_to_price(idx),
+                };
+                let fill = core::cmp::min(remaining, level.total_quantity);
+                notional += price as u128 * fill as u128;
+                remaining -= fill;
+
+                if remaining == 0 {
+                    let quantity = quantity as u128;
+                    return Some(((notional + quantity / 2) / quantity) as Price);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Walk the opposing levels an aggressor at `limit_price` would trade against
+    ///
+    /// Yields `(price, quantity, order_count)` in match order - best price
+    /// first - and stops at the first level the order could not reach,
+    /// honouring the minimum cross increment. Nothing is executed.
+    ///
+    /// Quantities and order counts are what the book shows, so iceberg
+    /// reserves and hidden orders are not included and a level holding only
+    /// hidden orders is skipped.
+    pub fn walk_opposite(
+        &self,
+        side: Side,
+        limit_price: Price,
+    ) -> impl Iterator<Item = (Price, Quantity, usize)> + '_ {
+        self.opposite_levels(side, limit_price)
+            .filter(|(_, level)| level.displayed_quantity() > 0)
+            .map(|(price, level)| {
+                let order_count = self.lit_order_count(level);
+                (price, level.displayed_quantity(), order_count)
+            })
+    }
+
+    /// Opposing levels reachable by an aggressor at `limit_price`, best first
+    fn opposite_levels(
+        &self,
+        side: Side,
+        limit_price: Price,
+    ) -> impl Iterator<Item = (Price, &PriceLevel)> + '_ {
+        let increment = self.min_cross_increment;
+
+        self.all_opposite_levels(side)
+            .take_while(move |&(price, _)| match side {
+                Side::Buy => price + increment <= limit_price,
+                Side::Sell => price >= limit_price + increment,
+            })
+    }
+
+    /// Every level an aggressor on `side` trades against, best first
+    fn all_opposite_levels(&self, side: Side) -> impl Iterator<Item = (Price, &PriceLevel)> + '_ {
+        let (levels, idx_to_price): (_, fn(&Self, usize) -> Price) = match side {
+            Side::Buy => (&self.sell_levels, Self::sell_idx_to_price),
+            Side::Sell => (&self.buy_levels, Self::buy_idx_to_price),
+        };
+
+        levels.iter().enumerate().filter_map(move |(idx, level)| {
+            level.as_ref().map(|level| (idx_to_price(self, idx), level))
+        })
+    }
+
+    /// Estimate the transaction cost of sweeping `quantity` against the book
+    ///
+    /// Returns `(mid, vwap, slippage_ticks)` where `mid` is the current mid price
+    /// rounded to the nearest integer, `vwap` is the expected fill price and
+    /// `slippage_ticks` is `vwap - mid` in ticks - positive for buys paying above
+    /// mid, negative for sells receiving below it. Returns `None` if either side
+    /// is empty or liquidity is insufficient.
+    pub fn arrival_slippage(&self, side: Side, quantity: Quantity) -> Option<(Price, Price, i64)> {
+        let mid = core::f64::math::round(self.mid_price()?) as Price;
+        let vwap = self.expected_fill(side, quantity)?;
+        let slippage_ticks = (vwap as i64 - mid as i64) / self.tick_size as i64;
+
+        Some((mid, vwap, slippage_ticks))
+    }
+
     /// Check if this orderbook is crossed (invalid state)
+    ///
+    /// Bids only rest below `base_price` and asks at or above it, so every way
+    /// of loading orders - matching, feed events or a snapshot restore - keeps
+    /// the book uncrossed. This guards the layout rather than detecting a
+    /// state orders can reach; orders meant to cross are collected with
+    /// [`add_auction_order`](Self::add_auction_order) instead.
     pub fn is_crossed(&self) -> bool {
         match (self.best_bid(), self.best_ask()) {
             (Some(bid), Some(ask)) => bid >= ask,
@@ -839,6 +3701,220 @@ impl OrderBook {
         }
     }
 
+    /// Queue a limit order for the next [`uncross`](Self::uncross)
+    ///
+    /// Auction orders are held off the price grid, so bids and asks may cross
+    /// each other while an auction collects them. Only plain limit orders
+    /// join: other types, and orders that are all-or-none, reduce-only or
+    /// carry a minimum quantity, are rejected with
+    /// [`OrderError::NotAuctionOrder`]. The price must be a whole number of
+    /// ticks from the base price, and the size and notional limits apply.
+    /// Queued orders are cancelled like any other.
+    pub fn add_auction_order(&mut self, order: Order) -> Result<(), OrderError> {
+        let submitted = self.reject_hook.is_some().then(|| order.clone());
+        let result = self.queue_auction_order(order);
+        if let (Err(error), Some(order)) = (&result, submitted) {
+            self.report_reject(&order, error);
+        }
+        result
+    }
+
+    fn queue_auction_order(&mut self, order: Order) -> Result<(), OrderError> {
+        if self.is_disabled() {
+            return Err(OrderError::Disabled);
+        }
+        if order.order_type() != OrderType::Limit
+            || order.all_or_none()
+            || order.reduce_only()
+            || order.min_quantity > 0
+        {
+            return Err(OrderError::NotAuctionOrder(order.order_id));
+        }
+        if order.quantity == 0 {
+            return Err(OrderError::ZeroQuantity(order.order_id));
+        }
+        if !order
+            .price
+            .abs_diff(self.base_price)
+            .is_multiple_of(self.tick_size)
+        {
+            return Err(OrderError::NotTickAligned(order.price));
+        }
+        self.check_order_id(order.order_id)?;
+        self.check_order_size(&order)?;
+        self.journal_op(JournalOp::AuctionOrder {
+            order: OrderRecord::from(&order),
+        })?;
+
+        self.grow_order_ids(order.order_id);
+        self.total_orders_processed += 1;
+        self.auction_orders.push(order);
+        Ok(())
+    }
+
+    /// Number of orders waiting for the next auction
+    #[inline]
+    pub fn auction_order_count(&self) -> usize {
+        self.auction_orders.len()
+    }
+
+    /// Clear the queued auction orders at a single price, returning it along
+    /// with the executions
+    ///
+    /// The clearing price is the queued limit price that matches the most
+    /// quantity, bids at or above it against asks at or below it. Ties go to
+    /// the price leaving the smaller surplus, then to the one nearer the last
+    /// trade, then to the lower price. Every execution is at the clearing
+    /// price: bids are filled best price first and then in arrival order
+    /// against asks taken the same way, the earlier order of each pair being
+    /// the maker. Self-trade prevention does not apply.
+    ///
+    /// Orders that do not cross the clearing price, and any part of an order
+    /// left over at it, stay queued for the next auction. Resting orders on
+    /// the grid take no part. Stops triggered by the clearing price fire on
+    /// the next trade or [`housekeeping`](Self::housekeeping) tick.
+    ///
+    /// Returns a price of 0 and no executions when nothing crosses, while the
+    /// kill switch is set, or if the journal cannot be written.
+    pub fn uncross(&mut self) -> (Price, Vec<Execution>) {
+        if self.is_disabled() {
+            return (0, Vec::new());
+        }
+        let Some(price) = self.auction_clearing_price() else {
+            return (0, Vec::new());
+        };
+        if self.journal_op(JournalOp::Uncross).is_err() {
+            return (0, Vec::new());
+        }
+
+        // Crossing orders best price first, then in arrival order
+        let crossing = |side: Side| {
+            let orders = &self.auction_orders;
+            let mut queue: Vec<usize> = (0..orders.len())
+                .filter(|&i| {
+                    orders[i].side() == side
+                        && match side {
+                            Side::Buy => orders[i].price >= price,
+                            Side::Sell => orders[i].price <= price,
+                        }
+                })
+                .collect();
+            queue.sort_by_key(|&i| match side {
+                Side::Buy => (Price::MAX - orders[i].price, i),
+                Side::Sell => (orders[i].price, i),
+            });
+            queue
+        };
+        let (bids, asks) = (crossing(Side::Buy), crossing(Side::Sell));
+
+        let timestamp = self.now();
+        let mut executions = Vec::new();
+        let (mut next_bid, mut next_ask) = (0, 0);
+        while let (Some(&bid), Some(&ask)) = (bids.get(next_bid), asks.get(next_ask)) {
+            let quantity = core::cmp::min(
+                self.auction_orders[bid].quantity,
+                self.auction_orders[ask].quantity,
+            );
+            self.auction_orders[bid].quantity -= quantity;
+            self.auction_orders[ask].quantity -= quantity;
+
+            self.total_quantity_matched = self.total_quantity_matched.saturating_add(quantity);
+            self.total_notional_matched = self
+                .total_notional_matched
+                .saturating_add(price as u128 * quantity as u128);
+            if let Some(traded) = self.traded_volume_slot(price) {
+                *traded = traded.saturating_add(quantity);
+            }
+            self.last_trade_id += 1;
+
+            let (maker, taker) = if bid < ask { (bid, ask) } else { (ask, bid) };
+            let (maker, taker) = (&self.auction_orders[maker], &self.auction_orders[taker]);
+            let execution = Execution {
+                order_id: maker.order_id,
+                price,
+                quantity,
+                timestamp,
+                side: maker.side(),
+                trade_id: self.last_trade_id,
+                maker_order_id: maker.order_id,
+                taker_order_id: taker.order_id,
+                aggressor_side: taker.side(),
+            };
+            if let Some(callback) = self.execution_hook.as_mut() {
+                callback(&execution);
+            }
+            executions.push(execution);
+
+            if let Some(positions) = self.positions.as_mut() {
+                update_position(positions, maker.account_id, maker.side(), quantity);
+                update_position(positions, taker.account_id, taker.side(), quantity);
+            }
+
+            if self.auction_orders[bid].quantity == 0 {
+                next_bid += 1;
+            }
+            if self.auction_orders[ask].quantity == 0 {
+                next_ask += 1;
+            }
+        }
+
+        self.auction_orders.retain(|order| order.quantity > 0);
+        self.record_last_trade(&executions);
+        (price, executions)
+    }
+
+    /// Queued limit price that matches the most auction quantity, if any
+    /// crosses at all
+    fn auction_clearing_price(&self) -> Option<Price> {
+        // Quantity matched at `price` and the surplus left on one side
+        let volume_at = |price: Price| {
+            let (mut bids, mut asks) = (0u128, 0u128);
+            for order in &self.auction_orders {
+                match order.side() {
+                    Side::Buy if order.price >= price => bids += order.quantity as u128,
+                    Side::Sell if order.price <= price => asks += order.quantity as u128,
+                    _ => {}
+                }
+            }
+            (bids.min(asks), bids.abs_diff(asks))
+        };
+        let distance = |price: Price| self.last_trade_price.map_or(0, |last| last.abs_diff(price));
+
+        self.auction_orders
+            .iter()
+            .map(|order| (order.price, volume_at(order.price)))
+            .filter(|&(_, (volume, _))| volume > 0)
+            .min_by_key(|&(price, (volume, surplus))| {
+                (Reverse(volume), surplus, distance(price), price)
+            })
+            .map(|(price, _)| price)
+    }
+
+    /// Assert, in debug builds, that the book is not crossed and that the
+    /// cached best prices agree with the occupied levels
+    ///
+    /// Matching consumes every level an order crosses before the remainder
+    /// may rest, so a failure here means a matching or bookkeeping bug.
+    #[inline]
+    fn debug_check_uncrossed(&self) {
+        debug_assert_eq!(
+            self.best_bid_idx,
+            self.find_best_bid_idx(),
+            "stale best bid"
+        );
+        debug_assert_eq!(
+            self.best_ask_idx,
+            self.find_best_ask_idx(),
+            "stale best ask"
+        );
+        debug_assert!(
+            !self.is_crossed(),
+            "book crossed at {:?}/{:?}",
+            self.best_bid(),
+            self.best_ask()
+        );
+    }
+
     /// Get a summary of the current orderbook state
     pub fn summary(&self) -> OrderBookSummary {
         let mut buy_level_count = 0;
@@ -858,14 +3934,21 @@ impl OrderBook {
 
         OrderBookSummary {
             symbol: self.symbol.clone(),
+            price_scale: self.price_scale,
+            price_offset: self.price_offset,
             best_bid: self.best_bid(),
             best_ask: self.best_ask(),
             buy_levels: buy_level_count,
             sell_levels: sell_level_count,
-            #[cfg(feature = "perf")]
-            order_count: self.order_count,
+            order_count: self.live_order_count,
+            total_orders: self.bid_order_count + self.ask_order_count,
+            bid_orders: self.bid_order_count,
+            ask_orders: self.ask_order_count,
             total_orders_processed: self.total_orders_processed,
             total_quantity_matched: self.total_quantity_matched,
+            total_notional_matched: self.total_notional_matched,
+            last_trade_price: self.last_trade_price,
+            last_trade_quantity: self.last_trade_quantity,
             #[cfg(feature = "perf")]
             last_insert_time_ns: self.last_insert_time.as_nanos() as u64,
             #[cfg(feature = "perf")]
@@ -876,18 +3959,310 @@ impl OrderBook {
     }
 }
 
+impl Clone for OrderBook {
+    fn clone(&self) -> Self {
+        Self {
+            symbol: self.symbol.clone(),
+            order_pool: self.order_pool.clone(),
+            order_id_to_index: self.order_id_to_index.clone(),
+            max_order_id: self.max_order_id,
+            buy_levels: self.buy_levels.clone(),
+            sell_levels: self.sell_levels.clone(),
+            base_price: self.base_price,
+            tick_size: self.tick_size,
+            price_levels: self.price_levels,
+            price_scale: self.price_scale,
+            price_offset: self.price_offset,
+            max_order_quantity: self.max_order_quantity,
+            max_order_notional: self.max_order_notional,
+            price_collar_bps: self.price_collar_bps,
+            orders_per_level: self.orders_per_level,
+            max_pool_capacity: self.max_pool_capacity,
+            best_bid_idx: self.best_bid_idx,
+            best_ask_idx: self.best_ask_idx,
+            buy_occupied: self.buy_occupied.clone(),
+            sell_occupied: self.sell_occupied.clone(),
+            min_cross_increment: self.min_cross_increment,
+            self_trade_policy: self.self_trade_policy,
+            matching_policy: self.matching_policy,
+            level_inline_capacity: self.level_inline_capacity,
+            stop_orders: self.stop_orders.clone(),
+            rejected_stops: self.rejected_stops.clone(),
+            auction_orders: self.auction_orders.clone(),
+            last_trade_price: self.last_trade_price,
+            last_trade_quantity: self.last_trade_quantity,
+            disabled: AtomicBool::new(self.is_disabled()),
+            // Callbacks belong to the original book
+            stats_hook: None,
+            execution_hook: None,
+            bbo_hook: None,
+            depth_hook: None,
+            reject_hook: None,
+            #[cfg(feature = "std")]
+            journal: None,
+            journal_sequence: self.journal_sequence,
+            clock: self.clock,
+            positions: self.positions.clone(),
+            #[cfg(feature = "perf")]
+            last_insert_time: self.last_insert_time,
+            #[cfg(feature = "perf")]
+            last_match_time: self.last_match_time,
+            #[cfg(feature = "perf")]
+            last_cancel_time: self.last_cancel_time,
+            live_order_count: self.live_order_count,
+            bid_order_count: self.bid_order_count,
+            ask_order_count: self.ask_order_count,
+            bid_quantity: self.bid_quantity,
+            ask_quantity: self.ask_quantity,
+            total_orders_processed: self.total_orders_processed,
+            total_quantity_matched: self.total_quantity_matched,
+            total_notional_matched: self.total_notional_matched,
+            buy_traded_volume: self.buy_traded_volume.clone(),
+            sell_traded_volume: self.sell_traded_volume.clone(),
+            last_trade_id: self.last_trade_id,
+            sequence: self.sequence,
+        }
+    }
+}
+
+/// Render integer price units as a decimal string
+///
+/// Power-of-ten scales print exactly with one digit per decade, so 9900 at a
+/// scale of 100 is `99.00`; other scales fall back to floating point.
+fn format_price(price: Price, offset: u64, scale: u64) -> String {
+    let signed = price as i128 - offset as i128;
+    let sign = if signed < 0 { "-" } else { "" };
+    let units = signed.unsigned_abs() as u64;
+    if scale == 1 {
+        format!("{}{}", sign, units)
+    } else if 10u64.pow(scale.ilog10()) == scale {
+        let digits = scale.ilog10() as usize;
+        format!("{}{}.{:0digits$}", sign, units / scale, units % scale)
+    } else {
+        format!("{}{}", sign, units as f64 / scale as f64)
+    }
+}
+
+/// Bitwise CRC-32 with the reflected IEEE polynomial used by zlib
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// One account's tracked exposure
+#[derive(Debug, Clone, Copy, Default)]
+struct AccountPosition {
+    // Signed with buys positive
+    net: i64,
+    traded_volume: Quantity,
+}
+
+/// Apply a fill of `quantity` on `side` to an account's position
+fn update_position(
+    positions: &mut BTreeMap<u64, AccountPosition>,
+    account_id: Option<u64>,
+    side: Side,
+    quantity: Quantity,
+) {
+    let Some(account_id) = account_id else {
+        return;
+    };
+    let position = positions.entry(account_id).or_default();
+    match side {
+        Side::Buy => position.net += quantity as i64,
+        Side::Sell => position.net -= quantity as i64,
+    }
+    position.traded_volume += quantity;
+}
+
+/// Split `quantity` across resting orders of the given `sizes` by the
+/// largest-remainder method
+///
+/// Requires `quantity` to be less than the total size. Each order gets its
+/// proportional share rounded down; the leftover units go one at a time to
+/// the largest remainders, earlier orders first on a tie.
+fn pro_rata_shares(quantity: Quantity, sizes: &[Quantity]) -> Vec<Quantity> {
+    let total: u128 = sizes.iter().map(|&size| size as u128).sum();
+    let mut shares = Vec::with_capacity(sizes.len());
+    let mut remainders = Vec::with_capacity(sizes.len());
+    for (position, &size) in sizes.iter().enumerate() {
+        let exact = quantity as u128 * size as u128;
+        shares.push((exact / total) as Quantity);
+        remainders.push((exact % total, position));
+    }
+
+    let leftover = quantity - shares.iter().sum::<Quantity>();
+    // Largest remainder first, then earliest in the queue
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    for &(_, position) in remainders.iter().take(leftover as usize) {
+        shares[position] += 1;
+    }
+
+    shares
+}
+
+/// Verify that every execution is priced at or better than the aggressor's limit
+///
+/// A buy aggressor must never pay more than `limit_price` and a sell aggressor
+/// must never receive less. Debug and `strict` builds run this after every limit
+/// match and panic on a violation; release builds skip it entirely.
+pub fn check_execution_prices(
+    side: Side,
+    limit_price: Price,
+    executions: &[Execution],
+) -> Result<(), OrderError> {
+    for exec in executions {
+        let worse = match side {
+            Side::Buy => exec.price > limit_price,
+            Side::Sell => exec.price < limit_price,
+        };
+
+        if worse {
+            return Err(OrderError::ExecutionThroughLimit {
+                order_id: exec.order_id,
+                price: exec.price,
+                side,
+                limit: limit_price,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Complete, serializable state of an [`OrderBook`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BookSnapshot {
+    pub symbol: String,
+    /// Order pool capacity
+    pub capacity: usize,
+    pub config: BookConfig,
+    pub min_cross_increment: Price,
+    pub self_trade_policy: SelfTradePolicy,
+    pub matching_policy: MatchingPolicy,
+    pub level_inline_capacity: Option<usize>,
+    pub total_orders_processed: u64,
+    pub total_quantity_matched: Quantity,
+    pub total_notional_matched: u128,
+    pub last_trade_id: u64,
+    pub last_trade_price: Option<Price>,
+    pub last_trade_quantity: Option<Quantity>,
+    /// `(price, quantity)` for every price that has traded
+    pub traded_volume: Vec<(Price, Quantity)>,
+    /// Resting orders, level by level in time priority
+    pub orders: Vec<OrderRecord>,
+    /// Dormant stop orders in arrival order
+    pub stop_orders: Vec<OrderRecord>,
+    /// Orders waiting for the next [`uncross`](OrderBook::uncross), in
+    /// arrival order
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub auction_orders: Vec<OrderRecord>,
+}
+
+/// One order as stored in a [`BookSnapshot`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrderRecord {
+    pub order_id: OrderId,
+    pub price: Price,
+    pub quantity: Quantity,
+    /// Part of `quantity` held back in an iceberg reserve
+    pub hidden_quantity: Quantity,
+    pub display_quantity: Quantity,
+    pub side: Side,
+    pub order_type: OrderType,
+    pub timestamp: u64,
+    pub stop_price: Price,
+    pub expires_at: Option<u64>,
+    pub account_id: Option<u64>,
+    pub min_quantity: Quantity,
+    pub reduce_only: bool,
+    pub hidden: bool,
+    pub all_or_none: bool,
+}
+
+impl OrderRecord {
+    /// Rebuild the order this record was taken from
+    pub(crate) fn to_order(self) -> Order {
+        let mut order = Order::new(
+            self.order_id,
+            self.price,
+            self.quantity,
+            self.side,
+            self.order_type,
+        );
+        order.timestamp = self.timestamp;
+        order.stop_price = self.stop_price;
+        order.display_quantity = self.display_quantity;
+        order.expires_at = self.expires_at;
+        order.account_id = self.account_id;
+        order.min_quantity = self.min_quantity;
+        order.hidden_quantity = self.hidden_quantity;
+        if self.reduce_only {
+            order = order.with_reduce_only();
+        }
+        if self.hidden {
+            order = order.with_hidden();
+        }
+        if self.all_or_none {
+            order = order.with_all_or_none();
+        }
+        order
+    }
+}
+
+impl From<&Order> for OrderRecord {
+    fn from(order: &Order) -> Self {
+        Self {
+            order_id: order.order_id,
+            price: order.price,
+            quantity: order.quantity,
+            hidden_quantity: order.hidden_quantity,
+            display_quantity: order.display_quantity,
+            side: order.side(),
+            order_type: order.order_type(),
+            timestamp: order.timestamp,
+            stop_price: order.stop_price,
+            expires_at: order.expires_at,
+            account_id: order.account_id,
+            min_quantity: order.min_quantity,
+            reduce_only: order.reduce_only(),
+            hidden: order.hidden(),
+            all_or_none: order.all_or_none(),
+        }
+    }
+}
+
 /// A summary of the orderbook state
 #[derive(Debug, Clone)]
 pub struct OrderBookSummary {
     pub symbol: String,
-    pub best_bid: Option<u64>,
-    pub best_ask: Option<u64>,
+    /// Integer price units per whole unit, used when displaying prices
+    pub price_scale: u64,
+    /// Book price shown as zero
+    pub price_offset: u64,
+    pub best_bid: Option<Price>,
+    pub best_ask: Option<Price>,
     pub buy_levels: usize,
     pub sell_levels: usize,
-    #[cfg(feature = "perf")]
     pub order_count: usize,
+    pub total_orders: usize,
+    pub bid_orders: usize,
+    pub ask_orders: usize,
     pub total_orders_processed: u64,
-    pub total_quantity_matched: u64,
+    pub total_quantity_matched: Quantity,
+    /// Traded value, price times quantity summed in book price units
+    pub total_notional_matched: u128,
+    pub last_trade_price: Option<Price>,
+    pub last_trade_quantity: Option<Quantity>,
     #[cfg(feature = "perf")]
     pub last_insert_time_ns: u64,
     #[cfg(feature = "perf")]
@@ -896,30 +4271,90 @@ pub struct OrderBookSummary {
     pub last_cancel_time_ns: u64,
 }
 
-impl std::fmt::Display for OrderBookSummary {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+/// Outcome of a market order: what traded and how much went unfilled
+#[derive(Debug, Clone)]
+pub struct MarketOrderResult {
+    pub executions: Vec<Execution>,
+    pub unfilled_quantity: Quantity,
+}
+
+/// Outcome of one [`OrderBook::housekeeping`] tick
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HousekeepingReport {
+    /// Resting orders and dormant stops cancelled at their expiry
+    pub expired: usize,
+    /// Stops that fired, including stop-limits rejected on firing
+    pub triggered: usize,
+    /// Executions the fired stops produced
+    pub executions: usize,
+}
+
+/// Outcome of [`OrderBook::simulate_fill`]: the fills an aggressor would get
+#[derive(Debug, Clone)]
+pub struct SimResult {
+    pub executions: Vec<Execution>,
+    /// Volume weighted price of the executions, `None` if nothing would trade
+    pub average_price: Option<f64>,
+    pub unfilled_quantity: Quantity,
+}
+
+/// Periodic telemetry delivered to a stats callback
+///
+/// Latency percentiles cover the operations since the previous snapshot.
+#[derive(Debug, Clone)]
+pub struct StatsSnapshot {
+    pub summary: OrderBookSummary,
+    pub operations: u64,
+    pub sample_count: usize,
+    pub p50_latency_ns: u64,
+    pub p99_latency_ns: u64,
+    pub max_latency_ns: u64,
+}
+
+impl core::fmt::Display for OrderBookSummary {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         writeln!(f, "OrderBook Summary for {}", self.symbol)?;
         writeln!(f, "----------------------------")?;
 
         if let Some(bid) = self.best_bid {
-            writeln!(f, "Best Bid: {}", bid)?;
+            writeln!(
+                f,
+                "Best Bid: {}",
+                format_price(bid, self.price_offset, self.price_scale)
+            )?;
         } else {
             writeln!(f, "Best Bid: None")?;
         }
 
         if let Some(ask) = self.best_ask {
-            writeln!(f, "Best Ask: {}", ask)?;
+            writeln!(
+                f,
+                "Best Ask: {}",
+                format_price(ask, self.price_offset, self.price_scale)
+            )?;
         } else {
             writeln!(f, "Best Ask: None")?;
         }
 
         writeln!(f, "Buy Levels: {}", self.buy_levels)?;
         writeln!(f, "Sell Levels: {}", self.sell_levels)?;
+        writeln!(
+            f,
+            "Resting Orders: {} ({} bids, {} asks)",
+            self.total_orders, self.bid_orders, self.ask_orders
+        )?;
         writeln!(f, "Processed Orders: {}", self.total_orders_processed)?;
         writeln!(f, "Matched Quantity: {}", self.total_quantity_matched)?;
+        writeln!(f, "Matched Notional: {}", self.total_notional_matched)?;
+
+        if let (Some(price), Some(quantity)) = (self.last_trade_price, self.last_trade_quantity) {
+            let price = format_price(price, self.price_offset, self.price_scale);
+            writeln!(f, "Last Trade: {} @ {}", quantity, price)?;
+        } else {
+            writeln!(f, "Last Trade: None")?;
+        }
         #[cfg(feature = "perf")]
         {
-            writeln!(f, "Total Orders: {}", self.order_count)?;
             writeln!(f, "Last Insert Time: {} ns", self.last_insert_time_ns)?;
             writeln!(f, "Last Match Time: {} ns", self.last_match_time_ns)?;
             writeln!(f, "Last Cancel Time: {} ns", self.last_cancel_time_ns)?;
@@ -0,0 +1,87 @@
+//! Thread-safe handle for sharing one order book between threads
+
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::orderbook::{DepthSide, OrderBook, OrderBookSummary};
+use crate::types::{Execution, Order, OrderError, OrderId, Price};
+
+/// An [`OrderBook`] behind a single reader-writer lock
+///
+/// The whole book is one lock: order entry and cancellation take it
+/// exclusively, while queries share it, so readers always see the book
+/// between two complete operations and never mid-match. Readers and the
+/// writer contend on that one lock, so keep guards from
+/// [`read`](Self::read) and [`write`](Self::write) short-lived.
+///
+/// `SharedOrderBook` is `Send + Sync`; wrap it in an `Arc` to hand it to
+/// several threads. A panic while the write lock is held poisons the book and
+/// makes every later call panic, as its state may be half-updated.
+pub struct SharedOrderBook {
+    book: RwLock<OrderBook>,
+}
+
+impl SharedOrderBook {
+    /// Wrap `book` for shared use
+    pub fn new(book: OrderBook) -> Self {
+        Self {
+            book: RwLock::new(book),
+        }
+    }
+
+    /// Add an order under the write lock
+    pub fn add_order(&self, order: Order) -> Result<Vec<Execution>, OrderError> {
+        self.write().add_order(order)
+    }
+
+    /// Cancel an order under the write lock
+    pub fn cancel_order(&self, order_id: OrderId) -> Result<(), OrderError> {
+        self.write().cancel_order(order_id)
+    }
+
+    /// Get market depth under the read lock
+    pub fn market_depth(&self, levels: usize) -> (DepthSide, DepthSide) {
+        self.read().market_depth(levels)
+    }
+
+    /// Get the best bid price under the read lock
+    pub fn best_bid(&self) -> Option<Price> {
+        self.read().best_bid()
+    }
+
+    /// Get the best ask price under the read lock
+    pub fn best_ask(&self) -> Option<Price> {
+        self.read().best_ask()
+    }
+
+    /// Get summary statistics under the read lock
+    pub fn summary(&self) -> OrderBookSummary {
+        self.read().summary()
+    }
+
+    /// Hold the read lock for several consistent queries
+    pub fn read(&self) -> RwLockReadGuard<'_, OrderBook> {
+        self.book.read().expect("order book lock poisoned")
+    }
+
+    /// Hold the write lock for operations not forwarded here
+    pub fn write(&self) -> RwLockWriteGuard<'_, OrderBook> {
+        self.book.write().expect("order book lock poisoned")
+    }
+
+    /// Unwrap the book
+    pub fn into_inner(self) -> OrderBook {
+        self.book.into_inner().expect("order book lock poisoned")
+    }
+}
+
+impl From<OrderBook> for SharedOrderBook {
+    fn from(book: OrderBook) -> Self {
+        Self::new(book)
+    }
+}
+
+// Compile-time check that the handle can be shared across threads
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SharedOrderBook>();
+};
@@ -1,49 +1,378 @@
 //! Core type definitions for the orderbook implementation
 
-use std::time::Instant;
+use alloc::collections::VecDeque;
+use alloc::string::String;
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+#[cfg(feature = "std")]
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Price in ticks of the book's integer price grid
+///
+/// Widths are set by these three aliases; a narrower type shrinks every
+/// resting order, a wider one raises the largest representable notional.
+pub type Price = u64;
+/// Order and level quantity
+pub type Quantity = u64;
+/// Caller-assigned order identifier
+pub type OrderId = u64;
 
 /// Order side enumeration
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Side {
     Buy,
     Sell,
 }
 
-/// Order type enumeration - simplified to just Limit and Market
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Order type enumeration
+///
+/// Discriminants are the values packed into an order's flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
 pub enum OrderType {
-    Limit,
-    Market,
+    Limit = 0,
+    Market = 1,
+    /// Match as much as possible at the limit price, then drop the remainder
+    ImmediateOrCancel = 2,
+    /// Execute the full quantity immediately or do nothing at all
+    FillOrKill = 3,
+    /// Dormant until the market trades through the stop price, then a market order
+    StopMarket = 4,
+    /// Dormant until the market trades through the stop price, then a limit order
+    StopLimit = 5,
+    /// Rest passively or be rejected - never matches on entry
+    PostOnly = 6,
+    /// Trade against the best opposing level only, limited at its price
+    ///
+    /// That price lies across the base price from the order's own side of the
+    /// grid, so the remainder cannot rest there.
+    /// [`OrderBook::add_market_order`](crate::OrderBook::add_market_order)
+    /// reports it as unfilled, and
+    /// [`OrderBook::add_order`](crate::OrderBook::add_order) refuses an order
+    /// that would leave one.
+    MarketToLimit = 7,
 }
 
+impl OrderType {
+    /// Decode a discriminant, returning `None` for unknown values
+    #[cfg(feature = "std")]
+    pub(crate) fn from_code(code: u8) -> Option<Self> {
+        (code <= OrderType::MarketToLimit as u8).then(|| Self::from_bits(code))
+    }
+
+    #[inline]
+    fn from_bits(bits: u8) -> Self {
+        match bits {
+            0 => OrderType::Limit,
+            1 => OrderType::Market,
+            2 => OrderType::ImmediateOrCancel,
+            3 => OrderType::FillOrKill,
+            4 => OrderType::StopMarket,
+            5 => OrderType::StopLimit,
+            6 => OrderType::PostOnly,
+            7 => OrderType::MarketToLimit,
+            _ => unreachable!("invalid order type bits {}", bits),
+        }
+    }
+}
+
+// Layout of the byte returned by `Order::flags`:
+//
+//   bit 0     side, set for sells
+//   bits 1-3  order type discriminant
+//   bit 4     reduce-only
+//   bit 5     hidden
+//   bit 6     all-or-none
+//   bit 7     reserved, always clear
+
+/// [`Order::flags`] bit set on sell orders and clear on buys
+pub const SELL_FLAG: u8 = 1;
+/// Position of the [`OrderType`] discriminant in [`Order::flags`]
+pub const ORDER_TYPE_SHIFT: u8 = 1;
+/// Mask of the order type bits once shifted down by [`ORDER_TYPE_SHIFT`]
+pub const ORDER_TYPE_MASK: u8 = 0b111;
+/// [`Order::flags`] bit marking a reduce-only order
+pub const REDUCE_ONLY_FLAG: u8 = 1 << 4;
+/// [`Order::flags`] bit marking an order left out of market data
+pub const HIDDEN_FLAG: u8 = 1 << 5;
+/// [`Order::flags`] bit marking an order that only fills in full
+pub const ALL_OR_NONE_FLAG: u8 = 1 << 6;
+/// [`Order::flags`] bits kept free for future attributes
+pub const RESERVED_FLAGS: u8 = 0b1000_0000;
+
 /// Trade execution report
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Execution {
-    pub order_id: u64,
-    pub price: u64,
-    pub quantity: u64,
+    /// The resting order that was hit, same as `maker_order_id`
+    pub order_id: OrderId,
+    pub price: Price,
+    pub quantity: Quantity,
     pub timestamp: u64,
+    /// Side of the resting order
     pub side: Side,
+    /// Book-assigned ID, increasing by one per execution from 1
+    pub trade_id: u64,
+    pub maker_order_id: OrderId,
+    pub taker_order_id: OrderId,
+    /// Side of the incoming order that took liquidity
+    pub aggressor_side: Side,
+}
+
+/// Volume-weighted average price of a batch of executions
+///
+/// Price-quantity products are accumulated in `u128`, so large fills cannot
+/// overflow. Returns `None` when nothing has traded.
+pub fn vwap(executions: &[Execution]) -> Option<f64> {
+    let (notional, volume) = executions
+        .iter()
+        .fold((0u128, 0u128), |(notional, volume), exec| {
+            (
+                notional + exec.price as u128 * exec.quantity as u128,
+                volume + exec.quantity as u128,
+            )
+        });
+    (volume > 0).then(|| notional as f64 / volume as f64)
+}
+
+/// Reasons an order book operation can fail
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderError {
+    /// An order with this ID is already on the book
+    DuplicateOrderId(OrderId),
+    /// The price cannot be mapped to a level on the order's side
+    PriceOutOfRange(Price),
+    /// The price falls between two ticks of the book's grid
+    NotTickAligned(Price),
+    /// No order with this ID is on the book
+    OrderNotFound(OrderId),
+    /// No level exists at this price
+    PriceLevelNotFound(Price),
+    /// The order pool has no free slots
+    PoolExhausted,
+    /// The price level cannot take another order
+    PriceLevelFull,
+    /// The book's kill switch is set
+    Disabled,
+    /// A market-only entry point was given another order type
+    NotMarketOrder(OrderId),
+    /// A stop order was submitted without a stop price
+    MissingStopPrice(OrderId),
+    /// The order would trade against the opposite side when it may not
+    WouldCross(OrderId),
+    /// An execution was priced worse than the aggressor's limit
+    ExecutionThroughLimit {
+        order_id: OrderId,
+        price: Price,
+        side: Side,
+        limit: Price,
+    },
+    /// No book is registered for this symbol
+    UnknownSymbol(String),
+    /// The order has no quantity
+    ZeroQuantity(OrderId),
+    /// A required order field was not set
+    MissingField(&'static str),
+    /// The operation could not be written to the journal
+    Journal(String),
+    /// A reduce-only order's account has no position it could reduce
+    WouldIncreasePosition(OrderId),
+    /// The order's quantity is over the book's per-order limit
+    QuantityTooLarge(OrderId),
+    /// Adding the order would overflow the quantity held at its price level
+    QuantityOverflow(OrderId),
+    /// A market-to-limit order found the opposite side empty
+    NoOpposingPrice(OrderId),
+    /// The order's price * quantity is over the book's per-order limit
+    NotionalTooLarge(OrderId),
+    /// The order is priced further from the reference than the collar allows
+    OutsideCollar(OrderId),
+    /// Only plain limit orders, without fill conditions, join an auction
+    NotAuctionOrder(OrderId),
+    /// A market-to-limit order would leave a remainder it cannot rest, on an
+    /// entry point that cannot report it unfilled
+    UnrestableRemainder(OrderId),
+}
+
+impl core::fmt::Display for OrderError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            OrderError::DuplicateOrderId(id) => write!(f, "Order ID {} already exists", id),
+            OrderError::PriceOutOfRange(price) => {
+                write!(f, "Price {} is outside the allowed range", price)
+            }
+            OrderError::NotTickAligned(price) => {
+                write!(f, "Price {} is not on the tick grid", price)
+            }
+            OrderError::OrderNotFound(id) => write!(f, "Order {} not found", id),
+            OrderError::PriceLevelNotFound(price) => write!(f, "Price level {} not found", price),
+            OrderError::PoolExhausted => write!(f, "Order pool full"),
+            OrderError::PriceLevelFull => write!(f, "Price level full"),
+            OrderError::Disabled => write!(f, "Order book is disabled"),
+            OrderError::NotMarketOrder(id) => write!(f, "Order {} is not a market order", id),
+            OrderError::MissingStopPrice(id) => write!(f, "Stop order {} has no stop price", id),
+            OrderError::WouldCross(id) => write!(f, "Order {} would cross the book", id),
+            OrderError::ExecutionThroughLimit {
+                order_id,
+                price,
+                side,
+                limit,
+            } => write!(
+                f,
+                "Execution for order {} at price {} is worse than {:?} limit {}",
+                order_id, price, side, limit
+            ),
+            OrderError::UnknownSymbol(symbol) => write!(f, "No order book for symbol {}", symbol),
+            OrderError::ZeroQuantity(id) => write!(f, "Order {} has zero quantity", id),
+            OrderError::MissingField(field) => write!(f, "Order is missing {}", field),
+            OrderError::Journal(reason) => write!(f, "Journal write failed: {}", reason),
+            OrderError::WouldIncreasePosition(id) => {
+                write!(f, "Reduce-only order {} would increase its position", id)
+            }
+            OrderError::QuantityTooLarge(id) => {
+                write!(f, "Order {} exceeds the maximum order quantity", id)
+            }
+            OrderError::QuantityOverflow(id) => {
+                write!(f, "Order {} would overflow its price level's quantity", id)
+            }
+            OrderError::NoOpposingPrice(id) => {
+                write!(f, "Order {} has no opposing price to trade at", id)
+            }
+            OrderError::NotionalTooLarge(id) => {
+                write!(f, "Order {} exceeds the maximum order notional", id)
+            }
+            OrderError::OutsideCollar(id) => {
+                write!(f, "Order {} is priced outside the price collar", id)
+            }
+            OrderError::NotAuctionOrder(id) => {
+                write!(f, "Order {} cannot join an auction", id)
+            }
+            OrderError::UnrestableRemainder(id) => {
+                write!(
+                    f,
+                    "Order {} would leave a remainder with nowhere to rest",
+                    id
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for OrderError {}
+
+/// A decoded market-data event for driving a book from an external feed
+#[derive(Clone)]
+pub enum Event {
+    /// A new resting order
+    Add(Order),
+    /// An order was removed in full
+    Cancel { order_id: OrderId },
+    /// An order's price and/or remaining quantity changed
+    Modify {
+        order_id: OrderId,
+        price: Price,
+        quantity: Quantity,
+    },
+    /// A resting order traded `quantity` units
+    Execution {
+        order_id: OrderId,
+        quantity: Quantity,
+    },
+    /// Every order at a price level was removed
+    LevelDelete { side: Side, price: Price },
+}
+
+/// A change to one aggregated price level, as reported to a depth callback
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DepthUpdate {
+    pub side: Side,
+    pub price: Price,
+    /// Visible quantity now at the price; 0 means the level was removed
+    pub quantity: Quantity,
+}
+
+/// A single aggregated price level in a market depth snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DepthLevel {
+    pub price: Price,
+    pub quantity: Quantity,
+    /// Resting orders at the level; 0 when built from a bare
+    /// `(price, quantity)` pair
+    pub order_count: usize,
+}
+
+impl From<(Price, Quantity)> for DepthLevel {
+    #[inline]
+    fn from((price, quantity): (Price, Quantity)) -> Self {
+        Self {
+            price,
+            quantity,
+            order_count: 0,
+        }
+    }
+}
+
+/// Read-only view of a resting order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OrderView {
+    pub order_id: OrderId,
+    pub price: Price,
+    /// Remaining quantity, including any hidden iceberg reserve
+    pub quantity: Quantity,
+    pub side: Side,
+    pub order_type: OrderType,
+    /// Wall-clock entry time in nanoseconds since the Unix epoch
+    pub timestamp: u64,
+    /// Time-priority sequence assigned by the book
+    pub sequence: u64,
+}
+
+impl From<&Order> for OrderView {
+    #[inline]
+    fn from(order: &Order) -> Self {
+        Self {
+            order_id: order.order_id,
+            price: order.price,
+            quantity: order.quantity,
+            side: order.side(),
+            order_type: order.order_type(),
+            timestamp: order.timestamp,
+            sequence: order.sequence,
+        }
+    }
 }
 
 /// Represents an order in the system with minimal memory footprint
-/// Designed for cache-friendly memory layout - 32 bytes total
+/// Designed for cache-friendly memory layout - 112 bytes total with the
+/// default 64-bit [`Price`], [`Quantity`] and [`OrderId`]
 #[derive(Clone)]
 pub struct Order {
-    pub order_id: u64,  // 8 bytes
-    pub price: u64,     // 8 bytes
-    pub quantity: u64,  // 8 bytes
-    pub timestamp: u64, // 8 bytes
+    pub order_id: OrderId,          // 8 bytes
+    pub price: Price,               // 8 bytes
+    pub quantity: Quantity,         // 8 bytes
+    pub timestamp: u64,             // 8 bytes, wall clock - for display, not priority
+    pub stop_price: Price,          // 8 bytes, only meaningful for stop orders
+    pub display_quantity: Quantity, // 8 bytes, iceberg peak size - 0 shows everything
+    pub expires_at: Option<u64>,    // 16 bytes, good-till-date expiry in nanoseconds
+    pub account_id: Option<u64>,    // 16 bytes, owner for self-trade prevention
+    pub min_quantity: Quantity,     // 8 bytes, least a market or IOC order must fill - 0 for none
+    // Iceberg reserve not yet shown in the book, part of `quantity`
+    pub(crate) hidden_quantity: Quantity,
+    // Time priority, assigned by the book each time the order joins a queue
+    pub(crate) sequence: u64,
     // Using bit flags in a single byte to reduce size
-    flags: u8, // 1 byte but padded to align
+    flags: u8, // 1 byte but padded to align, 7 bytes of padding
 }
 
 impl Order {
     #[inline]
     pub fn new(
-        order_id: u64,
-        price: u64,
-        quantity: u64,
+        order_id: OrderId,
+        price: Price,
+        quantity: Quantity,
         side: Side,
         order_type: OrderType,
     ) -> Self {
@@ -51,27 +380,38 @@ impl Order {
 
         // Set the side bit - 0 for buy, 1 for sell
         if side == Side::Sell {
-            flags |= 1;
+            flags |= SELL_FLAG;
         }
 
-        // Set the order type bit (using bit 1)
-        // 0 for Limit, 1 for Market
-        if order_type == OrderType::Market {
-            flags |= 1 << 1;
-        }
+        // Set the order type bits (using bits 1-3)
+        flags |= (order_type as u8) << ORDER_TYPE_SHIFT;
 
         Self {
             order_id,
             price,
             quantity,
-            timestamp: precise_time_ns(), // Using a monotonic timestamp for ordering
+            timestamp: clock_ns(), // Wall-clock entry time; the book assigns priority
+            stop_price: 0,
+            display_quantity: 0,
+            expires_at: None,
+            account_id: None,
+            min_quantity: 0,
+            hidden_quantity: 0,
+            sequence: 0,
             flags,
         }
     }
 
+    /// Set the trigger price of a stop order
+    #[inline]
+    pub fn with_stop_price(mut self, stop_price: Price) -> Self {
+        self.stop_price = stop_price;
+        self
+    }
+
     #[inline]
     pub fn side(&self) -> Side {
-        if self.flags & 1 == 0 {
+        if self.flags & SELL_FLAG == 0 {
             Side::Buy
         } else {
             Side::Sell
@@ -80,76 +420,508 @@ impl Order {
 
     #[inline]
     pub fn order_type(&self) -> OrderType {
-        if (self.flags >> 1) & 1 == 0 {
-            OrderType::Limit
+        OrderType::from_bits((self.flags >> ORDER_TYPE_SHIFT) & ORDER_TYPE_MASK)
+    }
+
+    /// Make this an iceberg showing at most `display_quantity` at a time
+    #[inline]
+    pub fn with_display_quantity(mut self, display_quantity: Quantity) -> Self {
+        self.display_quantity = display_quantity;
+        self
+    }
+
+    /// Make this a good-till-date order expiring at `expires_at` nanoseconds
+    #[inline]
+    pub fn with_expiry(mut self, expires_at: u64) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Require a market or immediate-or-cancel order to fill at least
+    /// `min_quantity` on entry, or else not trade at all
+    #[inline]
+    pub fn with_min_quantity(mut self, min_quantity: Quantity) -> Self {
+        self.min_quantity = min_quantity;
+        self
+    }
+
+    /// Tag the order with the account that owns it
+    #[inline]
+    pub fn with_account(mut self, account_id: u64) -> Self {
+        self.account_id = Some(account_id);
+        self
+    }
+
+    /// Only allow the order to shrink its account's tracked position
+    #[inline]
+    pub fn with_reduce_only(mut self) -> Self {
+        self.flags |= REDUCE_ONLY_FLAG;
+        self
+    }
+
+    #[inline]
+    pub fn reduce_only(&self) -> bool {
+        self.flags & REDUCE_ONLY_FLAG != 0
+    }
+
+    /// Rest without showing in market depth; the order still matches, in
+    /// time priority with the orders around it
+    ///
+    /// A hidden order shows nothing, so any display quantity is ignored.
+    #[inline]
+    pub fn with_hidden(mut self) -> Self {
+        self.flags |= HIDDEN_FLAG;
+        self
+    }
+
+    #[inline]
+    pub fn hidden(&self) -> bool {
+        self.flags & HIDDEN_FLAG != 0
+    }
+
+    /// While resting, only trade with an aggressor that takes the whole
+    /// remaining quantity in one go; smaller aggressors pass it by and match
+    /// the orders behind it
+    ///
+    /// An all-or-none order shows its full size, so any display quantity is
+    /// ignored. The attribute has no effect on the order as an aggressor.
+    #[inline]
+    pub fn with_all_or_none(mut self) -> Self {
+        self.flags |= ALL_OR_NONE_FLAG;
+        self
+    }
+
+    #[inline]
+    pub fn all_or_none(&self) -> bool {
+        self.flags & ALL_OR_NONE_FLAG != 0
+    }
+
+    /// The packed side, order type and attribute bits, laid out as described
+    /// by [`SELL_FLAG`], [`ORDER_TYPE_SHIFT`], [`REDUCE_ONLY_FLAG`],
+    /// [`HIDDEN_FLAG`] and [`ALL_OR_NONE_FLAG`]
+    #[inline]
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    /// Time-priority sequence given by the book when the order last joined a
+    /// queue; 0 until it rests
+    #[inline]
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Quantity currently shown in the book
+    #[inline]
+    pub fn visible_quantity(&self) -> Quantity {
+        self.quantity - self.hidden_quantity
+    }
+
+    /// Split the remaining quantity into a visible peak and a hidden reserve
+    #[inline]
+    pub(crate) fn conceal_reserve(&mut self) {
+        let shows_everything = self.hidden() || self.all_or_none();
+        self.hidden_quantity = if self.display_quantity > 0 && !shows_everything {
+            self.quantity.saturating_sub(self.display_quantity)
         } else {
-            OrderType::Market
-        }
+            0
+        };
+    }
+
+    /// Move the next peak out of the hidden reserve, returning its size
+    #[inline]
+    pub(crate) fn reveal_slice(&mut self) -> Quantity {
+        let slice = core::cmp::min(self.display_quantity, self.hidden_quantity);
+        self.hidden_quantity -= slice;
+        slice
+    }
+
+    /// Replace the order type, keeping the side
+    #[inline]
+    pub(crate) fn set_order_type(&mut self, order_type: OrderType) {
+        self.flags &= !(ORDER_TYPE_MASK << ORDER_TYPE_SHIFT);
+        self.flags |= (order_type as u8) << ORDER_TYPE_SHIFT;
     }
 
     #[inline]
     pub fn is_active(&self) -> bool {
         self.quantity > 0
     }
+
+    /// Start building an order from named fields
+    #[inline]
+    pub fn builder() -> OrderBuilder {
+        OrderBuilder::default()
+    }
+}
+
+/// Named-field alternative to [`Order::new`] that validates the order
+/// before creating it
+#[derive(Debug, Clone, Default)]
+pub struct OrderBuilder {
+    order_id: Option<OrderId>,
+    price: Option<Price>,
+    quantity: Quantity,
+    side: Option<Side>,
+    order_type: Option<OrderType>,
+    stop_price: Option<Price>,
+    display_quantity: Option<Quantity>,
+    expires_at: Option<u64>,
+    account_id: Option<u64>,
+    min_quantity: Quantity,
+    reduce_only: bool,
+    hidden: bool,
+    all_or_none: bool,
+}
+
+impl OrderBuilder {
+    pub fn id(mut self, order_id: OrderId) -> Self {
+        self.order_id = Some(order_id);
+        self
+    }
+
+    pub fn price(mut self, price: Price) -> Self {
+        self.price = Some(price);
+        self
+    }
+
+    pub fn quantity(mut self, quantity: Quantity) -> Self {
+        self.quantity = quantity;
+        self
+    }
+
+    pub fn side(mut self, side: Side) -> Self {
+        self.side = Some(side);
+        self
+    }
+
+    /// Defaults to [`OrderType::Limit`]
+    pub fn order_type(mut self, order_type: OrderType) -> Self {
+        self.order_type = Some(order_type);
+        self
+    }
+
+    pub fn stop_price(mut self, stop_price: Price) -> Self {
+        self.stop_price = Some(stop_price);
+        self
+    }
+
+    pub fn display_quantity(mut self, display_quantity: Quantity) -> Self {
+        self.display_quantity = Some(display_quantity);
+        self
+    }
+
+    pub fn expiry(mut self, expires_at: u64) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    pub fn account(mut self, account_id: u64) -> Self {
+        self.account_id = Some(account_id);
+        self
+    }
+
+    pub fn min_quantity(mut self, min_quantity: Quantity) -> Self {
+        self.min_quantity = min_quantity;
+        self
+    }
+
+    pub fn reduce_only(mut self, reduce_only: bool) -> Self {
+        self.reduce_only = reduce_only;
+        self
+    }
+
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    pub fn all_or_none(mut self, all_or_none: bool) -> Self {
+        self.all_or_none = all_or_none;
+        self
+    }
+
+    /// Check the fields and create the order
+    ///
+    /// The ID and side are required and the quantity must be positive. Every
+    /// type except market, market-to-limit and stop-market orders needs a
+    /// price, and stop orders need a stop price.
+    pub fn build(self) -> Result<Order, OrderError> {
+        let order_id = self.order_id.ok_or(OrderError::MissingField("order_id"))?;
+        let side = self.side.ok_or(OrderError::MissingField("side"))?;
+        let order_type = self.order_type.unwrap_or(OrderType::Limit);
+
+        if self.quantity == 0 {
+            return Err(OrderError::ZeroQuantity(order_id));
+        }
+        let priced = !matches!(
+            order_type,
+            OrderType::Market | OrderType::MarketToLimit | OrderType::StopMarket
+        );
+        if priced && self.price.is_none() {
+            return Err(OrderError::MissingField("price"));
+        }
+        let stop = matches!(order_type, OrderType::StopMarket | OrderType::StopLimit);
+        if stop && self.stop_price.is_none_or(|stop_price| stop_price == 0) {
+            return Err(OrderError::MissingStopPrice(order_id));
+        }
+
+        let mut order = Order::new(
+            order_id,
+            self.price.unwrap_or(0),
+            self.quantity,
+            side,
+            order_type,
+        );
+        order.stop_price = self.stop_price.unwrap_or(0);
+        order.display_quantity = self.display_quantity.unwrap_or(0);
+        order.expires_at = self.expires_at;
+        order.account_id = self.account_id;
+        order.min_quantity = self.min_quantity;
+        if self.reduce_only {
+            order = order.with_reduce_only();
+        }
+        if self.hidden {
+            order = order.with_hidden();
+        }
+        if self.all_or_none {
+            order = order.with_all_or_none();
+        }
+        Ok(order)
+    }
 }
 
 /// Function to get a precise timestamp in nanoseconds
+///
+/// Measured from a process-wide epoch fixed on first use, so values are
+/// monotonic and comparable across orders and executions.
+#[cfg(feature = "std")]
 #[inline]
 pub fn precise_time_ns() -> u64 {
-    let now = Instant::now();
-    let duration = now.elapsed();
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    let duration = EPOCH.get_or_init(Instant::now).elapsed();
     (duration.as_secs() * 1_000_000_000) + duration.subsec_nanos() as u64
 }
 
+/// Wall-clock time in nanoseconds since the Unix epoch
+///
+/// Used to stamp orders and executions for reporting. It can jump when the
+/// system clock is adjusted, so nothing orders by it: time priority comes
+/// from the book's sequence, and latencies from [`precise_time_ns`].
+#[cfg(feature = "std")]
+#[inline]
+pub fn wall_clock_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_nanos() as u64)
+}
+
+/// [`wall_clock_ns`] with `std`; without it there is no clock and this is 0
+#[inline]
+pub(crate) fn clock_ns() -> u64 {
+    #[cfg(feature = "std")]
+    {
+        wall_clock_ns()
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        0
+    }
+}
+
+/// [`precise_time_ns`] with `std`, for measuring latencies; 0 without it
+#[inline]
+pub(crate) fn monotonic_ns() -> u64 {
+    #[cfg(feature = "std")]
+    {
+        precise_time_ns()
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        0
+    }
+}
+
 /// Represents a price level in the order book
 /// Contains all orders at a specific price point
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PriceLevel {
-    pub price: u64,
-    pub total_quantity: u64,
-    pub order_indices: Vec<usize>,
+    pub price: Price,
+    pub total_quantity: Quantity,
+    // Iceberg reserve resting here but not shown in `total_quantity`
+    pub hidden_quantity: Quantity,
+    // Part of `total_quantity` belonging to hidden orders, left out of depth
+    pub dark_quantity: Quantity,
+    // A deque so that fills at the front of a deep queue stay cheap
+    pub order_indices: VecDeque<usize>,
+    // Orders queued behind a full `order_indices`, in time priority
+    spill: VecDeque<usize>,
+    // Maximum length of `order_indices` before new orders spill, if capped
+    inline_capacity: Option<usize>,
 }
 
 impl PriceLevel {
-    pub fn new(price: u64, capacity: usize) -> Self {
+    pub fn new(price: Price, capacity: usize) -> Self {
         Self {
             price,
             total_quantity: 0,
-            order_indices: Vec::with_capacity(capacity),
+            hidden_quantity: 0,
+            dark_quantity: 0,
+            order_indices: VecDeque::with_capacity(capacity),
+            spill: VecDeque::new(),
+            inline_capacity: None,
+        }
+    }
+
+    /// Create a level whose inline queue never grows past `inline_capacity`
+    ///
+    /// Further orders go to a secondary spill queue that is only allocated
+    /// once a level gets that deep.
+    pub fn with_inline_capacity(price: Price, inline_capacity: usize) -> Self {
+        Self {
+            inline_capacity: Some(inline_capacity),
+            ..Self::new(price, inline_capacity)
         }
     }
 
     #[inline]
-    pub fn add_order(&mut self, order_index: usize, quantity: u64) -> bool {
-        self.order_indices.push(order_index);
-        self.total_quantity += quantity;
+    pub fn add_order(&mut self, order_index: usize, quantity: Quantity) -> bool {
+        if self.spill.is_empty() && self.has_inline_room() {
+            self.order_indices.push_back(order_index);
+        } else {
+            self.spill.push_back(order_index);
+        }
+        let total = self.total_quantity.checked_add(quantity);
+        debug_assert!(total.is_some(), "level {} quantity overflowed", self.price);
+        self.total_quantity = total.unwrap_or(Quantity::MAX);
         true
     }
 
+    /// Remove an order index along with the `quantity` it shows
+    ///
+    /// `quantity` should be the order's current visible quantity. The level
+    /// total never goes below zero even if its accounting has drifted from the
+    /// orders; debug builds assert that it has not.
     #[inline]
-    pub fn remove_order(&mut self, order_index: usize, quantity: u64) -> bool {
-        let position = self
+    pub fn remove_order(&mut self, order_index: usize, quantity: Quantity) -> bool {
+        if !self.unlink(order_index) {
+            return false;
+        }
+        debug_assert!(
+            quantity <= self.total_quantity,
+            "removing {} from level {} showing {}",
+            quantity,
+            self.price,
+            self.total_quantity
+        );
+        self.total_quantity = self.total_quantity.saturating_sub(quantity);
+        true
+    }
+
+    /// Remove an order index without touching `total_quantity`, keeping the
+    /// remaining orders in time priority
+    #[inline]
+    pub fn unlink(&mut self, order_index: usize) -> bool {
+        if let Some(pos) = self
             .order_indices
             .iter()
-            .position(|&idx| idx == order_index);
-
-        if let Some(pos) = position {
-            // Remove order from list (swap and pop for O(1) removal)
-            self.order_indices.swap_remove(pos);
-            self.total_quantity -= quantity;
+            .position(|&idx| idx == order_index)
+        {
+            self.order_indices.remove(pos);
+            self.refill();
+            true
+        } else if let Some(pos) = self.spill.iter().position(|&idx| idx == order_index) {
+            self.spill.remove(pos);
             true
         } else {
             false
         }
     }
 
+    /// Remove the order at `position` in time priority without touching
+    /// `total_quantity`, returning its index
+    #[inline]
+    pub fn unlink_at(&mut self, position: usize) -> Option<usize> {
+        if position < self.order_indices.len() {
+            let idx = self.order_indices.remove(position);
+            self.refill();
+            idx
+        } else {
+            self.spill.remove(position - self.order_indices.len())
+        }
+    }
+
+    /// Show a new iceberg slice taken from the hidden reserve, moving the order
+    /// to the back of the queue
+    #[inline]
+    pub fn replenish(&mut self, order_index: usize, slice: Quantity) -> bool {
+        if !self.unlink(order_index) {
+            return false;
+        }
+        self.hidden_quantity -= slice;
+        self.add_order(order_index, slice)
+    }
+
+    /// Quantity shown in market data, `total_quantity` less hidden orders
+    #[inline]
+    pub fn displayed_quantity(&self) -> Quantity {
+        self.total_quantity - self.dark_quantity
+    }
+
+    /// Orders the level's inline queue can hold before it reallocates
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.order_indices.capacity()
+    }
+
+    /// Order index at `position` in time priority
+    #[inline]
+    pub fn get(&self, position: usize) -> Option<usize> {
+        match self.order_indices.get(position) {
+            Some(&idx) => Some(idx),
+            None => self.spill.get(position - self.order_indices.len()).copied(),
+        }
+    }
+
+    /// Iterate order indices in time priority, inline orders before spilled ones
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.order_indices.iter().chain(self.spill.iter()).copied()
+    }
+
     #[inline]
     pub fn is_empty(&self) -> bool {
-        self.order_indices.is_empty()
+        self.order_indices.is_empty() && self.spill.is_empty()
     }
 
     #[inline]
     pub fn order_count(&self) -> usize {
-        self.order_indices.len()
+        self.order_indices.len() + self.spill.len()
+    }
+
+    /// Number of orders currently held in the spill queue
+    #[inline]
+    pub fn spilled_count(&self) -> usize {
+        self.spill.len()
+    }
+
+    #[inline]
+    fn has_inline_room(&self) -> bool {
+        self.inline_capacity
+            .is_none_or(|capacity| self.order_indices.len() < capacity)
+    }
+
+    /// Promote spilled orders into freed inline slots
+    ///
+    /// Spilled orders are all younger than inline ones, so appending them
+    /// keeps the queue in time priority.
+    #[inline]
+    fn refill(&mut self) {
+        while self.has_inline_room() {
+            match self.spill.pop_front() {
+                Some(idx) => self.order_indices.push_back(idx),
+                None => break,
+            }
+        }
     }
 }